@@ -1,46 +1,239 @@
 use crate::db::models::Chunk;
+use crate::FlashgrepResult;
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
 use std::path::PathBuf;
 
-/// Maximum number of lines per chunk
-pub const MAX_CHUNK_LINES: usize = 300;
+/// Default maximum number of lines per chunk
+pub const MAX_CHUNK_LINES: usize = crate::MAX_CHUNK_LINES;
+
+/// Target size in bytes of chunks built from split or streamed lines
+pub const SPLIT_CHUNK_BYTES: usize = 4 * 1024;
+
+/// Maximum width in bytes of one segment of a split line
+pub const SPLIT_LINE_WIDTH: usize = 200;
+
+/// Number of streamed chunks handed to the caller at a time
+const STREAM_BATCH_CHUNKS: usize = 64;
+
+/// How a language's files are split into chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Split at blank lines once brackets are balanced
+    #[default]
+    Brackets,
+    /// Split at every blank line, for languages without block brackets
+    BlankLines,
+    /// Fixed windows of the target size, ignoring the content's structure
+    Lines,
+}
+
+/// Chunk size bounds, in lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkSettings {
+    /// Chunks end at the first natural boundary once they have this many lines
+    pub target_lines: usize,
+    /// Hard limit on a chunk's length, overlap included
+    pub max_lines: usize,
+    /// Lines repeated from the end of the previous chunk at the start of the next,
+    /// so a match at a boundary keeps its context. Minified and streamed files,
+    /// whose chunks hold line segments, do not overlap.
+    pub overlap_lines: usize,
+}
+
+impl ChunkSettings {
+    /// Bring the settings into range: at least one line per chunk, a target no
+    /// larger than the maximum, and an overlap of less than half the maximum
+    pub fn normalized(self) -> Self {
+        let max_lines = self.max_lines.max(1);
+        Self {
+            target_lines: self.target_lines.clamp(1, max_lines),
+            max_lines,
+            overlap_lines: self.overlap_lines.min((max_lines - 1) / 2),
+        }
+    }
+}
+
+impl Default for ChunkSettings {
+    fn default() -> Self {
+        Self {
+            target_lines: crate::TARGET_CHUNK_LINES,
+            max_lines: MAX_CHUNK_LINES,
+            overlap_lines: crate::CHUNK_OVERLAP_LINES,
+        }
+    }
+}
 
 /// Chunks file content into logical blocks
-pub struct Chunker;
+pub struct Chunker {
+    settings: ChunkSettings,
+}
 
 impl Chunker {
-    /// Create a new chunker
+    /// Create a new chunker with the default settings
     pub fn new() -> Self {
-        Self
+        Self::with_settings(ChunkSettings::default())
+    }
+
+    /// Create a chunker with the given settings
+    pub fn with_settings(settings: ChunkSettings) -> Self {
+        Self {
+            settings: settings.normalized(),
+        }
+    }
+
+    /// The normalized settings this chunker splits with
+    pub fn settings(&self) -> ChunkSettings {
+        self.settings
+    }
+
+    /// Longest chunk before overlap is added, so overlapping chunks stay within
+    /// the maximum
+    fn body_lines(&self) -> usize {
+        self.settings.max_lines - self.settings.overlap_lines
     }
 
     /// Chunk a file's content
     pub fn chunk_file(&self, file_path: PathBuf, content: &str, last_modified: i64) -> Vec<Chunk> {
         let lines: Vec<&str> = content.lines().collect();
-        let mut chunks = Vec::new();
+        let mut ends = Vec::new();
         let mut current_start = 0;
 
         while current_start < lines.len() {
-            let (chunk_end, chunk_lines) = self.find_chunk_boundary(&lines, current_start);
+            let chunk_end = self.find_chunk_boundary(&lines, current_start);
+            ends.push(chunk_end);
+            current_start = chunk_end; // Next chunk starts where this one ended
+        }
+
+        self.build_chunks(file_path, &lines, &ends, last_modified)
+    }
+
+    /// Chunk content into fixed windows of the target size
+    pub fn chunk_by_lines(
+        &self,
+        file_path: PathBuf,
+        content: &str,
+        last_modified: i64,
+    ) -> Vec<Chunk> {
+        let lines: Vec<&str> = content.lines().collect();
+        let window = self.settings.target_lines.min(self.body_lines());
+        let ends: Vec<usize> = (1..=lines.len().div_ceil(window))
+            .map(|i| (i * window).min(lines.len()))
+            .collect();
+        self.build_chunks(file_path, &lines, &ends, last_modified)
+    }
 
-            let content = chunk_lines.join("\n");
-            let chunk = Chunk::new(
+    /// Build chunks from the exclusive end index of each chunk, extending each
+    /// chunk after the first back over the overlap
+    fn build_chunks(
+        &self,
+        file_path: PathBuf,
+        lines: &[&str],
+        ends: &[usize],
+        last_modified: i64,
+    ) -> Vec<Chunk> {
+        let mut chunks: Vec<Chunk> = Vec::with_capacity(ends.len());
+        let mut boundary = 0usize;
+        for &end in ends {
+            let start = match chunks.last() {
+                // Never reach back to the previous chunk's own first line, whose
+                // 0-indexed position is one below its 1-indexed `start_line`
+                Some(previous) => boundary
+                    .saturating_sub(self.settings.overlap_lines)
+                    .max(previous.start_line),
+                None => 0,
+            };
+            chunks.push(Chunk::new(
                 file_path.clone(),
-                current_start + 1, // 1-indexed
-                chunk_end,         // 1-indexed, inclusive
-                content,
+                start + 1, // 1-indexed
+                end,       // 1-indexed, inclusive
+                lines[start..end].join("\n"),
                 last_modified,
-            );
+            ));
+            boundary = end;
+        }
+        chunks
+    }
 
-            chunks.push(chunk);
-            current_start = chunk_end; // Next chunk starts where this one ended
+    /// Chunk a file's content with the given strategy
+    pub fn chunk_with_strategy(
+        &self,
+        strategy: ChunkStrategy,
+        file_path: PathBuf,
+        content: &str,
+        last_modified: i64,
+    ) -> Vec<Chunk> {
+        match strategy {
+            ChunkStrategy::Brackets => self.chunk_file(file_path, content, last_modified),
+            ChunkStrategy::BlankLines => {
+                self.chunk_by_blank_lines(file_path, content, last_modified)
+            }
+            ChunkStrategy::Lines => self.chunk_by_lines(file_path, content, last_modified),
         }
+    }
 
+    /// Chunk minified content: long lines are split into segments and grouped
+    /// into size-bounded chunks that keep the real line numbers
+    pub fn chunk_split_lines(
+        &self,
+        file_path: PathBuf,
+        content: &str,
+        last_modified: i64,
+    ) -> Vec<Chunk> {
+        let mut builder = SplitChunkBuilder::new(file_path, last_modified, self.settings.max_lines);
+        let mut chunks = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            builder.push_line(i + 1, line, &mut chunks);
+        }
+        builder.flush(&mut chunks);
         chunks
     }
 
-    /// Find the boundary for the next chunk
-    fn find_chunk_boundary(&self, lines: &[&str], start: usize) -> (usize, Vec<String>) {
-        let max_end = (start + MAX_CHUNK_LINES).min(lines.len());
+    /// Chunk a file without holding it in memory, handing chunks to `on_batch`
+    /// in batches. Long lines are split as in [`Chunker::chunk_split_lines`] and
+    /// invalid UTF-8 is replaced. Returns the number of chunks produced.
+    pub fn stream_chunks<R: BufRead>(
+        &self,
+        mut reader: R,
+        file_path: PathBuf,
+        last_modified: i64,
+        mut on_batch: impl FnMut(Vec<Chunk>) -> FlashgrepResult<()>,
+    ) -> FlashgrepResult<usize> {
+        let mut builder = SplitChunkBuilder::new(file_path, last_modified, self.settings.max_lines);
+        let mut pending = Vec::new();
+        let mut total = 0;
+        let mut buf = Vec::new();
+        let mut line_number = 0;
+
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            line_number += 1;
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+            builder.push_line(line_number, line, &mut pending);
+
+            if pending.len() >= STREAM_BATCH_CHUNKS {
+                total += pending.len();
+                on_batch(std::mem::take(&mut pending))?;
+            }
+        }
+
+        builder.flush(&mut pending);
+        if !pending.is_empty() {
+            total += pending.len();
+            on_batch(pending)?;
+        }
+        Ok(total)
+    }
+
+    /// Find the exclusive end of the chunk starting at `start`: the first blank
+    /// line with balanced brackets once the chunk reaches the target size
+    fn find_chunk_boundary(&self, lines: &[&str], start: usize) -> usize {
+        let max_end = (start + self.body_lines()).min(lines.len());
         let mut end = start;
         let mut bracket_depth = 0;
         let mut last_blank_line = None;
@@ -63,7 +256,10 @@ impl Chunker {
             }
 
             // If bracket depth returns to 0 and we have a blank line, consider splitting
-            if bracket_depth == 0 && last_blank_line == Some(i) {
+            if bracket_depth == 0
+                && last_blank_line == Some(i)
+                && i + 1 - start >= self.settings.target_lines
+            {
                 end = i + 1;
                 break;
             }
@@ -76,9 +272,7 @@ impl Chunker {
             end = max_end;
         }
 
-        let chunk_lines: Vec<String> = lines[start..end].iter().map(|s| s.to_string()).collect();
-
-        (end, chunk_lines)
+        end
     }
 
     /// Split content by blank lines only
@@ -89,45 +283,27 @@ impl Chunker {
         last_modified: i64,
     ) -> Vec<Chunk> {
         let lines: Vec<&str> = content.lines().collect();
-        let mut chunks = Vec::new();
+        let mut ends = Vec::new();
         let mut current_start = 0;
-        let mut current_lines = Vec::new();
 
         for (i, line) in lines.iter().enumerate() {
-            current_lines.push(line.to_string());
+            let length = i + 1 - current_start;
 
-            // Split at blank lines or max size
-            if (line.trim().is_empty() || current_lines.len() >= MAX_CHUNK_LINES)
-                && !current_lines.is_empty()
+            // Split at blank lines once the target is reached, or at max size
+            if (line.trim().is_empty() && length >= self.settings.target_lines)
+                || length >= self.body_lines()
             {
-                let chunk_content = current_lines.join("\n");
-                let chunk = Chunk::new(
-                    file_path.clone(),
-                    current_start + 1,
-                    i + 1,
-                    chunk_content,
-                    last_modified,
-                );
-                chunks.push(chunk);
+                ends.push(i + 1);
                 current_start = i + 1;
-                current_lines.clear();
             }
         }
 
         // Don't forget the last chunk
-        if !current_lines.is_empty() {
-            let chunk_content = current_lines.join("\n");
-            let chunk = Chunk::new(
-                file_path.clone(),
-                current_start + 1,
-                lines.len(),
-                chunk_content,
-                last_modified,
-            );
-            chunks.push(chunk);
+        if current_start < lines.len() {
+            ends.push(lines.len());
         }
 
-        chunks
+        self.build_chunks(file_path, &lines, &ends, last_modified)
     }
 
     /// Check if brackets are balanced in a line range
@@ -146,6 +322,88 @@ impl Chunker {
     }
 }
 
+/// Groups line segments into chunks bounded by [`SPLIT_CHUNK_BYTES`] and a line limit
+struct SplitChunkBuilder {
+    file_path: PathBuf,
+    last_modified: i64,
+    max_lines: usize,
+    segments: Vec<String>,
+    bytes: usize,
+    start_line: usize,
+    end_line: usize,
+}
+
+impl SplitChunkBuilder {
+    fn new(file_path: PathBuf, last_modified: i64, max_lines: usize) -> Self {
+        Self {
+            file_path,
+            last_modified,
+            max_lines,
+            segments: Vec::new(),
+            bytes: 0,
+            start_line: 0,
+            end_line: 0,
+        }
+    }
+
+    fn push_line(&mut self, line_number: usize, line: &str, out: &mut Vec<Chunk>) {
+        for segment in split_line(line, SPLIT_LINE_WIDTH) {
+            if self.segments.is_empty() {
+                self.start_line = line_number;
+            }
+            self.segments.push(segment.to_string());
+            self.bytes += segment.len() + 1;
+            self.end_line = line_number;
+
+            if self.bytes >= SPLIT_CHUNK_BYTES
+                || self.end_line - self.start_line + 1 >= self.max_lines
+            {
+                self.flush(out);
+            }
+        }
+    }
+
+    fn flush(&mut self, out: &mut Vec<Chunk>) {
+        if self.segments.is_empty() {
+            return;
+        }
+        out.push(Chunk::new(
+            self.file_path.clone(),
+            self.start_line,
+            self.end_line,
+            self.segments.join("\n"),
+            self.last_modified,
+        ));
+        self.segments.clear();
+        self.bytes = 0;
+    }
+}
+
+/// Split a line into segments of at most `width` bytes, preferring to break
+/// after whitespace or punctuation in the second half of each segment
+fn split_line(line: &str, width: usize) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut rest = line;
+    while rest.len() > width {
+        let mut end = width;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let window = &rest[..end];
+        let split_at = window
+            .char_indices()
+            .rev()
+            .take_while(|(i, _)| *i >= width / 2)
+            .find(|(_, c)| c.is_whitespace() || matches!(c, ';' | ',' | '{' | '}' | ')' | '>'))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(end);
+        segments.push(&rest[..split_at]);
+        rest = &rest[split_at..];
+    }
+    segments.push(rest);
+    segments
+}
+
 impl Default for Chunker {
     fn default() -> Self {
         Self::new()
@@ -191,6 +449,155 @@ fn other() {
         assert!(chunks.len() >= 2);
     }
 
+    #[test]
+    fn test_chunk_with_strategy() {
+        let chunker = Chunker::new();
+        let content = "build:\n\tcargo build\n\ntest:\n\tcargo test";
+
+        let brackets = chunker.chunk_with_strategy(
+            ChunkStrategy::Brackets,
+            PathBuf::from("Makefile"),
+            content,
+            0,
+        );
+        let blank_lines = chunker.chunk_with_strategy(
+            ChunkStrategy::BlankLines,
+            PathBuf::from("Makefile"),
+            content,
+            0,
+        );
+
+        assert_eq!(blank_lines.len(), 2);
+        assert_eq!(blank_lines[1].start_line, 4);
+        assert!(!brackets.is_empty());
+    }
+
+    #[test]
+    fn test_target_lines_merge_small_blocks() {
+        let content = "fn a() {}\n\nfn b() {}\n\nfn c() {}\n\nfn d() {}";
+        let path = PathBuf::from("lib.rs");
+
+        let per_block = Chunker::new().chunk_file(path.clone(), content, 0);
+        assert_eq!(per_block.len(), 4);
+
+        let chunker = Chunker::with_settings(ChunkSettings {
+            target_lines: 4,
+            ..ChunkSettings::default()
+        });
+        let merged = chunker.chunk_file(path.clone(), content, 0);
+        let ranges: Vec<_> = merged.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, vec![(1, 4), (5, 7)]);
+
+        let blank_lines = chunker.chunk_by_blank_lines(path, content, 0);
+        assert_eq!(blank_lines.len(), 2);
+    }
+
+    #[test]
+    fn test_overlap_repeats_boundary_lines_within_max() {
+        let content = (1..=20)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunker = Chunker::with_settings(ChunkSettings {
+            target_lines: 6,
+            max_lines: 8,
+            overlap_lines: 2,
+        });
+
+        let chunks = chunker.chunk_with_strategy(
+            ChunkStrategy::Lines,
+            PathBuf::from("data.txt"),
+            &content,
+            0,
+        );
+        let ranges: Vec<_> = chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, vec![(1, 6), (5, 12), (11, 18), (17, 20)]);
+        assert!(chunks[1].content.starts_with("line 5\nline 6\nline 7"));
+
+        // Boundary-less content is cut so that the overlap still fits in the maximum
+        let brackets = chunker.chunk_file(PathBuf::from("data.txt"), &content, 0);
+        assert!(brackets.iter().all(|c| c.end_line - c.start_line < 8));
+        assert_eq!(brackets[1].start_line, brackets[0].end_line - 1);
+        assert_eq!(brackets.last().unwrap().end_line, 20);
+    }
+
+    #[test]
+    fn test_chunk_settings_normalize_into_range() {
+        let settings = ChunkSettings {
+            target_lines: 0,
+            max_lines: 0,
+            overlap_lines: 10,
+        }
+        .normalized();
+        assert_eq!(
+            settings,
+            ChunkSettings {
+                target_lines: 1,
+                max_lines: 1,
+                overlap_lines: 0,
+            }
+        );
+        let settings = ChunkSettings {
+            target_lines: 500,
+            max_lines: 100,
+            overlap_lines: 80,
+        }
+        .normalized();
+        assert_eq!(settings.target_lines, 100);
+        assert_eq!(settings.overlap_lines, 49);
+    }
+
+    #[test]
+    fn test_chunk_split_lines_keeps_line_numbers() {
+        let chunker = Chunker::new();
+        let minified = format!("header\n{}\nfooter", "var a=1;".repeat(2000));
+
+        let chunks = chunker.chunk_split_lines(PathBuf::from("app.min.js"), &minified, 0);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks
+            .iter()
+            .all(|c| c.content.len() <= SPLIT_CHUNK_BYTES + SPLIT_LINE_WIDTH));
+        assert!(chunks
+            .iter()
+            .all(|c| c.content.lines().all(|l| l.len() <= SPLIT_LINE_WIDTH)));
+        assert_eq!(chunks[0].start_line, 1);
+        assert_eq!(chunks[1].start_line, 2);
+        assert_eq!(chunks.last().unwrap().end_line, 3);
+    }
+
+    #[test]
+    fn test_stream_chunks_matches_split_lines() {
+        let chunker = Chunker::new();
+        let content = "INSERT INTO t VALUES (1, 'a');\n".repeat(2000);
+        let mut streamed = Vec::new();
+
+        let total = chunker
+            .stream_chunks(content.as_bytes(), PathBuf::from("dump.sql"), 0, |batch| {
+                streamed.extend(batch);
+                Ok(())
+            })
+            .unwrap();
+
+        let expected = chunker.chunk_split_lines(PathBuf::from("dump.sql"), &content, 0);
+        assert_eq!(total, streamed.len());
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_split_line_prefers_token_boundaries() {
+        let line = "alpha beta;".repeat(40);
+        let segments = split_line(&line, 50);
+        assert_eq!(segments.concat(), line);
+        assert!(segments[..segments.len() - 1]
+            .iter()
+            .all(|s| s.ends_with(' ') || s.ends_with(';')));
+        assert_eq!(
+            split_line("é".repeat(30).as_str(), 7).concat(),
+            "é".repeat(30)
+        );
+    }
+
     #[test]
     fn test_bracket_balanced() {
         let lines = vec!["fn main() {", "    println!();", "}"];
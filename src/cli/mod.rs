@@ -2,8 +2,12 @@ mod fs;
 
 use crate::config::paths::{get_repo_root, FlashgrepPaths};
 use crate::config::Config;
-use crate::db::Database;
+use crate::db::models::{BlameInfo, ChunkLocation, LineRange};
+use crate::index::doctor::DoctorReport;
 use crate::index::engine::Indexer;
+use crate::index::history::search_history;
+use crate::index::schema::open_text_index;
+use crate::index::stats::DetailedStats;
 use crate::mcp::stdio::McpStdioServer;
 use crate::neural::ensure_neural_config_prompt;
 use crate::path_utils::{normalize_glob_pattern, normalize_path_for_matching};
@@ -13,6 +17,7 @@ use crate::watcher::FileWatcher;
 use crate::FlashgrepResult;
 use clap::{Parser, Subcommand, ValueEnum};
 use serde::Serialize;
+use std::collections::HashSet;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -20,6 +25,9 @@ use tracing::info;
 
 use self::fs::{handle_fs_command, FsCommands};
 
+/// Number of skipped files listed by `stats` before the rest are summarized
+const SKIPPED_FILES_PREVIEW: usize = 10;
+
 /// Flashgrep CLI
 #[derive(Parser)]
 #[command(name = "flashgrep")]
@@ -41,6 +49,7 @@ pub enum QueryModeArg {
     Smart,
     Literal,
     Regex,
+    Structured,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -64,6 +73,7 @@ impl From<QueryModeArg> for QueryMode {
             QueryModeArg::Smart => QueryMode::Smart,
             QueryModeArg::Literal => QueryMode::Literal,
             QueryModeArg::Regex => QueryMode::Regex,
+            QueryModeArg::Structured => QueryMode::Structured,
         }
     }
 }
@@ -84,6 +94,8 @@ pub enum FileSortOrder {
 pub enum RunOutcome {
     Success,
     NoMatch,
+    /// `doctor` found inconsistencies that were not repaired
+    Unhealthy,
 }
 
 impl RunOutcome {
@@ -91,6 +103,7 @@ impl RunOutcome {
         match self {
             RunOutcome::Success => 0,
             RunOutcome::NoMatch => 1,
+            RunOutcome::Unhealthy => 1,
         }
     }
 }
@@ -112,6 +125,12 @@ struct CliResult {
     match_text: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     content: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    changed_hunks: Vec<LineRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blame: Option<BlameInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    also_in: Vec<ChunkLocation>,
 }
 
 #[derive(Subcommand)]
@@ -126,6 +145,9 @@ pub enum Commands {
         /// Force full re-index (ignore existing index)
         #[arg(short, long)]
         force: bool,
+        /// Index a git branch, tag or commit from the object database instead of the working tree
+        #[arg(long = "rev", value_name = "REV")]
+        rev: Option<String>,
     },
     /// Start file watcher only
     Start {
@@ -157,7 +179,7 @@ pub enum Commands {
         /// Maximum number of results
         #[arg(short, long, default_value_t = 20)]
         limit: usize,
-        /// Query mode
+        /// Query mode; `structured` accepts path:, lang:, sym:, case:, "phrases" and -exclusions
         #[arg(long, value_enum, default_value_t = QueryModeArg::Smart)]
         mode: QueryModeArg,
         /// Retrieval mode
@@ -172,12 +194,24 @@ pub enum Commands {
         /// Exclude path glob filter (repeatable)
         #[arg(long = "exclude")]
         exclude: Vec<String>,
+        /// Language filter (ids or aliases, comma-separated or repeatable, e.g. rust,go)
+        #[arg(long = "lang", value_delimiter = ',')]
+        languages: Vec<String>,
         /// Number of context lines around each match
         #[arg(short = 'C', long = "context", default_value_t = 0)]
         context: usize,
         /// Offset for deterministic continuation windows
         #[arg(long, default_value_t = 0)]
         offset: usize,
+        /// Search an indexed git revision instead of the working tree
+        #[arg(long = "rev", value_name = "REV")]
+        rev: Option<String>,
+        /// Only search files changed since REF forked off HEAD (HEAD: uncommitted changes)
+        #[arg(long = "changed-since", value_name = "REF")]
+        changed_since: Option<String>,
+        /// Attach the last commit (hash, author, date, summary) touching each result's lines
+        #[arg(long = "with-blame")]
+        with_blame: bool,
         /// Output format
         #[arg(long, value_enum, default_value_t = OutputMode::Text)]
         output: OutputMode,
@@ -233,6 +267,9 @@ pub enum Commands {
         /// File extension filters (e.g. rs or .rs)
         #[arg(long = "ext")]
         extensions: Vec<String>,
+        /// Language filter (ids or aliases, comma-separated or repeatable, e.g. rust,go)
+        #[arg(long = "lang", value_delimiter = ',')]
+        languages: Vec<String>,
         /// Include hidden paths
         #[arg(long = "include-hidden")]
         include_hidden: bool,
@@ -268,6 +305,23 @@ pub enum Commands {
         /// Maximum number of results
         #[arg(short, long, default_value_t = 50)]
         limit: usize,
+        /// Language filter (ids or aliases, comma-separated or repeatable, e.g. rust,go)
+        #[arg(long = "lang", value_delimiter = ',')]
+        languages: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputMode::Text)]
+        output: OutputMode,
+    },
+    /// Search commit messages, authors and changed paths in git history
+    LogSearch {
+        /// Search text, e.g. "retry logic"
+        query: String,
+        /// Path to the repository (defaults to current directory)
+        #[arg(value_name = "PATH")]
+        path: Option<PathBuf>,
+        /// Maximum number of commits
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
         /// Output format
         #[arg(long, value_enum, default_value_t = OutputMode::Text)]
         output: OutputMode,
@@ -310,6 +364,30 @@ pub enum Commands {
         /// Path to the repository (defaults to current directory)
         #[arg(value_name = "PATH")]
         path: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputMode::Text)]
+        output: OutputMode,
+    },
+    /// Check the index for inconsistencies between its stores and the filesystem
+    Doctor {
+        /// Path to the repository (defaults to current directory)
+        #[arg(value_name = "PATH")]
+        path: Option<PathBuf>,
+        /// Repair the inconsistencies found, file by file
+        #[arg(long)]
+        fix: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputMode::Text)]
+        output: OutputMode,
+    },
+    /// Reclaim space in the index and report how much was saved
+    Compact {
+        /// Path to the repository (defaults to current directory)
+        #[arg(value_name = "PATH")]
+        path: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputMode::Text)]
+        output: OutputMode,
     },
     /// Clear the index for a repository
     Clear {
@@ -334,7 +412,7 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             print_version_info();
             Ok(RunOutcome::Success)
         }
-        Commands::Index { path, force } => {
+        Commands::Index { path, force, rev } => {
             let repo_root = get_repo_root(path.as_deref())?;
             info!("Indexing repository: {}", repo_root.display());
 
@@ -349,6 +427,16 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
 
             let mut indexer = Indexer::new(repo_root.clone())?;
 
+            if let Some(rev) = rev {
+                let summary = indexer.index_revision(&rev)?;
+                println!("\n✓ Revision indexed!");
+                println!("  Revision: {} ({})", summary.name, summary.commit_id);
+                println!("  Files indexed: {}", summary.files);
+                println!("  Chunks created: {}", summary.chunks);
+                println!("  Files skipped: {}", summary.skipped);
+                return Ok(RunOutcome::Success);
+            }
+
             if force {
                 println!("Force re-indexing...");
                 indexer.clear_index()?;
@@ -483,8 +571,12 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             ignore_case,
             include,
             exclude,
+            languages,
             context,
             offset,
+            rev,
+            changed_since,
+            with_blame,
             output,
         } => {
             let (repo_root, searcher) = create_searcher(path.as_deref())?;
@@ -498,8 +590,12 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             options.case_sensitive = !ignore_case;
             options.include = include;
             options.exclude = exclude;
+            options.languages = languages;
             options.context = context;
             options.offset = offset;
+            options.revision = rev;
+            options.changed_since = changed_since;
+            options.with_blame = with_blame;
 
             let query_response = searcher.query_with_options(&options)?;
             let mut results = query_response.results;
@@ -523,6 +619,9 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
                     preview: Some(r.preview.clone()),
                     match_text: r.preview.lines().next().map(|s| s.to_string()),
                     content: r.content,
+                    changed_hunks: r.changed_hunks,
+                    blame: r.blame,
+                    also_in: r.also_in,
                 })
                 .collect();
 
@@ -530,8 +629,13 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
                 &rendered,
                 output,
                 &format!(
-                    "query in {} (truncated={}, scanned_files={})",
+                    "query in {}{} (truncated={}, scanned_files={})",
                     repo_root.display(),
+                    options
+                        .revision
+                        .as_ref()
+                        .map(|rev| format!(" at {}", rev))
+                        .unwrap_or_default(),
                     query_response.truncated,
                     query_response.scanned_files
                 ),
@@ -653,6 +757,7 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             include,
             exclude,
             extensions,
+            languages,
             include_hidden,
             max_depth,
             sort_by,
@@ -664,6 +769,9 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
         } => {
             let (repo_root, searcher) = create_searcher(path.as_deref())?;
             let mut files = searcher.list_files()?;
+            if let Some(language_files) = searcher.language_files(&languages)? {
+                files.retain(|p| language_files.contains(p));
+            }
 
             let mut includes = include;
             if !pattern.trim().is_empty() {
@@ -748,6 +856,9 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
                     preview: None,
                     match_text: None,
                     content: None,
+                    changed_hunks: Vec::new(),
+                    blame: None,
+                    also_in: Vec::new(),
                 })
                 .collect();
 
@@ -767,10 +878,14 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             symbol_name,
             path,
             limit,
+            languages,
             output,
         } => {
             let (repo_root, searcher) = create_searcher(path.as_deref())?;
             let mut symbols = searcher.get_symbol(&symbol_name)?;
+            if let Some(language_files) = searcher.language_files(&languages)? {
+                symbols.retain(|s| language_files.contains(&s.file_path));
+            }
             symbols.sort_by(|a, b| {
                 a.file_path
                     .cmp(&b.file_path)
@@ -790,6 +905,9 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
                     preview: Some(format!("type={}", s.symbol_type)),
                     match_text: None,
                     content: None,
+                    changed_hunks: Vec::new(),
+                    blame: None,
+                    also_in: Vec::new(),
                 })
                 .collect();
 
@@ -840,11 +958,62 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
                 preview: None,
                 match_text: None,
                 content: Some(content),
+                changed_hunks: Vec::new(),
+                blame: None,
+                also_in: Vec::new(),
             }];
             render_results(&rendered, output, "slice")?;
             Ok(RunOutcome::Success)
         }
-        Commands::Stats { path } => {
+        Commands::LogSearch {
+            query,
+            path,
+            limit,
+            output,
+        } => {
+            let repo_root = get_repo_root(path.as_deref())?;
+            let (update, hits) = search_history(&FlashgrepPaths::new(&repo_root), &query, limit)?;
+
+            if output == OutputMode::Json {
+                let payload = serde_json::json!({
+                    "query": query,
+                    "head": update.head,
+                    "added_commits": update.added_commits,
+                    "stale": update.stale,
+                    "commits": hits,
+                });
+                println!("{}", serde_json::to_string_pretty(&payload)?);
+            } else {
+                if update.stale {
+                    println!("⚠ History index is being updated by another process; newest commits may be missing");
+                }
+                if hits.is_empty() {
+                    println!("No commits found");
+                }
+                for hit in &hits {
+                    let date = chrono::DateTime::from_timestamp(hit.date, 0)
+                        .map(|date| date.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "{} {} {}  {}",
+                        &hit.commit_id[..hit.commit_id.len().min(12)],
+                        date,
+                        hit.author,
+                        hit.summary
+                    );
+                    for file in &hit.indexed_files {
+                        println!("  {}", file.display());
+                    }
+                }
+            }
+
+            if hits.is_empty() {
+                Ok(RunOutcome::NoMatch)
+            } else {
+                Ok(RunOutcome::Success)
+            }
+        }
+        Commands::Stats { path, output } => {
             let repo_root = get_repo_root(path.as_deref())?;
 
             if !FlashgrepPaths::new(&repo_root).exists() {
@@ -853,22 +1022,190 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             }
 
             let paths = FlashgrepPaths::new(&repo_root);
-            let db = Database::open(&paths.metadata_db())?;
-            let stats = db.get_stats()?;
+            let stats = DetailedStats::collect(&paths)?;
+
+            if output == OutputMode::Json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+                return Ok(RunOutcome::Success);
+            }
 
             println!("\n📊 Index Statistics");
             println!("==================");
-            println!("  Total files: {}", stats.total_files);
-            println!("  Total chunks: {}", stats.total_chunks);
-            println!("  Total symbols: {}", stats.total_symbols);
-            println!("  Index size: {} MB", stats.index_size_bytes / 1024 / 1024);
-            if let Some(last_update) = stats.last_update {
+            println!("  Total files: {}", stats.totals.total_files);
+            println!("  Total chunks: {}", stats.totals.total_chunks);
+            println!("  Unique chunk bodies: {}", stats.totals.unique_chunks);
+            println!("  Total symbols: {}", stats.totals.total_symbols);
+            println!(
+                "  Index size: {} MB",
+                stats.totals.index_size_bytes / 1024 / 1024
+            );
+            if let Some(last_update) = stats.totals.last_update {
                 let datetime = chrono::DateTime::from_timestamp(last_update, 0);
                 if let Some(dt) = datetime {
                     println!("  Last update: {}", dt.format("%Y-%m-%d %H:%M:%S"));
                 }
             }
+            if stats.tantivy.available {
+                println!(
+                    "  Text index: {} documents in {} segments",
+                    stats.tantivy.documents, stats.tantivy.segments
+                );
+            }
+
+            println!("\n  By language:");
+            for (language, entry) in &stats.by_language {
+                println!(
+                    "    {:<14} {:>6} files {:>8} chunks",
+                    language, entry.files, entry.chunks
+                );
+            }
+            println!("\n  By directory:");
+            for (directory, entry) in &stats.by_directory {
+                println!(
+                    "    {:<14} {:>6} files {:>8} chunks",
+                    directory, entry.files, entry.chunks
+                );
+            }
+            if !stats.by_symbol_type.is_empty() {
+                println!("\n  By symbol type:");
+                for (symbol_type, count) in &stats.by_symbol_type {
+                    println!("    {:<14} {:>6}", symbol_type, count);
+                }
+            }
+            println!("\n  Chunk sizes (lines):");
+            for bucket in &stats.chunk_size_histogram {
+                let range = match bucket.max_lines {
+                    Some(max) => format!("{}-{}", bucket.min_lines, max),
+                    None => format!("{}+", bucket.min_lines),
+                };
+                println!("    {:<14} {:>6}", range, bucket.chunks);
+            }
+            if !stats.vectors.is_empty() {
+                println!("\n  Vector coverage:");
+                for vectors in &stats.vectors {
+                    println!(
+                        "    {:<14} {:>6} vectors ({:.1}%)",
+                        vectors.model_id,
+                        vectors.vectors,
+                        vectors.coverage * 100.0
+                    );
+                }
+            }
+            if !stats.skipped_files.is_empty() {
+                println!("\n  Skipped files:");
+                for (reason, count) in &stats.skipped_by_reason {
+                    println!("    {:<14} {:>6}", reason, count);
+                }
+                for skipped in stats.skipped_files.iter().take(SKIPPED_FILES_PREVIEW) {
+                    println!("    {} ({})", skipped.file_path.display(), skipped.reason);
+                }
+                if stats.skipped_files.len() > SKIPPED_FILES_PREVIEW {
+                    println!(
+                        "    ... and {} more",
+                        stats.skipped_files.len() - SKIPPED_FILES_PREVIEW
+                    );
+                }
+            }
+            if !stats.revisions.is_empty() {
+                println!("\n  Indexed revisions:");
+                for revision in &stats.revisions {
+                    println!(
+                        "    {:<14} {} ({} files, {} chunks)",
+                        revision.name,
+                        &revision.commit_id[..revision.commit_id.len().min(12)],
+                        revision.file_count,
+                        revision.chunk_count
+                    );
+                }
+            }
+
+            Ok(RunOutcome::Success)
+        }
+        Commands::Doctor { path, fix, output } => {
+            let repo_root = get_repo_root(path.as_deref())?;
+
+            if !FlashgrepPaths::new(&repo_root).exists() {
+                println!("⚠ No index found. Run 'flashgrep index' first.");
+                return Ok(RunOutcome::Success);
+            }
+
+            let report = DoctorReport::check(&repo_root)?;
+            let (repairs, remaining) = if fix && !report.is_healthy() {
+                let mut indexer = Indexer::new(repo_root.clone())?;
+                let repairs = report.repair(&mut indexer)?;
+                drop(indexer);
+                (Some(repairs), DoctorReport::check(&repo_root)?)
+            } else {
+                (None, report.clone())
+            };
+
+            if output == OutputMode::Json {
+                let payload = serde_json::json!({
+                    "healthy": remaining.is_healthy(),
+                    "report": report,
+                    "repairs": repairs,
+                    "remaining_issues": remaining.issue_count(),
+                });
+                println!("{}", serde_json::to_string_pretty(&payload)?);
+            } else {
+                print_doctor_report(&report);
+                if let Some(repairs) = &repairs {
+                    println!("\n🔧 Repairs");
+                    println!("  Removed missing files: {}", repairs.removed_files);
+                    println!(
+                        "  Removed orphaned text documents: {}",
+                        repairs.removed_documents
+                    );
+                    println!("  Reindexed files: {}", repairs.reindexed_files);
+                    println!("  Removed orphaned rows: {}", repairs.removed_rows.total());
+                    println!(
+                        "  Removed stale state entries: {}",
+                        repairs.removed_state_entries
+                    );
+                    for failed in &repairs.failed_files {
+                        println!("  ✗ Failed to reindex {}", failed.display());
+                    }
+                }
+                if remaining.is_healthy() {
+                    println!("\n✓ Index is consistent");
+                } else if fix {
+                    println!("\n⚠ {} issue(s) remain", remaining.issue_count());
+                } else {
+                    println!("\nRun 'flashgrep doctor --fix' to repair.");
+                }
+            }
+
+            if remaining.is_healthy() {
+                Ok(RunOutcome::Success)
+            } else {
+                Ok(RunOutcome::Unhealthy)
+            }
+        }
+        Commands::Compact { path, output } => {
+            let repo_root = get_repo_root(path.as_deref())?;
+
+            if !FlashgrepPaths::new(&repo_root).exists() {
+                println!("⚠ No index found. Run 'flashgrep index' first.");
+                return Ok(RunOutcome::Success);
+            }
+
+            let mut indexer = Indexer::new(repo_root)?;
+            let report = indexer.compact()?;
+            drop(indexer);
 
+            if output == OutputMode::Json {
+                let mut payload = serde_json::to_value(&report)?;
+                payload["saved_bytes"] = serde_json::json!(report.saved_bytes());
+                println!("{}", serde_json::to_string_pretty(&payload)?);
+            } else {
+                println!("\n🗜 Index Compaction");
+                println!("===================");
+                println!("  Before: {}", format_bytes(report.bytes_before));
+                println!("  After:  {}", format_bytes(report.bytes_after));
+                println!("  Compressed chunk bodies: {}", report.compressed_bodies);
+                println!("  Removed orphaned rows: {}", report.removed_rows.total());
+                println!("\n✓ Saved {}", format_bytes(report.saved_bytes()));
+            }
             Ok(RunOutcome::Success)
         }
         Commands::Mcp {
@@ -948,8 +1285,15 @@ pub async fn run() -> FlashgrepResult<RunOutcome> {
             std::io::stdin().read_line(&mut input)?;
 
             if input.trim().eq_ignore_ascii_case("y") {
-                let mut indexer = Indexer::new(repo_root)?;
-                indexer.clear_index()?;
+                match Indexer::new(repo_root.clone()) {
+                    Ok(mut indexer) => indexer.clear_index()?,
+                    // Stores this build cannot open are deleted instead of cleared
+                    Err(crate::FlashgrepError::RebuildRequired(reason)) => {
+                        println!("⚠ Deleting index stores: {}", reason);
+                        FlashgrepPaths::new(&repo_root).remove_index_stores()?;
+                    }
+                    Err(err) => return Err(err),
+                }
                 println!("✓ Index cleared");
             } else {
                 println!("Cancelled");
@@ -1032,11 +1376,75 @@ fn create_searcher(path: Option<&Path>) -> FlashgrepResult<(PathBuf, Searcher)>
         )));
     }
 
-    let index = tantivy::Index::open_in_dir(paths.text_index_dir())?;
+    let index = open_text_index(&paths.text_index_dir())?;
     let searcher = Searcher::new(&index, &paths.metadata_db())?;
     Ok((repo_root, searcher))
 }
 
+/// Human-readable size in binary units
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn print_doctor_report(report: &DoctorReport) {
+    println!("\n🩺 Index Doctor");
+    println!("==============");
+    println!("  Missing files: {}", report.missing_files.len());
+    for path in &report.missing_files {
+        println!("    {}", path.display());
+    }
+    println!(
+        "  Orphaned text documents: {}",
+        report.orphaned_documents.len()
+    );
+    for path in &report.orphaned_documents {
+        println!("    {}", path.display());
+    }
+    println!(
+        "  Chunk/document mismatches: {}",
+        report.document_mismatches.len()
+    );
+    for mismatch in &report.document_mismatches {
+        println!(
+            "    {} ({} chunks, {} documents)",
+            mismatch.file_path.display(),
+            mismatch.chunks,
+            mismatch.documents
+        );
+    }
+    println!(
+        "  Orphaned rows: {} chunks, {} symbols, {} vectors",
+        report.orphaned_rows.chunks, report.orphaned_rows.symbols, report.orphaned_rows.vectors
+    );
+    println!(
+        "  Stale index state entries: {}",
+        report.stale_state_entries.len()
+    );
+    println!("  Stale skip records: {}", report.stale_skip_records.len());
+    for path in &report.stale_skip_records {
+        println!("    {}", path.display());
+    }
+    println!("  Skipped files: {}", report.skipped_files.len());
+    for skipped in &report.skipped_files {
+        println!(
+            "    {} ({}, {} bytes)",
+            skipped.file_path.display(),
+            skipped.reason,
+            skipped.file_size
+        );
+    }
+}
+
 fn render_results(results: &[CliResult], output: OutputMode, label: &str) -> FlashgrepResult<()> {
     match output {
         OutputMode::Json => {
@@ -1055,7 +1463,46 @@ fn render_results(results: &[CliResult], output: OutputMode, label: &str) -> Fla
                 if let Some(score) = r.relevance_score {
                     line = format!("{} score={:.3}", line, score);
                 }
+                if !r.changed_hunks.is_empty() {
+                    let hunks: Vec<String> = r
+                        .changed_hunks
+                        .iter()
+                        .map(|h| format!("{}-{}", h.start_line, h.end_line))
+                        .collect();
+                    line = format!("{} changed={}", line, hunks.join(","));
+                }
                 println!("- {}", line);
+                if let Some(blame) = &r.blame {
+                    let date = chrono::DateTime::from_timestamp(blame.date, 0)
+                        .map(|d| d.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "  blame: {} {} {} {}",
+                        &blame.commit_id[..blame.commit_id.len().min(12)],
+                        blame.author,
+                        date,
+                        blame.summary
+                    );
+                }
+                if !r.also_in.is_empty() {
+                    let files: HashSet<&PathBuf> = r
+                        .also_in
+                        .iter()
+                        .map(|location| &location.file_path)
+                        .collect();
+                    let locations: Vec<String> = r
+                        .also_in
+                        .iter()
+                        .map(|l| {
+                            format!("{}:{}-{}", l.file_path.display(), l.start_line, l.end_line)
+                        })
+                        .collect();
+                    println!(
+                        "  also appears in {} file(s): {}",
+                        files.len(),
+                        locations.join(", ")
+                    );
+                }
                 if let Some(preview) = &r.preview {
                     println!("  {}", preview.replace('\n', "\\n"));
                 }
@@ -1188,6 +1635,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_log_search_command() {
+        let cli = Cli::parse_from(["flashgrep", "log-search", "retry logic", "--limit", "5"]);
+        match cli.command {
+            Commands::LogSearch { query, limit, .. } => {
+                assert_eq!(query, "retry logic");
+                assert_eq!(limit, 5);
+            }
+            _ => panic!("expected log-search command"),
+        }
+    }
+
     #[test]
     fn background_spawn_failure_is_reported() {
         let bad_exe = std::path::PathBuf::from("definitely_missing_flashgrep_binary");
@@ -1216,6 +1675,102 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_index_and_query_with_revision() {
+        let cli = Cli::parse_from(["flashgrep", "index", "--rev", "main"]);
+        match cli.command {
+            Commands::Index { rev, .. } => assert_eq!(rev.as_deref(), Some("main")),
+            _ => panic!("expected index command"),
+        }
+        let cli = Cli::parse_from(["flashgrep", "query", "main", "--rev", "v1.0"]);
+        match cli.command {
+            Commands::Query { rev, .. } => assert_eq!(rev.as_deref(), Some("v1.0")),
+            _ => panic!("expected query command"),
+        }
+    }
+
+    #[test]
+    fn parse_query_with_blame() {
+        let cli = Cli::parse_from(["flashgrep", "query", "main", "--with-blame"]);
+        match cli.command {
+            Commands::Query { with_blame, .. } => assert!(with_blame),
+            _ => panic!("expected query command"),
+        }
+    }
+
+    #[test]
+    fn parse_query_with_changed_since() {
+        let cli = Cli::parse_from(["flashgrep", "query", "main", "--changed-since", "main"]);
+        match cli.command {
+            Commands::Query { changed_since, .. } => {
+                assert_eq!(changed_since.as_deref(), Some("main"))
+            }
+            _ => panic!("expected query command"),
+        }
+    }
+
+    #[test]
+    fn parse_stats_with_json_output() {
+        let cli = Cli::parse_from(["flashgrep", "stats", "--output", "json"]);
+        match cli.command {
+            Commands::Stats { path, output } => {
+                assert!(path.is_none());
+                assert_eq!(output, OutputMode::Json);
+            }
+            _ => panic!("expected stats command"),
+        }
+    }
+
+    #[test]
+    fn parse_lang_filter_lists() {
+        let cli = Cli::parse_from([
+            "flashgrep",
+            "symbol",
+            "main",
+            "--lang",
+            "rust,go",
+            "--lang",
+            "shell",
+        ]);
+        match cli.command {
+            Commands::Symbol { languages, .. } => {
+                assert_eq!(languages, vec!["rust", "go", "shell"]);
+            }
+            _ => panic!("expected symbol command"),
+        }
+    }
+
+    #[test]
+    fn parse_doctor_with_fix() {
+        let cli = Cli::parse_from(["flashgrep", "doctor", "--fix", "--output", "json"]);
+        match cli.command {
+            Commands::Doctor { fix, output, .. } => {
+                assert!(fix);
+                assert_eq!(output, OutputMode::Json);
+            }
+            _ => panic!("expected doctor command"),
+        }
+    }
+
+    #[test]
+    fn parse_compact_command() {
+        let cli = Cli::parse_from(["flashgrep", "compact", "--output", "json"]);
+        match cli.command {
+            Commands::Compact { path, output } => {
+                assert!(path.is_none());
+                assert_eq!(output, OutputMode::Json);
+            }
+            _ => panic!("expected compact command"),
+        }
+    }
+
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+
     #[test]
     fn parse_ask_with_neural_mode() {
         let cli = Cli::parse_from([
@@ -1296,6 +1851,9 @@ mod tests {
             preview: Some("fn main".to_string()),
             match_text: Some("fn main".to_string()),
             content: None,
+            changed_hunks: Vec::new(),
+            blame: None,
+            also_in: Vec::new(),
         }];
 
         let encoded = serde_json::to_string(&data)?;
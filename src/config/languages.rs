@@ -0,0 +1,435 @@
+use crate::chunking::ChunkStrategy;
+use crate::symbols::SymbolExtractor;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Number of lines at the start and end of a file searched for modelines
+const MODELINE_SEARCH_LINES: usize = 5;
+
+/// Bytes read from extension-less files to look for a shebang or modeline
+const DETECTION_READ_LIMIT: u64 = 64 * 1024;
+
+/// One language in the registry and how to recognise it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LanguageDef {
+    /// Language id stored in the index (`files.language`)
+    pub id: String,
+
+    /// File extensions without the leading dot, matched case-insensitively
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// Exact file names; a trailing `.*` also matches any suffix (`Dockerfile.*`)
+    #[serde(default)]
+    pub filenames: Vec<String>,
+
+    /// Interpreters named in a `#!` line (`bash`, `python3`, `env node`)
+    #[serde(default)]
+    pub interpreters: Vec<String>,
+
+    /// Alternative names used in vim/emacs modelines and language filters
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    /// How files of this language are split into chunks
+    #[serde(default)]
+    pub chunker: ChunkStrategy,
+
+    /// Which symbol extractor runs on the chunks
+    #[serde(default)]
+    pub symbols: SymbolExtractor,
+}
+
+/// Registry of languages, checked in order: file name, extension, shebang, modeline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct LanguageMap(pub Vec<LanguageDef>);
+
+impl LanguageMap {
+    pub fn languages(&self) -> &[LanguageDef] {
+        &self.0
+    }
+
+    /// Look up a language by id or alias (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&LanguageDef> {
+        let name = name.trim().to_lowercase();
+        self.0.iter().find(|def| {
+            def.id.eq_ignore_ascii_case(&name)
+                || def.aliases.iter().any(|a| a.eq_ignore_ascii_case(&name))
+        })
+    }
+
+    /// Resolve user-supplied language names (ids or aliases, comma-separated
+    /// lists allowed) to language ids. Unknown names are kept as lowercase ids.
+    pub fn resolve_ids(&self, names: &[String]) -> Vec<String> {
+        let mut ids = Vec::new();
+        for name in names.iter().flat_map(|n| n.split(',')) {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let id = self
+                .get(name)
+                .map(|def| def.id.clone())
+                .unwrap_or_else(|| name.to_lowercase());
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Detect a language from the file name and extension only
+    pub fn detect_path(&self, path: &Path) -> Option<&LanguageDef> {
+        let file_name = path.file_name()?.to_string_lossy();
+        let by_name = self.0.iter().find(|def| {
+            def.filenames
+                .iter()
+                .any(|pattern| filename_matches(pattern, &file_name))
+        });
+        if by_name.is_some() {
+            return by_name;
+        }
+
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        self.0
+            .iter()
+            .find(|def| def.extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext)))
+    }
+
+    /// Detect a language from the path, then the shebang and modelines in `content`
+    pub fn detect(&self, path: &Path, content: &str) -> Option<&LanguageDef> {
+        self.detect_path(path)
+            .or_else(|| shebang_interpreter(content).and_then(|i| self.by_interpreter(&i)))
+            .or_else(|| modeline_language(content).and_then(|m| self.get(&m)))
+    }
+
+    /// Detect a language, reading the head of the file when the path alone is not
+    /// enough. Only extension-less files are read, so images, archives and other
+    /// data files with unknown extensions cost no I/O.
+    pub fn detect_file(&self, path: &Path) -> Option<&LanguageDef> {
+        if let Some(def) = self.detect_path(path) {
+            return Some(def);
+        }
+        if !sniffs_content(path) {
+            return None;
+        }
+        let mut head = Vec::new();
+        std::fs::File::open(path)
+            .ok()?
+            .take(DETECTION_READ_LIMIT)
+            .read_to_end(&mut head)
+            .ok()?;
+        self.detect(path, &String::from_utf8_lossy(&head))
+    }
+
+    fn by_interpreter(&self, interpreter: &str) -> Option<&LanguageDef> {
+        let find = |name: &str| {
+            self.0
+                .iter()
+                .find(|def| def.interpreters.iter().any(|i| i == name))
+        };
+        // `python3.11` falls back to `python3`, then `python`
+        find(interpreter)
+            .or_else(|| find(interpreter.split('.').next().unwrap_or(interpreter)))
+            .or_else(|| {
+                find(interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+            })
+    }
+}
+
+impl Default for LanguageMap {
+    fn default() -> Self {
+        use ChunkStrategy::{BlankLines, Brackets};
+        use SymbolExtractor::{CFamily, Dockerfile, Generic, Makefile, Shell, Terraform};
+
+        let def = |id: &str,
+                   extensions: &[&str],
+                   filenames: &[&str],
+                   interpreters: &[&str],
+                   aliases: &[&str],
+                   chunker: ChunkStrategy,
+                   symbols: SymbolExtractor| LanguageDef {
+            id: id.to_string(),
+            extensions: extensions.iter().map(|s| s.to_string()).collect(),
+            filenames: filenames.iter().map(|s| s.to_string()).collect(),
+            interpreters: interpreters.iter().map(|s| s.to_string()).collect(),
+            aliases: aliases.iter().map(|s| s.to_string()).collect(),
+            chunker,
+            symbols,
+        };
+
+        Self(vec![
+            def("rust", &["rs"], &[], &[], &["rs"], Brackets, Generic),
+            def("go", &["go"], &[], &[], &["golang"], Brackets, Generic),
+            def(
+                "javascript",
+                &["js", "mjs", "cjs", "jsx"],
+                &[],
+                &["node", "nodejs"],
+                &["js"],
+                Brackets,
+                Generic,
+            ),
+            def(
+                "typescript",
+                &["ts", "mts", "cts", "tsx"],
+                &[],
+                &["ts-node", "deno"],
+                &["ts"],
+                Brackets,
+                Generic,
+            ),
+            def(
+                "python",
+                &["py", "pyi"],
+                &[],
+                &["python", "python2", "python3"],
+                &["py"],
+                Brackets,
+                Generic,
+            ),
+            def("solidity", &["sol"], &[], &[], &["sol"], Brackets, Generic),
+            def("json", &["json"], &[], &[], &[], Brackets, Generic),
+            def(
+                "markdown",
+                &["md", "markdown"],
+                &[],
+                &[],
+                &["md"],
+                Brackets,
+                Generic,
+            ),
+            def(
+                "yaml",
+                &["yaml", "yml"],
+                &[],
+                &[],
+                &["yml"],
+                Brackets,
+                Generic,
+            ),
+            def("toml", &["toml"], &[], &[], &[], Brackets, Generic),
+            def("c", &["c", "h"], &[], &[], &[], Brackets, CFamily),
+            def(
+                "cpp",
+                &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+                &[],
+                &[],
+                &["c++"],
+                Brackets,
+                CFamily,
+            ),
+            def("java", &["java"], &[], &[], &[], Brackets, CFamily),
+            def(
+                "kotlin",
+                &["kt", "kts"],
+                &[],
+                &[],
+                &["kt"],
+                Brackets,
+                CFamily,
+            ),
+            def(
+                "csharp",
+                &["cs"],
+                &[],
+                &[],
+                &["cs", "c#"],
+                Brackets,
+                CFamily,
+            ),
+            def(
+                "ruby",
+                &["rb"],
+                &["Rakefile", "Gemfile"],
+                &["ruby"],
+                &["rb"],
+                Brackets,
+                Generic,
+            ),
+            def("php", &["php"], &[], &["php"], &[], Brackets, Generic),
+            def("swift", &["swift"], &[], &[], &[], Brackets, CFamily),
+            def("sql", &["sql"], &[], &[], &[], BlankLines, Generic),
+            def(
+                "shell",
+                &["sh", "bash", "zsh", "ksh"],
+                &[".bashrc", ".bash_profile", ".zshrc", ".profile"],
+                &["sh", "bash", "zsh", "ksh", "dash", "ash"],
+                &["sh", "bash", "zsh"],
+                BlankLines,
+                Shell,
+            ),
+            def(
+                "makefile",
+                &["mk", "mak"],
+                &["Makefile", "makefile", "GNUmakefile"],
+                &["make"],
+                &["make"],
+                BlankLines,
+                Makefile,
+            ),
+            def(
+                "dockerfile",
+                &["dockerfile"],
+                &["Dockerfile", "Containerfile", "Dockerfile.*"],
+                &[],
+                &["docker"],
+                BlankLines,
+                Dockerfile,
+            ),
+            def(
+                "terraform",
+                &["tf", "tfvars", "hcl"],
+                &[],
+                &[],
+                &["tf", "hcl"],
+                Brackets,
+                Terraform,
+            ),
+        ])
+    }
+}
+
+fn filename_matches(pattern: &str, file_name: &str) -> bool {
+    match pattern.strip_suffix(".*") {
+        Some(prefix) => file_name
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.') && rest.len() > 1),
+        None => pattern == file_name,
+    }
+}
+
+/// Whether a path's language is looked for in its content when the name does not
+/// give it away, which is only done for files without an extension
+pub fn sniffs_content(path: &Path) -> bool {
+    path.extension().is_none()
+}
+
+/// Interpreter named by a `#!` line, skipping `env` and its flags
+fn shebang_interpreter(content: &str) -> Option<String> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    if program != "env" {
+        return Some(program.to_string());
+    }
+    words
+        .find(|w| !w.starts_with('-') && !w.contains('='))
+        .map(|w| w.rsplit('/').next().unwrap_or(w).to_string())
+}
+
+/// Language named by a vim (`vim: set ft=sh:`) or emacs (`-*- mode: ruby -*-`) modeline
+fn modeline_language(content: &str) -> Option<String> {
+    static VIM: OnceLock<Regex> = OnceLock::new();
+    static EMACS: OnceLock<Regex> = OnceLock::new();
+    let vim = VIM.get_or_init(|| {
+        Regex::new(r"(?:^|\s)(?:vi|vim|ex):.*?\b(?:ft|filetype|syntax)=([A-Za-z0-9_+#-]+)")
+            .expect("valid vim modeline regex")
+    });
+    let emacs = EMACS.get_or_init(|| {
+        Regex::new(r"-\*-\s*(?:.*?\bmode:\s*([A-Za-z0-9_+#-]+)|([A-Za-z0-9_+#-]+)\s*)[^*]*-\*-")
+            .expect("valid emacs modeline regex")
+    });
+
+    let lines: Vec<&str> = content.lines().collect();
+    let tail_start = lines.len().saturating_sub(MODELINE_SEARCH_LINES);
+    let candidates = lines
+        .iter()
+        .take(MODELINE_SEARCH_LINES)
+        .chain(lines.iter().skip(tail_start.max(MODELINE_SEARCH_LINES)));
+
+    for line in candidates {
+        if let Some(cap) = vim.captures(line) {
+            return Some(cap[1].to_lowercase());
+        }
+        if let Some(cap) = emacs.captures(line) {
+            let mode = cap.get(1).or_else(|| cap.get(2))?;
+            return Some(mode.as_str().trim_end_matches("-mode").to_lowercase());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn detected(map: &LanguageMap, path: &str, content: &str) -> Option<String> {
+        map.detect(&PathBuf::from(path), content)
+            .map(|def| def.id.clone())
+    }
+
+    #[test]
+    fn detects_by_filename_extension_shebang_and_modeline() {
+        let map = LanguageMap::default();
+        assert_eq!(detected(&map, "src/Main.java", ""), Some("java".into()));
+        assert_eq!(detected(&map, "lib/x.HPP", ""), Some("cpp".into()));
+        assert_eq!(detected(&map, "Dockerfile", ""), Some("dockerfile".into()));
+        assert_eq!(
+            detected(&map, "Dockerfile.dev", ""),
+            Some("dockerfile".into())
+        );
+        assert_eq!(detected(&map, "GNUmakefile", ""), Some("makefile".into()));
+        assert_eq!(
+            detected(&map, "bin/deploy", "#!/usr/bin/env bash\nset -e\n"),
+            Some("shell".into())
+        );
+        assert_eq!(
+            detected(&map, "bin/tool", "#!/usr/bin/python3.11\n"),
+            Some("python".into())
+        );
+        assert_eq!(
+            detected(&map, "scripts/run", "echo hi\n# vim: set ft=sh :\n"),
+            Some("shell".into())
+        );
+        assert_eq!(
+            detected(&map, "conf/rules", "# -*- mode: python -*-\nx = 1\n"),
+            Some("python".into())
+        );
+        assert_eq!(detected(&map, "LICENSE", "MIT License\n"), None);
+    }
+
+    #[test]
+    fn detect_file_reads_only_extension_less_files() -> std::io::Result<()> {
+        let map = LanguageMap::default();
+        let temp = tempfile::TempDir::new()?;
+        let script = temp.path().join("deploy");
+        let data = temp.path().join("deploy.dat");
+        std::fs::write(&script, "#!/usr/bin/env bash\n")?;
+        std::fs::write(&data, "#!/usr/bin/env bash\n")?;
+
+        assert_eq!(
+            map.detect_file(&script).map(|def| def.id.as_str()),
+            Some("shell")
+        );
+        assert!(map.detect_file(&data).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn custom_languages_deserialize_with_defaults() {
+        let json = r#"[{"id": "zig", "extensions": ["zig"]}]"#;
+        let map: LanguageMap = serde_json::from_str(json).expect("valid map");
+        let def = map.detect_path(Path::new("build.zig")).expect("zig");
+        assert_eq!(def.id, "zig");
+        assert_eq!(def.chunker, ChunkStrategy::Brackets);
+        assert_eq!(def.symbols, SymbolExtractor::Generic);
+        assert!(map.get("ZIG").is_some());
+    }
+
+    #[test]
+    fn resolve_ids_maps_aliases_and_splits_lists() {
+        let map = LanguageMap::default();
+        let names = vec![
+            "golang,RS".to_string(),
+            "bash".to_string(),
+            "zig".to_string(),
+        ];
+        assert_eq!(map.resolve_ids(&names), vec!["go", "rust", "shell", "zig"]);
+    }
+}
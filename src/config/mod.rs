@@ -1,5 +1,9 @@
+pub mod languages;
 pub mod paths;
 
+use crate::chunking::ChunkSettings;
+use languages::LanguageMap;
+
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -45,6 +49,20 @@ pub struct NeuralConfig {
     pub provider: NeuralProviderConfig,
 }
 
+/// How the file watcher learns about changes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WatcherBackend {
+    /// Operating system file notifications
+    Native,
+    /// Periodic mtime and content-hash rescans, for network and container mounts
+    Poll,
+    /// Native notifications, switching to polling on file systems that do not
+    /// deliver them or when changes show up without notifications
+    #[default]
+    Auto,
+}
+
 /// Configuration for flashgrep
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -62,14 +80,33 @@ pub struct Config {
     #[serde(default = "default_socket_path")]
     pub socket_path: PathBuf,
 
-    /// Maximum file size to index (in bytes)
+    /// Files larger than this (in bytes) are streamed in fixed-size chunks
+    /// instead of being read whole
     #[serde(default = "default_max_file_size")]
     pub max_file_size: u64,
 
-    /// Maximum chunk size in lines
+    /// Files larger than this (in bytes) are skipped and recorded with a reason
+    #[serde(default = "default_skip_file_size")]
+    pub skip_file_size: u64,
+
+    /// Files with a line longer than this are treated as minified and their
+    /// lines are split before chunking
+    #[serde(default = "default_minified_line_length")]
+    pub minified_line_length: usize,
+
+    /// Maximum chunk size in lines, overlap included
     #[serde(default = "default_max_chunk_lines")]
     pub max_chunk_lines: usize,
 
+    /// Chunk size in lines from which a chunk ends at the next natural boundary
+    /// of its language's strategy; the window size of the `lines` strategy
+    #[serde(default = "default_target_chunk_lines")]
+    pub target_chunk_lines: usize,
+
+    /// Lines repeated from the end of each chunk at the start of the next
+    #[serde(default = "default_chunk_overlap_lines")]
+    pub chunk_overlap_lines: usize,
+
     /// File extensions to index
     #[serde(default = "default_extensions")]
     pub extensions: Vec<String>,
@@ -82,6 +119,24 @@ pub struct Config {
     #[serde(default = "default_debounce_ms")]
     pub debounce_ms: u64,
 
+    /// Pending watcher changes at which they are reindexed as one parallel batch
+    /// with a single commit instead of file by file
+    #[serde(default = "default_watcher_burst_threshold")]
+    pub watcher_burst_threshold: usize,
+
+    /// How the watcher detects changes: `native`, `poll` or `auto`
+    #[serde(default)]
+    pub watcher_backend: WatcherBackend,
+
+    /// Interval between rescans when the watcher polls, in milliseconds
+    #[serde(default = "default_watcher_poll_interval_ms")]
+    pub watcher_poll_interval_ms: u64,
+
+    /// How long to wait for another process to release the index write lock,
+    /// in milliseconds, before failing
+    #[serde(default = "default_writer_lock_timeout_ms")]
+    pub writer_lock_timeout_ms: u64,
+
     /// Enable initial indexing on watcher start
     #[serde(default = "default_enable_initial_index")]
     pub enable_initial_index: bool,
@@ -97,6 +152,10 @@ pub struct Config {
     /// Neural navigation and provider configuration
     #[serde(default)]
     pub neural: NeuralConfig,
+
+    /// Language registry used for detection, chunking and symbol extraction
+    #[serde(default)]
+    pub languages: LanguageMap,
 }
 
 impl Default for Config {
@@ -107,14 +166,23 @@ impl Default for Config {
             use_unix_socket: default_use_unix_socket(),
             socket_path: default_socket_path(),
             max_file_size: default_max_file_size(),
+            skip_file_size: default_skip_file_size(),
+            minified_line_length: default_minified_line_length(),
             max_chunk_lines: default_max_chunk_lines(),
+            target_chunk_lines: default_target_chunk_lines(),
+            chunk_overlap_lines: default_chunk_overlap_lines(),
             extensions: default_extensions(),
             ignored_dirs: default_ignored_dirs(),
             debounce_ms: default_debounce_ms(),
+            watcher_burst_threshold: default_watcher_burst_threshold(),
+            watcher_backend: WatcherBackend::default(),
+            watcher_poll_interval_ms: default_watcher_poll_interval_ms(),
+            writer_lock_timeout_ms: default_writer_lock_timeout_ms(),
             enable_initial_index: default_enable_initial_index(),
             progress_interval: default_progress_interval(),
             index_state_path: default_index_state_path(),
             neural: NeuralConfig::default(),
+            languages: LanguageMap::default(),
         }
     }
 }
@@ -134,6 +202,33 @@ impl Config {
         Ok(())
     }
 
+    /// Chunk size bounds for the chunker, normalized into a usable range
+    pub fn chunk_settings(&self) -> ChunkSettings {
+        ChunkSettings {
+            target_lines: self.target_chunk_lines,
+            max_lines: self.max_chunk_lines,
+            overlap_lines: self.chunk_overlap_lines,
+        }
+        .normalized()
+    }
+
+    /// Everything that decides how files are split into chunks, serialized so a
+    /// change can be detected and the index re-chunked
+    pub fn chunking_fingerprint(&self) -> String {
+        let strategies: Vec<(&str, crate::chunking::ChunkStrategy)> = self
+            .languages
+            .languages()
+            .iter()
+            .map(|def| (def.id.as_str(), def.chunker))
+            .collect();
+        serde_json::json!({
+            "settings": self.chunk_settings(),
+            "minified_line_length": self.minified_line_length,
+            "strategies": strategies,
+        })
+        .to_string()
+    }
+
     /// Get the default configuration file path within a flashgrep directory
     pub fn default_path(flashgrep_dir: &Path) -> PathBuf {
         flashgrep_dir.join("config.json")
@@ -216,10 +311,26 @@ fn default_max_file_size() -> u64 {
     crate::MAX_FILE_SIZE
 }
 
+fn default_skip_file_size() -> u64 {
+    crate::SKIP_FILE_SIZE
+}
+
+fn default_minified_line_length() -> usize {
+    crate::MINIFIED_LINE_LENGTH
+}
+
 fn default_max_chunk_lines() -> usize {
     crate::MAX_CHUNK_LINES
 }
 
+fn default_target_chunk_lines() -> usize {
+    crate::TARGET_CHUNK_LINES
+}
+
+fn default_chunk_overlap_lines() -> usize {
+    crate::CHUNK_OVERLAP_LINES
+}
+
 fn default_extensions() -> Vec<String> {
     vec![
         "go".to_string(),
@@ -256,6 +367,18 @@ fn default_enable_initial_index() -> bool {
     true
 }
 
+fn default_watcher_burst_threshold() -> usize {
+    100
+}
+
+fn default_watcher_poll_interval_ms() -> u64 {
+    2000
+}
+
+fn default_writer_lock_timeout_ms() -> u64 {
+    30_000
+}
+
 fn default_progress_interval() -> usize {
     1000
 }
@@ -303,6 +426,39 @@ mod tests {
         assert_eq!(config.mcp_port, 7777);
         assert_eq!(config.max_file_size, 2 * 1024 * 1024);
         assert_eq!(config.max_chunk_lines, 300);
+        assert_eq!(config.chunk_settings(), ChunkSettings::default());
+    }
+
+    #[test]
+    fn test_chunking_fingerprint_tracks_chunk_settings() {
+        let config = Config::default();
+        let mut overlapping = config.clone();
+        overlapping.chunk_overlap_lines = 5;
+        assert_ne!(
+            config.chunking_fingerprint(),
+            overlapping.chunking_fingerprint()
+        );
+
+        // Settings that normalize to the same bounds chunk the same way
+        let mut clamped = config.clone();
+        clamped.target_chunk_lines = 0;
+        assert_eq!(
+            config.chunking_fingerprint(),
+            clamped.chunking_fingerprint()
+        );
+
+        let mut restrategized = config.clone();
+        restrategized.languages.0[0].chunker = crate::chunking::ChunkStrategy::Lines;
+        assert_ne!(
+            config.chunking_fingerprint(),
+            restrategized.chunking_fingerprint()
+        );
+
+        // Settings missing from older config files take their defaults
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("chunk_overlap_lines");
+        let older: Config = serde_json::from_value(json).unwrap();
+        assert_eq!(older.chunk_overlap_lines, 0);
     }
 
     #[test]
@@ -314,6 +470,18 @@ mod tests {
         assert_eq!(config.mcp_port, deserialized.mcp_port);
     }
 
+    #[test]
+    fn watcher_backend_defaults_to_auto_and_parses_lowercase() {
+        assert_eq!(Config::default().watcher_backend, WatcherBackend::Auto);
+        let json = serde_json::to_value(Config::default()).unwrap();
+        let mut object = json.as_object().unwrap().clone();
+        object.insert("watcher_backend".to_string(), serde_json::json!("poll"));
+        object.remove("watcher_poll_interval_ms");
+        let config: Config = serde_json::from_value(object.into()).unwrap();
+        assert_eq!(config.watcher_backend, WatcherBackend::Poll);
+        assert_eq!(config.watcher_poll_interval_ms, 2000);
+    }
+
     #[test]
     fn neural_defaults_match_openrouter_profile() {
         let cfg = Config::default();
@@ -20,6 +20,11 @@ impl FlashgrepPaths {
         &self.root
     }
 
+    /// Get the repository root that owns this .flashgrep directory
+    pub fn repo_root(&self) -> &Path {
+        self.root.parent().unwrap_or(&self.root)
+    }
+
     /// Get the path to the metadata database
     pub fn metadata_db(&self) -> PathBuf {
         self.root.join("metadata.db")
@@ -35,6 +40,21 @@ impl FlashgrepPaths {
         self.root.join("text_index")
     }
 
+    /// Get the path the text index is rebuilt in during a schema migration
+    pub fn text_index_staging_dir(&self) -> PathBuf {
+        self.root.join("text_index.migrating")
+    }
+
+    /// Get the path the replaced text index is moved to during a schema migration
+    pub fn text_index_retired_dir(&self) -> PathBuf {
+        self.root.join("text_index.old")
+    }
+
+    /// Get the path to the git history index directory (Tantivy)
+    pub fn history_index_dir(&self) -> PathBuf {
+        self.root.join("history_index")
+    }
+
     /// Get the path to the logs directory
     pub fn logs_dir(&self) -> PathBuf {
         self.root.join("logs")
@@ -78,6 +98,29 @@ impl FlashgrepPaths {
         Ok(())
     }
 
+    /// Delete the text index and metadata database, keeping the config and logs.
+    /// Used to clear stores this build can no longer open.
+    pub fn remove_index_stores(&self) -> FlashgrepResult<()> {
+        for dir in [
+            self.text_index_dir(),
+            self.text_index_staging_dir(),
+            self.text_index_retired_dir(),
+            self.history_index_dir(),
+        ] {
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir)?;
+            }
+        }
+        let db = self.metadata_db().to_string_lossy().to_string();
+        for file in [db.clone(), format!("{}-wal", db), format!("{}-shm", db)] {
+            match std::fs::remove_file(&file) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Get the size of the flashgrep directory in bytes
     pub fn size_bytes(&self) -> u64 {
         fn dir_size(path: &std::path::Path) -> u64 {
@@ -172,6 +215,13 @@ mod tests {
         assert!(paths.logs_dir().exists());
         assert!(paths.model_cache_dir().exists());
 
+        std::fs::write(paths.metadata_db(), "")?;
+        std::fs::write(paths.config_file(), "{}")?;
+        paths.remove_index_stores()?;
+        assert!(!paths.text_index_dir().exists());
+        assert!(!paths.metadata_db().exists());
+        assert!(paths.config_file().exists());
+
         paths.remove()?;
         assert!(!paths.exists());
 
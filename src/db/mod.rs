@@ -1,12 +1,71 @@
 pub mod models;
 
-use crate::FlashgrepResult;
-use models::{Chunk, ChunkVector, FileMetadata, IndexStats, SemanticChunk, Symbol};
+use crate::{FlashgrepError, FlashgrepResult};
+use models::{
+    BlameInfo, Chunk, ChunkLocation, ChunkVector, FileMetadata, IndexFailure, IndexStats,
+    IndexedRevision, JournalEntry, OrphanedRows, SemanticChunk, SkippedFile, Symbol,
+};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::types::{Type, ValueRef};
+use rusqlite::{OpenFlags, OptionalExtension};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tracing::debug;
 
+/// Rows whose file is no longer present in `files`
+const ORPHANED_BY_FILE: &str = "WHERE file_path NOT IN (SELECT file_path FROM files)";
+
+/// Vectors without a matching chunk (same file, line range and content hash)
+const ORPHANED_VECTORS: &str = "WHERE NOT EXISTS (
+    SELECT 1 FROM chunks c
+    WHERE c.file_path = chunk_vectors.file_path
+      AND c.start_line = chunk_vectors.start_line
+      AND c.end_line = chunk_vectors.end_line
+      AND c.content_hash = chunk_vectors.content_hash
+)";
+
+/// zstd level for chunk bodies, favouring indexing speed over ratio
+const CONTENT_COMPRESSION_LEVEL: i32 = 3;
+
+/// Milliseconds a connection waits for another process's write lock before failing
+const BUSY_TIMEOUT_MS: u32 = 5_000;
+
+/// Connections in a read-write pool; parallel indexing queries from several threads
+const WRITE_POOL_SIZE: u32 = 10;
+
+/// Connections in a read-only pool; queries are answered one at a time
+const READ_POOL_SIZE: u32 = 4;
+
+/// Content hashes looked up per statement, below SQLite's bound parameter limit
+const CONTENT_LOOKUP_BATCH: usize = 500;
+
+/// Milliseconds a read-only process waits for the write lock to cache blame
+const BLAME_CACHE_BUSY_TIMEOUT_MS: u64 = 100;
+
+/// Version of the metadata schema written by this build
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// `index_meta` key holding the schema version of the database
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+type Migration = fn(&rusqlite::Connection) -> FlashgrepResult<()>;
+
+/// Forward migrations, in order; entry `i` brings the schema to version `i + 1`.
+/// Databases written before versions were recorded run all of them, so each
+/// migration must be a no-op on a schema that already has its change.
+const MIGRATIONS: [(&str, Migration); SCHEMA_VERSION as usize] = [
+    ("add files.encoding", Database::add_file_encoding),
+    (
+        "move chunk bodies to shared tables",
+        Database::move_chunk_bodies_to_shared_tables,
+    ),
+    (
+        "release chunk bodies still used by revisions",
+        Database::recreate_release_content_trigger,
+    ),
+];
+
 /// Database wrapper with connection pooling
 pub struct Database {
     pool: Pool<SqliteConnectionManager>,
@@ -15,15 +74,47 @@ pub struct Database {
 impl Database {
     /// Open or create the database at the given path with optimizations
     pub fn open(path: &Path) -> FlashgrepResult<Self> {
-        let manager = SqliteConnectionManager::file(path);
+        let manager = SqliteConnectionManager::file(path).with_init(configure_connection);
         let pool = Pool::builder()
-            .max_size(10) // Max 10 connections in pool
+            .max_size(WRITE_POOL_SIZE)
+            .min_idle(Some(1))
             .build(manager)?;
 
         let db = Self { pool };
-        db.init_schema()?;
         db.optimize()?;
+        db.init_schema()?;
+
+        Ok(db)
+    }
+
+    /// Open an existing database for queries only.
+    ///
+    /// Read-only connections never take SQLite's write lock, so any number of
+    /// readers can query while one process indexes. A database written by an
+    /// older version fails with [`FlashgrepError::MigrationRequired`]; only the
+    /// indexer, which holds the write lock, migrates it.
+    pub fn open_read_only(path: &Path) -> FlashgrepResult<Self> {
+        if !path.exists() {
+            return Err(FlashgrepError::IndexNotFound(path.to_path_buf()));
+        }
+        let manager = SqliteConnectionManager::file(path)
+            .with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .with_init(configure_connection);
+        let pool = Pool::builder()
+            .max_size(READ_POOL_SIZE)
+            .min_idle(Some(1))
+            .build(manager)?;
 
+        let db = Self { pool };
+        let version = Self::stored_schema_version(&*db.pool.get()?)?;
+        if version < SCHEMA_VERSION {
+            return Err(FlashgrepError::MigrationRequired(format!(
+                "the metadata database at {} has schema version {}, older than {}",
+                path.display(),
+                version,
+                SCHEMA_VERSION
+            )));
+        }
         Ok(db)
     }
 
@@ -31,9 +122,6 @@ impl Database {
     fn init_schema(&self) -> FlashgrepResult<()> {
         let conn = self.pool.get()?;
 
-        // Enable foreign keys
-        conn.pragma_update(None, "foreign_keys", "ON")?;
-
         // Create files table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS files (
@@ -54,13 +142,33 @@ impl Database {
                 start_line INTEGER NOT NULL,
                 end_line INTEGER NOT NULL,
                 content_hash TEXT NOT NULL,
-                content TEXT NOT NULL,
                 last_modified INTEGER NOT NULL,
                 FOREIGN KEY (file_path) REFERENCES files(file_path) ON DELETE CASCADE
             )",
             [],
         )?;
 
+        // Chunk bodies, zstd-compressed and stored once however many files, line
+        // ranges and revisions share them. This is the only copy of indexed text:
+        // the text index keeps postings and line offsets only.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS chunk_contents (
+                content_hash TEXT PRIMARY KEY,
+                content BLOB NOT NULL
+            )",
+            [],
+        )?;
+
+        // Chunk bodies referenced by indexed git revisions, which have no chunk rows
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS revision_contents (
+                commit_id TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                PRIMARY KEY (commit_id, content_hash)
+            )",
+            [],
+        )?;
+
         // Create index on file_path for chunks
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_chunks_file_path ON chunks(file_path)",
@@ -98,7 +206,6 @@ impl Database {
                 start_line INTEGER NOT NULL,
                 end_line INTEGER NOT NULL,
                 content_hash TEXT NOT NULL,
-                embedding TEXT NOT NULL,
                 model_id TEXT NOT NULL,
                 last_modified INTEGER NOT NULL,
                 UNIQUE(file_path, start_line, end_line, content_hash),
@@ -107,6 +214,17 @@ impl Database {
             [],
         )?;
 
+        // Embeddings, stored once per chunk body and model
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS chunk_embeddings (
+                content_hash TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                embedding TEXT NOT NULL,
+                PRIMARY KEY (content_hash, model_id)
+            )",
+            [],
+        )?;
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_chunk_vectors_file_path ON chunk_vectors(file_path)",
             [],
@@ -151,6 +269,251 @@ impl Database {
             [],
         )?;
 
+        // Settings the index was built with, to detect changes that need a rebuild
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS index_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Updates started but not yet covered by a text index commit, replayed on open
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS index_journal (
+                kind TEXT NOT NULL,
+                target TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                PRIMARY KEY (kind, target)
+            )",
+            [],
+        )?;
+
+        // Files that failed to index during the last attempt, with the reason
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS index_failures (
+                file_path TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                failed_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Files deliberately left out of the index (binary, too large, ...)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS skipped_files (
+                file_path TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                file_size INTEGER NOT NULL,
+                skipped_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Git revisions indexed from the object database
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS revisions (
+                commit_id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                file_count INTEGER NOT NULL,
+                chunk_count INTEGER NOT NULL,
+                indexed_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // git blame results keyed by file and content, reused until the content changes
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS blame_cache (
+                file_path TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                commit_id TEXT NOT NULL,
+                author TEXT NOT NULL,
+                date INTEGER NOT NULL,
+                summary TEXT NOT NULL,
+                PRIMARY KEY (file_path, content_hash)
+            )",
+            [],
+        )?;
+
+        Self::migrate(&conn)?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_chunks_content_hash ON chunks(content_hash)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_chunk_vectors_content_hash
+             ON chunk_vectors(content_hash, model_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_revision_contents_content_hash
+             ON revision_contents(content_hash)",
+            [],
+        )?;
+
+        // Drop shared bodies and embeddings once nothing refers to them
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS revision_contents_release_content
+             AFTER DELETE ON revision_contents
+             BEGIN
+                DELETE FROM chunk_contents
+                WHERE content_hash = OLD.content_hash
+                  AND NOT EXISTS (SELECT 1 FROM chunks WHERE content_hash = OLD.content_hash)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM revision_contents WHERE content_hash = OLD.content_hash
+                  );
+             END",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS chunk_vectors_release_embedding AFTER DELETE ON chunk_vectors
+             BEGIN
+                DELETE FROM chunk_embeddings
+                WHERE content_hash = OLD.content_hash
+                  AND model_id = OLD.model_id
+                  AND NOT EXISTS (
+                      SELECT 1 FROM chunk_vectors
+                      WHERE content_hash = OLD.content_hash AND model_id = OLD.model_id
+                  );
+             END",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Bring a database written by an older version up to [`SCHEMA_VERSION`],
+    /// recording the version after each step
+    fn migrate(conn: &rusqlite::Connection) -> FlashgrepResult<()> {
+        let version = Self::stored_schema_version(conn)?;
+        for (index, (description, migration)) in
+            MIGRATIONS.iter().enumerate().skip(version as usize)
+        {
+            migration(conn)?;
+            conn.execute(
+                "INSERT INTO index_meta (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                (SCHEMA_VERSION_KEY, (index + 1).to_string()),
+            )?;
+            debug!(
+                "Migrated metadata schema to version {}: {}",
+                index + 1,
+                description
+            );
+        }
+        Ok(())
+    }
+
+    /// Schema version recorded in the database, 0 if none was recorded.
+    /// Fails with [`FlashgrepError::RebuildRequired`] for versions this build
+    /// does not know.
+    fn stored_schema_version(conn: &rusqlite::Connection) -> FlashgrepResult<u32> {
+        let has_meta: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'index_meta')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_meta {
+            return Ok(0);
+        }
+        let stored: Option<String> = conn
+            .query_row(
+                "SELECT value FROM index_meta WHERE key = ?1",
+                [SCHEMA_VERSION_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let version = match stored {
+            Some(value) => value.parse::<u32>().map_err(|_| {
+                FlashgrepError::RebuildRequired(format!(
+                    "the metadata database has an unreadable schema version '{}'",
+                    value
+                ))
+            })?,
+            None => 0,
+        };
+        if version > SCHEMA_VERSION {
+            return Err(FlashgrepError::RebuildRequired(format!(
+                "the metadata database has schema version {}, newer than the {} this build supports",
+                version, SCHEMA_VERSION
+            )));
+        }
+        Ok(version)
+    }
+
+    /// Schema version 1: files remember the encoding they were decoded with
+    fn add_file_encoding(conn: &rusqlite::Connection) -> FlashgrepResult<()> {
+        Self::add_column_if_missing(conn, "files", "encoding", "TEXT")
+    }
+
+    /// Schema version 3: chunk bodies still referenced by an indexed revision
+    /// are kept when the last chunk using them is deleted
+    fn recreate_release_content_trigger(conn: &rusqlite::Connection) -> FlashgrepResult<()> {
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS chunks_release_content;
+             CREATE TRIGGER chunks_release_content AFTER DELETE ON chunks
+             BEGIN
+                DELETE FROM chunk_contents
+                WHERE content_hash = OLD.content_hash
+                  AND NOT EXISTS (SELECT 1 FROM chunks WHERE content_hash = OLD.content_hash)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM revision_contents WHERE content_hash = OLD.content_hash
+                  );
+             END;",
+        )?;
+        Ok(())
+    }
+
+    /// Schema version 2: move chunk bodies and embeddings stored inline by
+    /// older versions into the tables shared by content hash
+    fn move_chunk_bodies_to_shared_tables(conn: &rusqlite::Connection) -> FlashgrepResult<()> {
+        if Self::has_column(conn, "chunks", "content")? {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(
+                "INSERT OR IGNORE INTO chunk_contents (content_hash, content)
+                     SELECT content_hash, content FROM chunks;
+                 ALTER TABLE chunks DROP COLUMN content;",
+            )?;
+            tx.commit()?;
+            debug!("Moved chunk bodies into chunk_contents");
+        }
+        if Self::has_column(conn, "chunk_vectors", "embedding")? {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(
+                "INSERT OR IGNORE INTO chunk_embeddings (content_hash, model_id, embedding)
+                     SELECT content_hash, model_id, embedding FROM chunk_vectors;
+                 ALTER TABLE chunk_vectors DROP COLUMN embedding;",
+            )?;
+            tx.commit()?;
+            debug!("Moved chunk embeddings into chunk_embeddings");
+        }
+        Ok(())
+    }
+
+    fn has_column(conn: &rusqlite::Connection, table: &str, column: &str) -> FlashgrepResult<bool> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|name| name.ok())
+            .any(|name| name == column);
+        Ok(exists)
+    }
+
+    /// Add a column to a table created by an older version of the schema
+    fn add_column_if_missing(
+        conn: &rusqlite::Connection,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> FlashgrepResult<()> {
+        if !Self::has_column(conn, table, column)? {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
         Ok(())
     }
 
@@ -158,13 +521,14 @@ impl Database {
     pub fn insert_file(&self, file: &FileMetadata) -> FlashgrepResult<i64> {
         let conn = self.pool.get()?;
         conn.execute(
-            "INSERT OR REPLACE INTO files (file_path, file_size, last_modified, language)
-             VALUES (?1, ?2, ?3, ?4)",
+            "INSERT OR REPLACE INTO files (file_path, file_size, last_modified, language, encoding)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
             (
                 file.file_path.to_string_lossy().to_string(),
                 file.file_size as i64,
                 file.last_modified,
                 file.language.as_ref(),
+                file.encoding.as_ref(),
             ),
         )?;
         Ok(conn.last_insert_rowid())
@@ -174,21 +538,10 @@ impl Database {
     fn optimize(&self) -> FlashgrepResult<()> {
         let conn = self.pool.get()?;
 
-        // Enable WAL mode for better concurrent access
+        // Enable WAL mode so readers in other processes never block on the writer.
+        // The mode is stored in the database file and applies to every connection.
         conn.pragma_update(None, "journal_mode", "WAL")?;
 
-        // Increase cache size to ~100MB (in pages of 4KB)
-        conn.pragma_update(None, "cache_size", -25000)?;
-
-        // Set synchronous mode to NORMAL for better performance (still safe with WAL)
-        conn.pragma_update(None, "synchronous", "NORMAL")?;
-
-        // Enable memory-mapped I/O (256MB)
-        conn.pragma_update(None, "mmap_size", 268435456)?;
-
-        // Set temp store to memory for better performance
-        conn.pragma_update(None, "temp_store", "MEMORY")?;
-
         debug!("SQLite optimizations applied");
         Ok(())
     }
@@ -200,18 +553,24 @@ impl Database {
 
         let mut count = 0;
         {
+            let mut content_stmt = tx.prepare(
+                "INSERT OR IGNORE INTO chunk_contents (content_hash, content) VALUES (?1, ?2)",
+            )?;
             let mut stmt = tx.prepare(
-                "INSERT INTO chunks (file_path, start_line, end_line, content_hash, content, last_modified)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+                "INSERT INTO chunks (file_path, start_line, end_line, content_hash, last_modified)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
             )?;
 
             for chunk in chunks {
+                content_stmt.execute(rusqlite::params![
+                    &chunk.content_hash,
+                    compress_content(&chunk.content)?
+                ])?;
                 stmt.execute([
                     chunk.file_path.to_string_lossy().to_string(),
                     chunk.start_line.to_string(),
                     chunk.end_line.to_string(),
                     chunk.content_hash.clone(),
-                    chunk.content.clone(),
                     chunk.last_modified.to_string(),
                 ])?;
                 count += 1;
@@ -233,25 +592,33 @@ impl Database {
         let mut count = 0usize;
 
         {
+            let mut embedding_stmt = tx.prepare(
+                "INSERT INTO chunk_embeddings (content_hash, model_id, embedding)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(content_hash, model_id) DO UPDATE SET embedding = excluded.embedding",
+            )?;
             let mut stmt = tx.prepare(
                 "INSERT INTO chunk_vectors (
-                    file_path, start_line, end_line, content_hash, embedding, model_id, last_modified
-                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    file_path, start_line, end_line, content_hash, model_id, last_modified
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
                  ON CONFLICT(file_path, start_line, end_line, content_hash)
                  DO UPDATE SET
-                    embedding = excluded.embedding,
                     model_id = excluded.model_id,
                     last_modified = excluded.last_modified",
             )?;
 
             for vector in vectors {
                 let embedding_json = serde_json::to_string(&vector.embedding)?;
+                embedding_stmt.execute([
+                    &vector.content_hash,
+                    &vector.model_id,
+                    &embedding_json,
+                ])?;
                 stmt.execute([
                     vector.file_path.to_string_lossy().to_string(),
                     vector.start_line.to_string(),
                     vector.end_line.to_string(),
                     vector.content_hash.clone(),
-                    embedding_json,
                     vector.model_id.clone(),
                     vector.last_modified.to_string(),
                 ])?;
@@ -267,13 +634,17 @@ impl Database {
     pub fn insert_chunk(&self, chunk: &Chunk) -> FlashgrepResult<i64> {
         let conn = self.pool.get()?;
         conn.execute(
-            "INSERT INTO chunks (file_path, start_line, end_line, content_hash, content, last_modified)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT OR IGNORE INTO chunk_contents (content_hash, content) VALUES (?1, ?2)",
+            rusqlite::params![&chunk.content_hash, compress_content(&chunk.content)?],
+        )?;
+        conn.execute(
+            "INSERT INTO chunks (file_path, start_line, end_line, content_hash, last_modified)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
             [
                 chunk.file_path.to_string_lossy().to_string(),
                 chunk.start_line.to_string(),
+                chunk.end_line.to_string(),
                 chunk.content_hash.clone(),
-                chunk.content.clone(),
                 chunk.last_modified.to_string(),
             ],
         )?;
@@ -479,6 +850,137 @@ impl Database {
         Ok(deleted)
     }
 
+    /// Move a file's records to a new path, keeping its chunks, symbols, vectors
+    /// and graph nodes. Anything indexed at `to` is replaced. Returns `false`,
+    /// changing nothing, if `from` is not indexed.
+    pub fn rename_file(&self, from: &Path, to: &Path) -> FlashgrepResult<bool> {
+        let from_str = from.to_string_lossy().to_string();
+        let to_str = to.to_string_lossy().to_string();
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+
+        let indexed = tx
+            .query_row(
+                "SELECT 1 FROM files WHERE file_path = ?1",
+                [&from_str],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !indexed {
+            return Ok(false);
+        }
+
+        for table in [
+            "chunks",
+            "symbols",
+            "chunk_vectors",
+            "knowledge_graph_edges",
+        ] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE file_path = ?1", table),
+                [&to_str],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM knowledge_graph_nodes WHERE file_path = ?1",
+            [&to_str],
+        )?;
+        tx.execute("DELETE FROM files WHERE file_path = ?1", [&to_str])?;
+
+        // Insert the new parent row before moving children off the old one, so the
+        // final delete of the old row cascades to nothing
+        tx.execute(
+            "INSERT INTO files (file_path, file_size, last_modified, language, encoding)
+             SELECT ?2, file_size, last_modified, language, encoding
+             FROM files WHERE file_path = ?1",
+            [&from_str, &to_str],
+        )?;
+        for table in ["chunks", "symbols", "chunk_vectors"] {
+            tx.execute(
+                &format!("UPDATE {} SET file_path = ?2 WHERE file_path = ?1", table),
+                [&from_str, &to_str],
+            )?;
+        }
+
+        let nodes = {
+            let mut stmt =
+                tx.prepare("SELECT node_id FROM knowledge_graph_nodes WHERE file_path = ?1")?;
+            let ids = stmt
+                .query_map([&from_str], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            ids
+        };
+        for node_id in &nodes {
+            tx.execute(
+                "UPDATE knowledge_graph_nodes SET node_id = ?2, file_path = ?3 WHERE node_id = ?1",
+                [
+                    node_id,
+                    &rekey_graph_node(node_id, &from_str, &to_str),
+                    &to_str,
+                ],
+            )?;
+        }
+        let edges = {
+            let mut stmt = tx.prepare(
+                "SELECT from_node, to_node, relation FROM knowledge_graph_edges WHERE file_path = ?1",
+            )?;
+            let rows = stmt
+                .query_map([&from_str], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        for (from_node, to_node, relation) in &edges {
+            tx.execute(
+                "UPDATE knowledge_graph_edges SET from_node = ?4, to_node = ?5, file_path = ?6
+                 WHERE from_node = ?1 AND to_node = ?2 AND relation = ?3",
+                [
+                    from_node,
+                    to_node,
+                    relation,
+                    &rekey_graph_node(from_node, &from_str, &to_str),
+                    &rekey_graph_node(to_node, &from_str, &to_str),
+                    &to_str,
+                ],
+            )?;
+        }
+        if !nodes.is_empty() {
+            tx.execute(
+                "UPDATE graph_meta SET revision = revision + 1 WHERE id = 1",
+                [],
+            )?;
+        }
+
+        tx.execute("DELETE FROM files WHERE file_path = ?1", [&from_str])?;
+        tx.execute(
+            "DELETE FROM index_failures WHERE file_path = ?1",
+            [&from_str],
+        )?;
+        tx.execute(
+            "DELETE FROM skipped_files WHERE file_path = ?1",
+            [&from_str],
+        )?;
+        tx.commit()?;
+        debug!("Re-keyed {} to {}", from_str, to_str);
+        Ok(true)
+    }
+
+    /// Set the modification time recorded for an indexed file
+    pub fn set_file_mtime(&self, file_path: &Path, last_modified: i64) -> FlashgrepResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE files SET last_modified = ?2 WHERE file_path = ?1",
+            rusqlite::params![file_path.to_string_lossy().to_string(), last_modified],
+        )?;
+        Ok(())
+    }
+
     /// Check if a file needs reindexing (returns true if file is new or modified)
     pub fn needs_reindex(&self, file_path: &Path, current_modified: i64) -> FlashgrepResult<bool> {
         let conn = self.pool.get()?;
@@ -498,75 +1000,614 @@ impl Database {
         }
     }
 
-    /// Get index statistics
-    pub fn get_stats(&self) -> FlashgrepResult<IndexStats> {
+    /// Get the text encoding recorded for an indexed file
+    pub fn get_file_encoding(&self, file_path: &Path) -> FlashgrepResult<Option<String>> {
         let conn = self.pool.get()?;
-
-        let total_files: usize =
-            conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
-
-        let total_chunks: usize =
-            conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
-
-        let total_symbols: usize =
-            conn.query_row("SELECT COUNT(*) FROM symbols", [], |row| row.get(0))?;
-
-        let last_update: Option<i64> = conn
-            .query_row("SELECT MAX(last_modified) FROM files", [], |row| row.get(0))
-            .ok();
-
-        // Calculate index size (simplified - just database file size)
-        let index_size_bytes = conn
+        let encoding = conn
             .query_row(
-                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
-                [],
+                "SELECT encoding FROM files WHERE file_path = ?1",
+                [file_path.to_string_lossy().to_string()],
                 |row| row.get(0),
             )
-            .unwrap_or(0);
+            .optional()?;
+        Ok(encoding.flatten())
+    }
 
-        Ok(IndexStats {
-            total_files,
-            total_chunks,
-            total_symbols,
-            index_size_bytes,
-            last_update,
-        })
+    /// Get the stored modification time of every indexed file
+    pub fn get_file_mtimes(&self) -> FlashgrepResult<HashMap<PathBuf, i64>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare("SELECT file_path, last_modified FROM files")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    PathBuf::from(row.get::<_, String>(0)?),
+                    row.get::<_, i64>(1)?,
+                ))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(rows)
     }
 
-    /// Find symbols by name
-    pub fn find_symbols_by_name(&self, name: &str) -> FlashgrepResult<Vec<Symbol>> {
+    /// Record that a file failed to index, replacing any earlier failure
+    pub fn record_index_failure(&self, file_path: &Path, reason: &str) -> FlashgrepResult<()> {
         let conn = self.pool.get()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, symbol_name, file_path, line_number, symbol_type FROM symbols
-             WHERE symbol_name = ?1",
+        conn.execute(
+            "INSERT INTO index_failures (file_path, reason, failed_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(file_path) DO UPDATE SET reason = excluded.reason, failed_at = excluded.failed_at",
+            rusqlite::params![
+                file_path.to_string_lossy().to_string(),
+                reason,
+                chrono::Utc::now().timestamp()
+            ],
         )?;
+        Ok(())
+    }
 
-        let symbols = stmt
-            .query_map([name], |row| {
-                Ok(Symbol {
-                    id: row.get(0)?,
-                    symbol_name: row.get(1)?,
-                    file_path: PathBuf::from(row.get::<_, String>(2)?),
-                    line_number: row.get::<_, i64>(3)? as usize,
-                    symbol_type: parse_symbol_type(&row.get::<_, String>(4)?),
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(symbols)
+    /// Forget a recorded failure once the file indexes (or disappears)
+    pub fn clear_index_failure(&self, file_path: &Path) -> FlashgrepResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM index_failures WHERE file_path = ?1",
+            [file_path.to_string_lossy().to_string()],
+        )?;
+        Ok(())
     }
 
-    /// Get all indexed file paths
-    pub fn get_all_files(&self) -> FlashgrepResult<Vec<PathBuf>> {
+    /// List files that failed to index, most recent first
+    pub fn get_index_failures(&self) -> FlashgrepResult<Vec<IndexFailure>> {
         let conn = self.pool.get()?;
-        let mut stmt = conn.prepare("SELECT file_path FROM files")?;
-        let files = stmt
+        let mut stmt = conn.prepare(
+            "SELECT file_path, reason, failed_at FROM index_failures
+             ORDER BY failed_at DESC, file_path ASC",
+        )?;
+        let failures = stmt
             .query_map([], |row| {
-                let path: String = row.get(0)?;
-                Ok(PathBuf::from(path))
+                Ok(IndexFailure {
+                    file_path: PathBuf::from(row.get::<_, String>(0)?),
+                    reason: row.get(1)?,
+                    failed_at: row.get(2)?,
+                })
             })?
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(files)
+        Ok(failures)
+    }
+
+    /// Record that a file was left out of the index, replacing any earlier record
+    pub fn record_skipped_file(
+        &self,
+        file_path: &Path,
+        reason: &str,
+        file_size: u64,
+    ) -> FlashgrepResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO skipped_files (file_path, reason, file_size, skipped_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(file_path) DO UPDATE SET reason = excluded.reason,
+                 file_size = excluded.file_size, skipped_at = excluded.skipped_at",
+            rusqlite::params![
+                file_path.to_string_lossy().to_string(),
+                reason,
+                file_size as i64,
+                chrono::Utc::now().timestamp()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Forget a skip record once the file indexes (or disappears)
+    pub fn clear_skipped_file(&self, file_path: &Path) -> FlashgrepResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM skipped_files WHERE file_path = ?1",
+            [file_path.to_string_lossy().to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Record an indexed revision, replacing any earlier record for the same commit
+    pub fn record_revision(&self, revision: &IndexedRevision) -> FlashgrepResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO revisions (commit_id, name, file_count, chunk_count, indexed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(commit_id) DO UPDATE SET name = excluded.name,
+                 file_count = excluded.file_count, chunk_count = excluded.chunk_count,
+                 indexed_at = excluded.indexed_at",
+            rusqlite::params![
+                revision.commit_id,
+                revision.name,
+                revision.file_count as i64,
+                revision.chunk_count as i64,
+                revision.indexed_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Forget an indexed revision and the chunk bodies only it referenced.
+    /// Returns `false` if the revision was not recorded.
+    pub fn delete_revision(&self, commit_id: &str) -> FlashgrepResult<bool> {
+        self.clear_revision_contents(commit_id)?;
+        let conn = self.pool.get()?;
+        let deleted = conn.execute("DELETE FROM revisions WHERE commit_id = ?1", [commit_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// List indexed revisions, most recently indexed first
+    pub fn get_revisions(&self) -> FlashgrepResult<Vec<IndexedRevision>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT commit_id, name, file_count, chunk_count, indexed_at FROM revisions
+             ORDER BY indexed_at DESC, name ASC",
+        )?;
+        let revisions = stmt
+            .query_map([], |row| {
+                Ok(IndexedRevision {
+                    commit_id: row.get(0)?,
+                    name: row.get(1)?,
+                    file_count: row.get::<_, i64>(2)? as usize,
+                    chunk_count: row.get::<_, i64>(3)? as usize,
+                    indexed_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(revisions)
+    }
+
+    /// Find an indexed revision by name, full commit id or commit id prefix
+    pub fn find_revision(&self, rev: &str) -> FlashgrepResult<Option<IndexedRevision>> {
+        Ok(self.get_revisions()?.into_iter().find(|revision| {
+            revision.name == rev
+                || revision.commit_id == rev
+                || (rev.len() >= 7 && revision.commit_id.starts_with(rev))
+        }))
+    }
+
+    /// Look up cached blame for a file's content
+    pub fn get_cached_blame(
+        &self,
+        file_path: &Path,
+        content_hash: &str,
+    ) -> FlashgrepResult<Option<BlameInfo>> {
+        let conn = self.pool.get()?;
+        let blame = conn
+            .query_row(
+                "SELECT commit_id, author, date, summary FROM blame_cache
+                 WHERE file_path = ?1 AND content_hash = ?2",
+                rusqlite::params![file_path.to_string_lossy().to_string(), content_hash],
+                |row| {
+                    Ok(BlameInfo {
+                        commit_id: row.get(0)?,
+                        author: row.get(1)?,
+                        date: row.get(2)?,
+                        summary: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(blame)
+    }
+
+    /// Cache blame for a file's content. Read-only databases write it through
+    /// a short-lived writable connection, which gives up quickly rather than
+    /// wait for an indexer holding the write lock.
+    pub fn cache_blame(
+        &self,
+        file_path: &Path,
+        content_hash: &str,
+        blame: &BlameInfo,
+    ) -> FlashgrepResult<()> {
+        let pooled = self.pool.get()?;
+        let writable;
+        let conn: &rusqlite::Connection = if pooled.is_readonly(rusqlite::DatabaseName::Main)? {
+            let path = pooled.path().map(PathBuf::from).ok_or_else(|| {
+                FlashgrepError::Config("the metadata store has no file to write to".to_string())
+            })?;
+            writable = rusqlite::Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?;
+            writable.busy_timeout(std::time::Duration::from_millis(
+                BLAME_CACHE_BUSY_TIMEOUT_MS,
+            ))?;
+            &writable
+        } else {
+            &pooled
+        };
+        conn.execute(
+            "INSERT INTO blame_cache (file_path, content_hash, commit_id, author, date, summary)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(file_path, content_hash) DO UPDATE SET commit_id = excluded.commit_id,
+                 author = excluded.author, date = excluded.date, summary = excluded.summary",
+            rusqlite::params![
+                file_path.to_string_lossy().to_string(),
+                content_hash,
+                blame.commit_id,
+                blame.author,
+                blame.date,
+                blame.summary
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// List files left out of the index, ordered by path
+    pub fn get_skipped_files(&self) -> FlashgrepResult<Vec<SkippedFile>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT file_path, reason, file_size, skipped_at FROM skipped_files
+             ORDER BY file_path ASC",
+        )?;
+        let skipped = stmt
+            .query_map([], |row| {
+                Ok(SkippedFile {
+                    file_path: PathBuf::from(row.get::<_, String>(0)?),
+                    reason: row.get(1)?,
+                    file_size: row.get::<_, i64>(2)? as u64,
+                    skipped_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(skipped)
+    }
+
+    /// Record updates about to be made, before either store is touched
+    pub fn record_journal_entries(&self, entries: &[JournalEntry]) -> FlashgrepResult<()> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO index_journal (kind, target, started_at)
+                 VALUES (?1, ?2, ?3)",
+            )?;
+            let now = chrono::Utc::now().timestamp();
+            for entry in entries {
+                let (kind, target) = entry.key();
+                stmt.execute(rusqlite::params![kind, target, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Updates recorded since the last text index commit, oldest first
+    pub fn get_journal_entries(&self) -> FlashgrepResult<Vec<JournalEntry>> {
+        let conn = self.pool.get()?;
+        let mut stmt =
+            conn.prepare("SELECT kind, target FROM index_journal ORDER BY started_at, rowid")?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .filter_map(|row| row.ok())
+            .filter_map(|(kind, target)| JournalEntry::from_key(&kind, target))
+            .collect();
+        Ok(entries)
+    }
+
+    /// Forget journaled updates once the text index commit covering them is durable
+    pub fn clear_journal(&self) -> FlashgrepResult<usize> {
+        let conn = self.pool.get()?;
+        Ok(conn.execute("DELETE FROM index_journal", [])?)
+    }
+
+    /// Read a value from `index_meta`
+    pub fn get_meta(&self, key: &str) -> FlashgrepResult<Option<String>> {
+        let conn = self.pool.get()?;
+        Ok(conn
+            .query_row(
+                "SELECT value FROM index_meta WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Store a value in `index_meta`, replacing any previous one
+    pub fn set_meta(&self, key: &str, value: &str) -> FlashgrepResult<()> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO index_meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Get index statistics
+    pub fn get_stats(&self) -> FlashgrepResult<IndexStats> {
+        let conn = self.pool.get()?;
+
+        let total_files: usize =
+            conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
+
+        let total_chunks: usize =
+            conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+
+        let total_symbols: usize =
+            conn.query_row("SELECT COUNT(*) FROM symbols", [], |row| row.get(0))?;
+
+        let unique_chunks: usize =
+            conn.query_row("SELECT COUNT(*) FROM chunk_contents", [], |row| row.get(0))?;
+
+        let last_update: Option<i64> = conn
+            .query_row("SELECT MAX(last_modified) FROM files", [], |row| row.get(0))
+            .ok();
+
+        // Calculate index size (simplified - just database file size)
+        let index_size_bytes = conn
+            .query_row(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+
+        Ok(IndexStats {
+            total_files,
+            total_chunks,
+            unique_chunks,
+            total_symbols,
+            index_size_bytes,
+            last_update,
+        })
+    }
+
+    /// Find symbols by name
+    pub fn find_symbols_by_name(&self, name: &str) -> FlashgrepResult<Vec<Symbol>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, symbol_name, file_path, line_number, symbol_type FROM symbols
+             WHERE symbol_name = ?1",
+        )?;
+
+        let symbols = stmt
+            .query_map([name], |row| {
+                Ok(Symbol {
+                    id: row.get(0)?,
+                    symbol_name: row.get(1)?,
+                    file_path: PathBuf::from(row.get::<_, String>(2)?),
+                    line_number: row.get::<_, i64>(3)? as usize,
+                    symbol_type: parse_symbol_type(&row.get::<_, String>(4)?),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(symbols)
+    }
+
+    /// Get all indexed file paths
+    pub fn get_all_files(&self) -> FlashgrepResult<Vec<PathBuf>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare("SELECT file_path FROM files")?;
+        let files = stmt
+            .query_map([], |row| {
+                let path: String = row.get(0)?;
+                Ok(PathBuf::from(path))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(files)
+    }
+
+    /// Files whose stored language is one of `languages`
+    pub fn files_with_languages(&self, languages: &[String]) -> FlashgrepResult<Vec<PathBuf>> {
+        if languages.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.pool.get()?;
+        let placeholders = vec!["?"; languages.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT file_path FROM files WHERE language IN ({}) ORDER BY file_path",
+            placeholders
+        ))?;
+        let files = stmt
+            .query_map(rusqlite::params_from_iter(languages), |row| {
+                let path: String = row.get(0)?;
+                Ok(PathBuf::from(path))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(files)
+    }
+
+    /// Load the stored chunks of a file in line order
+    pub fn get_file_chunks(&self, file_path: &Path) -> FlashgrepResult<Vec<Chunk>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.file_path, c.start_line, c.end_line, c.content_hash, cc.content,
+                    c.last_modified
+             FROM chunks c
+             INNER JOIN chunk_contents cc ON cc.content_hash = c.content_hash
+             WHERE c.file_path = ?1
+             ORDER BY c.start_line",
+        )?;
+        let chunks = stmt
+            .query_map([file_path.to_string_lossy().to_string()], |row| {
+                Ok(Chunk {
+                    id: Some(row.get(0)?),
+                    file_path: PathBuf::from(row.get::<_, String>(1)?),
+                    start_line: row.get::<_, i64>(2)? as usize,
+                    end_line: row.get::<_, i64>(3)? as usize,
+                    content_hash: row.get(4)?,
+                    content: read_content(row, 5)?,
+                    last_modified: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(chunks)
+    }
+
+    /// Stored embeddings for the given chunk bodies under a model, by content hash
+    pub fn get_embeddings(
+        &self,
+        model_id: &str,
+        content_hashes: &[String],
+    ) -> FlashgrepResult<HashMap<String, Vec<f32>>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT embedding FROM chunk_embeddings WHERE content_hash = ?1 AND model_id = ?2",
+        )?;
+        let mut embeddings = HashMap::new();
+        for hash in content_hashes {
+            if embeddings.contains_key(hash) {
+                continue;
+            }
+            let json: Option<String> = stmt
+                .query_row([hash.as_str(), model_id], |row| row.get(0))
+                .optional()?;
+            if let Some(json) = json {
+                embeddings.insert(hash.clone(), serde_json::from_str(&json)?);
+            }
+        }
+        Ok(embeddings)
+    }
+
+    /// Every file and line range whose chunk has the given content hash
+    pub fn get_chunk_locations(&self, content_hash: &str) -> FlashgrepResult<Vec<ChunkLocation>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT file_path, start_line, end_line FROM chunks
+             WHERE content_hash = ?1
+             ORDER BY file_path, start_line",
+        )?;
+        let locations = stmt
+            .query_map([content_hash], |row| {
+                Ok(ChunkLocation {
+                    file_path: PathBuf::from(row.get::<_, String>(0)?),
+                    start_line: row.get::<_, i64>(1)? as usize,
+                    end_line: row.get::<_, i64>(2)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(locations)
+    }
+
+    /// Stored chunk bodies by content hash; hashes without a body are left out
+    pub fn get_chunk_contents(
+        &self,
+        content_hashes: &[String],
+    ) -> FlashgrepResult<HashMap<String, String>> {
+        let conn = self.pool.get()?;
+        let mut unique: Vec<&String> = content_hashes.iter().collect();
+        unique.sort_unstable();
+        unique.dedup();
+        let mut contents = HashMap::new();
+        for batch in unique.chunks(CONTENT_LOOKUP_BATCH) {
+            let placeholders = vec!["?"; batch.len()].join(", ");
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT content_hash, content FROM chunk_contents WHERE content_hash IN ({})",
+                placeholders
+            ))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(batch), |row| {
+                Ok((row.get::<_, String>(0)?, read_content(row, 1)?))
+            })?;
+            for row in rows {
+                let (hash, content) = row?;
+                contents.insert(hash, content);
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Chunks of a file overlapping the 1-indexed, inclusive line range, in line order
+    pub fn get_chunks_in_range(
+        &self,
+        file_path: &Path,
+        start_line: usize,
+        end_line: usize,
+    ) -> FlashgrepResult<Vec<Chunk>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.file_path, c.start_line, c.end_line, c.content_hash, cc.content,
+                    c.last_modified
+             FROM chunks c
+             INNER JOIN chunk_contents cc ON cc.content_hash = c.content_hash
+             WHERE c.file_path = ?1 AND c.end_line >= ?2 AND c.start_line <= ?3
+             ORDER BY c.start_line",
+        )?;
+        let chunks = stmt
+            .query_map(
+                rusqlite::params![
+                    file_path.to_string_lossy().to_string(),
+                    start_line as i64,
+                    end_line as i64
+                ],
+                |row| {
+                    Ok(Chunk {
+                        id: Some(row.get(0)?),
+                        file_path: PathBuf::from(row.get::<_, String>(1)?),
+                        start_line: row.get::<_, i64>(2)? as usize,
+                        end_line: row.get::<_, i64>(3)? as usize,
+                        content_hash: row.get(4)?,
+                        content: read_content(row, 5)?,
+                        last_modified: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(chunks)
+    }
+
+    /// Release the chunk bodies referenced by an indexed revision
+    pub fn clear_revision_contents(&self, commit_id: &str) -> FlashgrepResult<usize> {
+        let conn = self.pool.get()?;
+        Ok(conn.execute(
+            "DELETE FROM revision_contents WHERE commit_id = ?1",
+            [commit_id],
+        )?)
+    }
+
+    /// Store the bodies of a revision's chunks and reference them from the revision
+    pub fn add_revision_contents(
+        &self,
+        commit_id: &str,
+        chunks: &[Chunk],
+    ) -> FlashgrepResult<usize> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let mut count = 0usize;
+        {
+            let mut content_stmt = tx.prepare(
+                "INSERT OR IGNORE INTO chunk_contents (content_hash, content) VALUES (?1, ?2)",
+            )?;
+            let mut reference_stmt = tx.prepare(
+                "INSERT OR IGNORE INTO revision_contents (commit_id, content_hash) VALUES (?1, ?2)",
+            )?;
+            for chunk in chunks {
+                content_stmt.execute(rusqlite::params![
+                    &chunk.content_hash,
+                    compress_content(&chunk.content)?
+                ])?;
+                count += reference_stmt.execute([commit_id, chunk.content_hash.as_str()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Compress chunk bodies still stored as plain text by older versions,
+    /// returning how many were rewritten
+    pub fn compress_plain_contents(&self) -> FlashgrepResult<usize> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let plain: Vec<(String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT content_hash, content FROM chunk_contents WHERE typeof(content) = 'text'",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        {
+            let mut stmt =
+                tx.prepare("UPDATE chunk_contents SET content = ?2 WHERE content_hash = ?1")?;
+            for (hash, content) in &plain {
+                stmt.execute(rusqlite::params![hash, compress_content(content)?])?;
+            }
+        }
+        tx.commit()?;
+        Ok(plain.len())
     }
 
     /// Load semantic chunks for a given model id.
@@ -577,8 +1618,8 @@ impl Database {
                 cv.file_path,
                 cv.start_line,
                 cv.end_line,
-                c.content,
-                cv.embedding,
+                cc.content,
+                ce.embedding,
                 cv.last_modified,
                 cv.content_hash
              FROM chunk_vectors cv
@@ -587,6 +1628,10 @@ impl Database {
                 AND c.start_line = cv.start_line
                 AND c.end_line = cv.end_line
                 AND c.content_hash = cv.content_hash
+             INNER JOIN chunk_contents cc ON cc.content_hash = cv.content_hash
+             INNER JOIN chunk_embeddings ce
+                 ON ce.content_hash = cv.content_hash
+                AND ce.model_id = cv.model_id
              WHERE cv.model_id = ?1",
         )?;
 
@@ -605,7 +1650,7 @@ impl Database {
                     file_path: PathBuf::from(row.get::<_, String>(0)?),
                     start_line: row.get::<_, i64>(1)? as usize,
                     end_line: row.get::<_, i64>(2)? as usize,
-                    content: row.get(3)?,
+                    content: read_content(row, 3)?,
                     embedding,
                     last_modified: row.get(5)?,
                     content_hash: row.get(6)?,
@@ -627,6 +1672,125 @@ impl Database {
         Ok(total)
     }
 
+    /// Count vectors per embedding model id
+    pub fn vector_counts_by_model(&self) -> FlashgrepResult<Vec<(String, usize)>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT model_id, COUNT(*) FROM chunk_vectors GROUP BY model_id ORDER BY model_id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// File and chunk counts grouped by detected language
+    pub fn language_breakdown(&self) -> FlashgrepResult<Vec<(String, usize, usize)>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT COALESCE(f.language, 'unknown'),
+                    COUNT(*),
+                    COALESCE(SUM((SELECT COUNT(*) FROM chunks c WHERE c.file_path = f.file_path)), 0)
+             FROM files f
+             GROUP BY 1
+             ORDER BY 1",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Every indexed file with its chunk count
+    pub fn file_chunk_counts(&self) -> FlashgrepResult<Vec<(PathBuf, usize)>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT f.file_path, (SELECT COUNT(*) FROM chunks c WHERE c.file_path = f.file_path)
+             FROM files f",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((PathBuf::from(row.get::<_, String>(0)?), row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Symbol counts grouped by symbol type
+    pub fn symbol_type_counts(&self) -> FlashgrepResult<Vec<(String, usize)>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT symbol_type, COUNT(*) FROM symbols GROUP BY symbol_type ORDER BY symbol_type",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Line counts of every stored chunk
+    pub fn chunk_line_counts(&self) -> FlashgrepResult<Vec<usize>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare("SELECT end_line - start_line + 1 FROM chunks")?;
+        let rows = stmt
+            .query_map([], |row| Ok(row.get::<_, i64>(0)?.max(0) as usize))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Count chunks, symbols and vectors that no longer belong to an indexed file.
+    ///
+    /// Vectors are also orphaned when no chunk matches their line range and hash,
+    /// which happens when a file was re-chunked without refreshing embeddings.
+    pub fn orphaned_row_counts(&self) -> FlashgrepResult<OrphanedRows> {
+        let conn = self.pool.get()?;
+        let count = |sql: &str| -> FlashgrepResult<usize> {
+            Ok(conn.query_row(sql, [], |row| row.get::<_, usize>(0))?)
+        };
+        Ok(OrphanedRows {
+            chunks: count(&format!("SELECT COUNT(*) FROM chunks {}", ORPHANED_BY_FILE))?,
+            symbols: count(&format!(
+                "SELECT COUNT(*) FROM symbols {}",
+                ORPHANED_BY_FILE
+            ))?,
+            vectors: count(&format!(
+                "SELECT COUNT(*) FROM chunk_vectors {}",
+                ORPHANED_VECTORS
+            ))?,
+        })
+    }
+
+    /// Delete orphaned chunks, symbols and vectors in one transaction
+    pub fn delete_orphaned_rows(&self) -> FlashgrepResult<OrphanedRows> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let deleted = OrphanedRows {
+            chunks: tx.execute(&format!("DELETE FROM chunks {}", ORPHANED_BY_FILE), [])?,
+            symbols: tx.execute(&format!("DELETE FROM symbols {}", ORPHANED_BY_FILE), [])?,
+            vectors: tx.execute(
+                &format!("DELETE FROM chunk_vectors {}", ORPHANED_VECTORS),
+                [],
+            )?,
+        };
+        // Shared rows are released by triggers; this catches any left by older versions
+        tx.execute(
+            "DELETE FROM chunk_contents
+             WHERE content_hash NOT IN (SELECT content_hash FROM chunks)
+               AND content_hash NOT IN (SELECT content_hash FROM revision_contents)",
+            [],
+        )?;
+        tx.execute(
+            "DELETE FROM chunk_embeddings WHERE NOT EXISTS (
+                SELECT 1 FROM chunk_vectors cv
+                WHERE cv.content_hash = chunk_embeddings.content_hash
+                  AND cv.model_id = chunk_embeddings.model_id
+             )",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
     /// Run VACUUM to optimize database file size
     pub fn vacuum(&self) -> FlashgrepResult<()> {
         let conn = self.pool.get()?;
@@ -654,7 +1818,15 @@ impl Database {
         conn.execute("DELETE FROM knowledge_graph_edges", [])?;
         conn.execute("DELETE FROM knowledge_graph_nodes", [])?;
         conn.execute("DELETE FROM chunks", [])?;
+        conn.execute("DELETE FROM revision_contents", [])?;
+        conn.execute("DELETE FROM chunk_contents", [])?;
+        conn.execute("DELETE FROM chunk_embeddings", [])?;
         conn.execute("DELETE FROM files", [])?;
+        conn.execute("DELETE FROM index_failures", [])?;
+        conn.execute("DELETE FROM skipped_files", [])?;
+        conn.execute("DELETE FROM revisions", [])?;
+        conn.execute("DELETE FROM blame_cache", [])?;
+        conn.execute("DELETE FROM index_journal", [])?;
         conn.execute("UPDATE graph_meta SET revision = 0 WHERE id = 1", [])?;
 
         debug!("Database cleared: all tables emptied");
@@ -662,6 +1834,52 @@ impl Database {
     }
 }
 
+/// Per-connection settings, applied to every pooled connection as it is opened
+fn configure_connection(conn: &mut rusqlite::Connection) -> rusqlite::Result<()> {
+    // Wait for another process's write lock instead of failing with SQLITE_BUSY
+    conn.busy_timeout(std::time::Duration::from_millis(BUSY_TIMEOUT_MS as u64))?;
+
+    // Enable foreign keys
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+
+    // Increase cache size to ~100MB (in pages of 4KB)
+    conn.pragma_update(None, "cache_size", -25000)?;
+
+    // Set synchronous mode to NORMAL for better performance (still safe with WAL)
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+
+    // Enable memory-mapped I/O (256MB)
+    conn.pragma_update(None, "mmap_size", 268435456)?;
+
+    // Set temp store to memory for better performance
+    conn.pragma_update(None, "temp_store", "MEMORY")?;
+    Ok(())
+}
+
+/// Compress a chunk body for `chunk_contents`
+fn compress_content(content: &str) -> FlashgrepResult<Vec<u8>> {
+    Ok(zstd::bulk::compress(
+        content.as_bytes(),
+        CONTENT_COMPRESSION_LEVEL,
+    )?)
+}
+
+/// Read a chunk body column; bodies stored as plain text by older versions are
+/// returned as they are
+fn read_content(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<String> {
+    match row.get_ref(idx)? {
+        ValueRef::Blob(bytes) => {
+            let conversion_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+                rusqlite::Error::FromSqlConversionFailure(idx, Type::Blob, e)
+            };
+            let decoded =
+                zstd::stream::decode_all(bytes).map_err(|e| conversion_error(e.into()))?;
+            String::from_utf8(decoded).map_err(|e| conversion_error(e.into()))
+        }
+        _ => row.get(idx),
+    }
+}
+
 fn parse_symbol_type(s: &str) -> models::SymbolType {
     use models::SymbolType;
     match s {
@@ -679,6 +1897,17 @@ fn parse_symbol_type(s: &str) -> models::SymbolType {
     }
 }
 
+/// Rewrite a graph node id (`file:<path>`, `chunk:<path>:<lines>`,
+/// `symbol:<path>:<line>:<name>`) for a file moved from `from` to `to`
+fn rekey_graph_node(node_id: &str, from: &str, to: &str) -> String {
+    match node_id.split_once(':') {
+        Some((kind, rest)) if rest.starts_with(from) => {
+            format!("{}:{}{}", kind, to, &rest[from.len()..])
+        }
+        _ => node_id.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -710,6 +1939,7 @@ mod tests {
             file_size: 100,
             last_modified: 1234567890,
             language: Some("rust".to_string()),
+            encoding: None,
         };
         db.insert_file(&file)?;
 
@@ -751,6 +1981,7 @@ mod tests {
             file_size: 10,
             last_modified: 123,
             language: Some("rust".to_string()),
+            encoding: None,
         };
         let file_b = FileMetadata {
             id: None,
@@ -758,6 +1989,7 @@ mod tests {
             file_size: 20,
             last_modified: 124,
             language: Some("rust".to_string()),
+            encoding: None,
         };
         db.insert_file(&file_a)?;
         db.insert_file(&file_b)?;
@@ -785,6 +2017,7 @@ mod tests {
             file_size: 10,
             last_modified: 1000,
             language: Some("rust".to_string()),
+            encoding: None,
         };
         db.insert_file(&file)?;
 
@@ -819,4 +2052,442 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_rename_file_rekeys_vectors_and_graph() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+
+        db.insert_file(&FileMetadata {
+            id: None,
+            file_path: PathBuf::from("src/old.rs"),
+            file_size: 20,
+            last_modified: 1000,
+            language: Some("rust".to_string()),
+            encoding: None,
+        })?;
+        let chunk = Chunk::new(
+            PathBuf::from("src/old.rs"),
+            1,
+            1,
+            "fn moved() {}".to_string(),
+            1000,
+        );
+        db.insert_chunks_batch(std::slice::from_ref(&chunk))?;
+        db.upsert_chunk_vectors_batch(&[ChunkVector {
+            id: None,
+            file_path: PathBuf::from("src/old.rs"),
+            start_line: 1,
+            end_line: 1,
+            content_hash: chunk.content_hash.clone(),
+            embedding: vec![0.5, 0.5],
+            model_id: "test-model".to_string(),
+            last_modified: 1000,
+        }])?;
+        let node = |id: &str, kind: &str| {
+            (
+                id.to_string(),
+                kind.to_string(),
+                "src/old.rs".to_string(),
+                1000,
+            )
+        };
+        db.upsert_graph_nodes(&[
+            node("file:src/old.rs", "file"),
+            node("chunk:src/old.rs:1-1", "chunk"),
+        ])?;
+        db.upsert_graph_edges(&[(
+            "file:src/old.rs".to_string(),
+            "chunk:src/old.rs:1-1".to_string(),
+            "contains".to_string(),
+            "src/old.rs".to_string(),
+        )])?;
+        let revision = db.graph_revision()?;
+
+        assert!(db.rename_file(Path::new("src/old.rs"), Path::new("lib/new.rs"))?);
+        assert!(!db.rename_file(Path::new("src/old.rs"), Path::new("lib/new.rs"))?);
+
+        assert_eq!(db.get_all_files()?, vec![PathBuf::from("lib/new.rs")]);
+        assert!(!db.needs_reindex(Path::new("lib/new.rs"), 1000)?);
+        let chunks = db.get_file_chunks(Path::new("lib/new.rs"))?;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "fn moved() {}");
+        let vectors = db.get_semantic_chunks("test-model")?;
+        assert_eq!(vectors.len(), 1);
+        assert_eq!(vectors[0].file_path, PathBuf::from("lib/new.rs"));
+        assert_eq!(vectors[0].embedding, vec![0.5, 0.5]);
+        assert!(db.graph_revision()? > revision);
+
+        let conn = db.pool.get()?;
+        let node_ids: Vec<String> = conn
+            .prepare("SELECT node_id FROM knowledge_graph_nodes ORDER BY node_id")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(node_ids, vec!["chunk:lib/new.rs:1-1", "file:lib/new.rs"]);
+        let edge: (String, String, String) = conn.query_row(
+            "SELECT from_node, to_node, file_path FROM knowledge_graph_edges",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        assert_eq!(
+            edge,
+            (
+                "file:lib/new.rs".to_string(),
+                "chunk:lib/new.rs:1-1".to_string(),
+                "lib/new.rs".to_string()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_identical_chunks_share_body_and_embedding() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+        let content = "fn vendored() {}".to_string();
+        for name in ["a.rs", "b.rs"] {
+            db.insert_file(&FileMetadata {
+                id: None,
+                file_path: PathBuf::from(name),
+                file_size: 16,
+                last_modified: 1000,
+                language: Some("rust".to_string()),
+                encoding: None,
+            })?;
+            let chunk = Chunk::new(PathBuf::from(name), 1, 1, content.clone(), 1000);
+            db.insert_chunks_batch(std::slice::from_ref(&chunk))?;
+            db.upsert_chunk_vectors_batch(&[ChunkVector {
+                id: None,
+                file_path: PathBuf::from(name),
+                start_line: 1,
+                end_line: 1,
+                content_hash: chunk.content_hash.clone(),
+                embedding: vec![1.0, 0.0],
+                model_id: "test-model".to_string(),
+                last_modified: 1000,
+            }])?;
+        }
+        let hash = Chunk::new(PathBuf::from("a.rs"), 1, 1, content.clone(), 0).content_hash;
+        let shared_rows = |db: &Database| -> FlashgrepResult<(usize, usize)> {
+            let conn = db.pool.get()?;
+            Ok((
+                conn.query_row("SELECT COUNT(*) FROM chunk_contents", [], |r| r.get(0))?,
+                conn.query_row("SELECT COUNT(*) FROM chunk_embeddings", [], |r| r.get(0))?,
+            ))
+        };
+
+        assert_eq!(shared_rows(&db)?, (1, 1));
+        assert_eq!(db.get_chunk_locations(&hash)?.len(), 2);
+        assert_eq!(db.get_semantic_chunks("test-model")?.len(), 2);
+        assert_eq!(
+            db.get_embeddings("test-model", std::slice::from_ref(&hash))?[&hash],
+            vec![1.0, 0.0]
+        );
+
+        // The body stays while any chunk refers to it
+        db.delete_file_vectors(Path::new("a.rs"))?;
+        db.delete_file_chunks(Path::new("a.rs"))?;
+        assert_eq!(shared_rows(&db)?, (1, 1));
+        assert_eq!(db.get_file_chunks(Path::new("b.rs"))?[0].content, content);
+        db.delete_file_vectors(Path::new("b.rs"))?;
+        db.delete_file_chunks(Path::new("b.rs"))?;
+        assert_eq!(shared_rows(&db)?, (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_chunk_bodies_move_to_shared_tables() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db_path = temp_dir.path().join("legacy.db");
+        {
+            let conn = rusqlite::Connection::open(&db_path)?;
+            conn.execute_batch(
+                "CREATE TABLE chunks (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    file_path TEXT NOT NULL,
+                    start_line INTEGER NOT NULL,
+                    end_line INTEGER NOT NULL,
+                    content_hash TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    last_modified INTEGER NOT NULL
+                 );
+                 CREATE TABLE chunk_vectors (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    file_path TEXT NOT NULL,
+                    start_line INTEGER NOT NULL,
+                    end_line INTEGER NOT NULL,
+                    content_hash TEXT NOT NULL,
+                    embedding TEXT NOT NULL,
+                    model_id TEXT NOT NULL,
+                    last_modified INTEGER NOT NULL,
+                    UNIQUE(file_path, start_line, end_line, content_hash)
+                 );
+                 INSERT INTO chunks (file_path, start_line, end_line, content_hash, content, last_modified)
+                    VALUES ('a.rs', 1, 1, 'h1', 'fn legacy() {}', 1000);
+                 INSERT INTO chunk_vectors
+                    (file_path, start_line, end_line, content_hash, embedding, model_id, last_modified)
+                    VALUES ('a.rs', 1, 1, 'h1', '[0.25]', 'm', 1000);",
+            )?;
+        }
+
+        let db = Database::open(&db_path)?;
+        let chunks = db.get_file_chunks(Path::new("a.rs"))?;
+        assert_eq!(chunks[0].content, "fn legacy() {}");
+        let vectors = db.get_semantic_chunks("m")?;
+        assert_eq!(vectors[0].embedding, vec![0.25]);
+        let conn = db.pool.get()?;
+        assert!(!Database::has_column(&conn, "chunks", "content")?);
+        assert!(!Database::has_column(&conn, "chunk_vectors", "embedding")?);
+        assert_eq!(
+            db.get_meta("schema_version")?,
+            Some(SCHEMA_VERSION.to_string())
+        );
+
+        // Moved bodies stay plain text until compaction compresses them
+        let body_type = "SELECT typeof(content) FROM chunk_contents WHERE content_hash = 'h1'";
+        assert_eq!(
+            conn.query_row(body_type, [], |r| r.get::<_, String>(0))?,
+            "text"
+        );
+        assert_eq!(db.compress_plain_contents()?, 1);
+        assert_eq!(
+            conn.query_row(body_type, [], |r| r.get::<_, String>(0))?,
+            "blob"
+        );
+        assert_eq!(
+            db.get_file_chunks(Path::new("a.rs"))?[0].content,
+            "fn legacy() {}"
+        );
+        assert_eq!(db.compress_plain_contents()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_version_runs_pending_migrations_and_rejects_newer() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db_path = temp_dir.path().join("test.db");
+        drop(Database::open(&db_path)?);
+
+        // A database stopped at version 2 still gets the revision-aware trigger
+        {
+            let conn = rusqlite::Connection::open(&db_path)?;
+            conn.execute_batch(
+                "DROP TRIGGER chunks_release_content;
+                 UPDATE index_meta SET value = '2' WHERE key = 'schema_version';",
+            )?;
+        }
+        let db = Database::open(&db_path)?;
+        assert_eq!(
+            db.get_meta("schema_version")?,
+            Some(SCHEMA_VERSION.to_string())
+        );
+        let triggers: i64 = db.pool.get()?.query_row(
+            "SELECT COUNT(*) FROM sqlite_master
+             WHERE type = 'trigger' AND name = 'chunks_release_content'",
+            [],
+            |r| r.get(0),
+        )?;
+        assert_eq!(triggers, 1);
+
+        db.set_meta("schema_version", &(SCHEMA_VERSION + 1).to_string())?;
+        drop(db);
+        let err = Database::open(&db_path)
+            .err()
+            .expect("newer schema must be rejected");
+        assert!(matches!(err, FlashgrepError::RebuildRequired(_)));
+        assert_eq!(err.exit_code(), 11);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_open_sees_writes_and_rejects_its_own() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db_path = temp_dir.path().join("test.db");
+        assert!(matches!(
+            Database::open_read_only(&db_path),
+            Err(FlashgrepError::IndexNotFound(_))
+        ));
+
+        let writer = Database::open(&db_path)?;
+        let reader = Database::open_read_only(&db_path)?;
+        writer.set_meta("probe", "1")?;
+        assert_eq!(reader.get_meta("probe")?, Some("1".to_string()));
+        assert!(reader.set_meta("probe", "2").is_err());
+
+        let journal_mode: String =
+            reader
+                .pool
+                .get()?
+                .query_row("PRAGMA journal_mode", [], |r| r.get(0))?;
+        assert_eq!(journal_mode, "wal");
+
+        // An older database is left for the indexer to migrate
+        writer.set_meta("schema_version", "2")?;
+        drop((writer, reader));
+        let err = Database::open_read_only(&db_path)
+            .err()
+            .expect("older schema must not be opened read-only");
+        assert!(matches!(err, FlashgrepError::MigrationRequired(_)));
+        assert_eq!(err.exit_code(), 12);
+        let writer = Database::open(&db_path)?;
+        assert_eq!(
+            writer.get_meta("schema_version")?,
+            Some(SCHEMA_VERSION.to_string())
+        );
+        drop(writer);
+        Database::open_read_only(&db_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk_bodies_are_compressed_and_kept_for_revisions() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+        let file_path = PathBuf::from("src/lib.rs");
+        let content = "fn repeated() { do_work(); }\n".repeat(50);
+        db.insert_file(&FileMetadata {
+            id: None,
+            file_path: file_path.clone(),
+            file_size: content.len() as u64,
+            last_modified: 1000,
+            language: Some("rust".to_string()),
+            encoding: None,
+        })?;
+        let chunk = Chunk::new(file_path.clone(), 1, 50, content.clone(), 1000);
+        db.insert_chunks_batch(std::slice::from_ref(&chunk))?;
+        db.add_revision_contents("abc123", std::slice::from_ref(&chunk))?;
+
+        let stored_len: usize = db.pool.get()?.query_row(
+            "SELECT length(content) FROM chunk_contents WHERE content_hash = ?1",
+            [&chunk.content_hash],
+            |r| r.get(0),
+        )?;
+        assert!(stored_len < content.len() / 4);
+        let contents = db.get_chunk_contents(&[chunk.content_hash.clone(), "missing".into()])?;
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[&chunk.content_hash], content);
+
+        // The revision still refers to the body after the working-tree file is gone
+        db.delete_file(&file_path)?;
+        assert_eq!(
+            db.get_chunk_contents(std::slice::from_ref(&chunk.content_hash))?
+                .len(),
+            1
+        );
+        db.delete_orphaned_rows()?;
+        assert_eq!(
+            db.get_chunk_contents(std::slice::from_ref(&chunk.content_hash))?
+                .len(),
+            1
+        );
+        assert_eq!(db.clear_revision_contents("abc123")?, 1);
+        assert!(db
+            .get_chunk_contents(std::slice::from_ref(&chunk.content_hash))?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_entries_record_and_clear() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+        assert!(db.get_journal_entries()?.is_empty());
+
+        let file = JournalEntry::File(PathBuf::from("/repo/src/lib.rs"));
+        let revision = JournalEntry::Revision("abc123".to_string());
+        db.record_journal_entries(&[file.clone(), revision.clone()])?;
+        // Recording the same update twice keeps one entry
+        db.record_journal_entries(std::slice::from_ref(&file))?;
+        assert_eq!(db.get_journal_entries()?, vec![file, revision]);
+
+        assert_eq!(db.clear_journal()?, 2);
+        assert!(db.get_journal_entries()?.is_empty());
+
+        db.record_journal_entries(&[JournalEntry::Clear])?;
+        db.clear_all()?;
+        assert!(db.get_journal_entries()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_failures_record_and_clear() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+        let path = PathBuf::from("broken.rs");
+
+        db.record_index_failure(&path, "stream did not contain valid UTF-8")?;
+        db.record_index_failure(&path, "permission denied")?;
+        let failures = db.get_index_failures()?;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].reason, "permission denied");
+
+        db.clear_index_failure(&path)?;
+        assert!(db.get_index_failures()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_skipped_files_record_and_clear() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+        let path = PathBuf::from("fixtures/blob.json");
+
+        db.record_skipped_file(&path, "binary", 10)?;
+        db.record_skipped_file(&path, "too_large", 99)?;
+        let skipped = db.get_skipped_files()?;
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].reason, "too_large");
+        assert_eq!(skipped[0].file_size, 99);
+
+        db.clear_skipped_file(&path)?;
+        assert!(db.get_skipped_files()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_orphaned_vectors_detected_and_deleted() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let db = Database::open(&temp_dir.path().join("test.db"))?;
+
+        db.insert_file(&FileMetadata {
+            id: None,
+            file_path: PathBuf::from("auth.rs"),
+            file_size: 10,
+            last_modified: 1000,
+            language: Some("rust".to_string()),
+            encoding: None,
+        })?;
+        let chunk = Chunk::new(
+            PathBuf::from("auth.rs"),
+            1,
+            5,
+            "fn auth_handler() {}".to_string(),
+            1000,
+        );
+        db.insert_chunks_batch(std::slice::from_ref(&chunk))?;
+
+        let vector = |start_line, content_hash: &str| ChunkVector {
+            id: None,
+            file_path: PathBuf::from("auth.rs"),
+            start_line,
+            end_line: 5,
+            content_hash: content_hash.to_string(),
+            embedding: vec![0.1, 0.2, 0.3],
+            model_id: "test-model".to_string(),
+            last_modified: 1000,
+        };
+        db.upsert_chunk_vectors_batch(&[vector(1, &chunk.content_hash), vector(2, "stale")])?;
+
+        let orphaned = db.orphaned_row_counts()?;
+        assert_eq!(orphaned.vectors, 1);
+        assert_eq!(orphaned.chunks, 0);
+
+        assert_eq!(db.delete_orphaned_rows()?.vectors, 1);
+        assert_eq!(db.orphaned_row_counts()?.total(), 0);
+        assert_eq!(db.count_vectors_for_model("test-model")?, 1);
+
+        Ok(())
+    }
 }
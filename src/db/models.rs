@@ -1,5 +1,7 @@
+use crate::config::languages::LanguageMap;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Represents a chunk of a file for indexing
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -164,27 +166,23 @@ pub struct FileMetadata {
 
     /// Detected programming language
     pub language: Option<String>,
+
+    /// Detected text encoding (e.g. `UTF-8`, `UTF-16LE`, `Shift_JIS`)
+    #[serde(default)]
+    pub encoding: Option<String>,
 }
 
 impl FileMetadata {
-    /// Detect language from file extension
+    /// Detect language from the file name or extension using the built-in language map
     pub fn detect_language(path: &Path) -> Option<String> {
-        path.extension().and_then(|ext| ext.to_str()).map(|ext| {
-            match ext.to_lowercase().as_str() {
-                "rs" => "rust",
-                "go" => "go",
-                "js" => "javascript",
-                "ts" => "typescript",
-                "py" => "python",
-                "sol" => "solidity",
-                "json" => "json",
-                "md" => "markdown",
-                "yaml" | "yml" => "yaml",
-                "toml" => "toml",
-                _ => "unknown",
-            }
-            .to_string()
-        })
+        static LANGUAGES: OnceLock<LanguageMap> = OnceLock::new();
+        match LANGUAGES
+            .get_or_init(LanguageMap::default)
+            .detect_path(path)
+        {
+            Some(def) => Some(def.id.clone()),
+            None => path.extension().map(|_| "unknown".to_string()),
+        }
     }
 
     /// Create metadata from a file path
@@ -203,6 +201,7 @@ impl FileMetadata {
             file_size,
             last_modified,
             language,
+            encoding: None,
         })
     }
 }
@@ -231,6 +230,51 @@ pub struct SearchResult {
     /// The actual content (if explicitly requested)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+
+    /// Commit id when the match comes from an indexed git revision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+
+    /// Changed line ranges overlapping the match, for diff-scoped queries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_hunks: Vec<LineRange>,
+
+    /// Last commit touching the matched lines, when blame was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<BlameInfo>,
+
+    /// Other places with the same content, collapsed into this result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_in: Vec<ChunkLocation>,
+}
+
+/// A file and line range holding a chunk
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChunkLocation {
+    pub file_path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// An inclusive, 1-indexed range of lines
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl LineRange {
+    pub fn new(start_line: usize, end_line: usize) -> Self {
+        Self {
+            start_line,
+            end_line,
+        }
+    }
+
+    /// Whether this range shares at least one line with `start..=end`
+    pub fn overlaps(&self, start_line: usize, end_line: usize) -> bool {
+        self.start_line <= end_line && start_line <= self.end_line
+    }
 }
 
 /// Statistics about the index
@@ -242,6 +286,10 @@ pub struct IndexStats {
     /// Total number of chunks
     pub total_chunks: usize,
 
+    /// Number of distinct chunk bodies; duplicated chunks share one
+    #[serde(default)]
+    pub unique_chunks: usize,
+
     /// Total number of detected symbols
     pub total_symbols: usize,
 
@@ -252,6 +300,106 @@ pub struct IndexStats {
     pub last_update: Option<i64>,
 }
 
+/// A file that could not be indexed, and why
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexFailure {
+    /// Path to the file
+    pub file_path: PathBuf,
+
+    /// Error message from the failed attempt
+    pub reason: String,
+
+    /// Unix timestamp of the failed attempt
+    pub failed_at: i64,
+}
+
+/// A file deliberately left out of the index, and why
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkippedFile {
+    /// Path to the file
+    pub file_path: PathBuf,
+
+    /// Skip reason (`binary`, `too_large`, `invalid_encoding`)
+    pub reason: String,
+
+    /// File size in bytes when it was skipped
+    pub file_size: u64,
+
+    /// Unix timestamp of the skip
+    pub skipped_at: i64,
+}
+
+/// An update started on the metadata store and text index that is not yet
+/// covered by a text index commit
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JournalEntry {
+    /// A working-tree file's documents, rows, symbols and vectors
+    File(PathBuf),
+    /// The documents and chunk bodies of an indexed git revision
+    Revision(String),
+    /// Removal of everything from both stores
+    Clear,
+}
+
+impl JournalEntry {
+    /// Kind and target stored in `index_journal`
+    pub fn key(&self) -> (&'static str, String) {
+        match self {
+            JournalEntry::File(path) => ("file", path.to_string_lossy().to_string()),
+            JournalEntry::Revision(commit_id) => ("revision", commit_id.clone()),
+            JournalEntry::Clear => ("clear", String::new()),
+        }
+    }
+
+    /// Rebuild an entry from its stored kind and target
+    pub fn from_key(kind: &str, target: String) -> Option<Self> {
+        match kind {
+            "file" => Some(JournalEntry::File(PathBuf::from(target))),
+            "revision" => Some(JournalEntry::Revision(target)),
+            "clear" => Some(JournalEntry::Clear),
+            _ => None,
+        }
+    }
+}
+
+/// A git revision whose tree has been indexed alongside the working tree
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexedRevision {
+    /// Full commit id the revision resolved to
+    pub commit_id: String,
+
+    /// Revision name as given (branch, tag or commit-ish)
+    pub name: String,
+
+    /// Number of files indexed from the revision's tree
+    pub file_count: usize,
+
+    /// Number of chunks indexed from the revision's tree
+    pub chunk_count: usize,
+
+    /// Unix timestamp of the indexing run
+    pub indexed_at: i64,
+}
+
+/// The most recent commit touching a range of lines, from `git blame`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlameInfo {
+    /// Commit id; all zeros for lines with uncommitted changes
+    pub commit_id: String,
+    pub author: String,
+    /// Author date as a Unix timestamp
+    pub date: i64,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+impl BlameInfo {
+    /// Whether the lines have changes that are not committed yet
+    pub fn is_uncommitted(&self) -> bool {
+        self.commit_id.bytes().all(|b| b == b'0')
+    }
+}
+
 /// Calculate SHA256 hash of content
 fn calculate_hash(content: &str) -> String {
     use sha2::{Digest, Sha256};
@@ -260,6 +408,20 @@ fn calculate_hash(content: &str) -> String {
     hex::encode(hasher.finalize())
 }
 
+/// Metadata rows that no longer belong to an indexed file or chunk
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OrphanedRows {
+    pub chunks: usize,
+    pub symbols: usize,
+    pub vectors: usize,
+}
+
+impl OrphanedRows {
+    pub fn total(&self) -> usize {
+        self.chunks + self.symbols + self.vectors
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
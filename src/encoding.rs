@@ -0,0 +1,275 @@
+//! Text encoding detection and transcoding
+//!
+//! Files are indexed and read as UTF-8. Other encodings are detected from a byte
+//! order mark, a UTF-16 zero-byte heuristic, or statistical detection for legacy
+//! encodings (Latin-1, Shift-JIS, ...), and transcoded. Writes go back out in
+//! the file's original encoding.
+
+use crate::{FlashgrepError, FlashgrepResult};
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+
+/// Bytes read from the start of a file to detect its encoding
+const DETECTION_SAMPLE_BYTES: u64 = 8 * 1024;
+
+/// Minimum share of code units with a zero high byte for BOM-less UTF-16 detection
+const UTF16_ZERO_RATIO: f64 = 0.3;
+
+/// Encoding of a text file and whether it starts with a byte order mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+/// File content transcoded to UTF-8, with the encoding it was stored in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub content: String,
+    pub encoding: TextEncoding,
+}
+
+impl Default for TextEncoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+impl TextEncoding {
+    pub const UTF8: Self = Self {
+        encoding: UTF_8,
+        bom: false,
+    };
+
+    /// WHATWG name of the encoding, e.g. `UTF-8`, `UTF-16LE`, `Shift_JIS`
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// Detect the encoding of `bytes`, which may be a prefix of the file.
+    /// Returns `None` for content that looks binary.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+            return Some(Self {
+                encoding,
+                bom: true,
+            });
+        }
+        if is_utf8_prefix(bytes) {
+            return Some(Self::UTF8);
+        }
+        if let Some(encoding) = detect_utf16(bytes) {
+            return Some(Self {
+                encoding,
+                bom: false,
+            });
+        }
+        if bytes.contains(&0) {
+            return None;
+        }
+
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        Some(Self {
+            encoding: detector.guess(None, false),
+            bom: false,
+        })
+    }
+
+    /// Decode a whole file's bytes, returning `None` if they are malformed in this encoding
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        let body = if self.bom {
+            &bytes[Encoding::for_bom(bytes).map_or(0, |(_, len)| len)..]
+        } else {
+            bytes
+        };
+        if self.encoding == UTF_8 {
+            return String::from_utf8(body.to_vec()).ok();
+        }
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(body)
+            .map(|content| content.into_owned())
+    }
+
+    /// Encode UTF-8 text back into this encoding, including the byte order mark if present
+    pub fn encode(&self, text: &str) -> FlashgrepResult<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let little_endian = self.encoding == UTF_16LE;
+            let bom = self.bom.then_some(0xFEFF);
+            for unit in bom.into_iter().chain(text.encode_utf16()) {
+                bytes.extend_from_slice(&if little_endian {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                });
+            }
+            return Ok(bytes);
+        }
+
+        if self.bom && self.encoding == UTF_8 {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        let (encoded, _, unmappable) = self.encoding.encode(text);
+        if unmappable {
+            return Err(FlashgrepError::Config(format!(
+                "Text contains characters that cannot be written in {}",
+                self.name()
+            )));
+        }
+        bytes.extend_from_slice(&encoded);
+        Ok(bytes)
+    }
+}
+
+/// Detect the encoding of `bytes` and transcode them to UTF-8.
+/// Returns `None` for binary or malformed content.
+pub fn decode(bytes: &[u8]) -> Option<DecodedText> {
+    let encoding = TextEncoding::detect(bytes)?;
+    let content = encoding.decode(bytes)?;
+    Some(DecodedText { content, encoding })
+}
+
+/// Detect a file's encoding from its first few kilobytes, returning `None` if it looks binary
+pub fn detect_file(path: &Path) -> FlashgrepResult<Option<TextEncoding>> {
+    let mut sample = Vec::new();
+    std::fs::File::open(path)?
+        .take(DETECTION_SAMPLE_BYTES)
+        .read_to_end(&mut sample)?;
+    Ok(TextEncoding::detect(&sample))
+}
+
+/// Read a text file in any supported encoding, returning `None` if it is not text
+pub fn read_text(path: &Path) -> FlashgrepResult<Option<DecodedText>> {
+    Ok(decode(&std::fs::read(path)?))
+}
+
+/// Read a text file as a UTF-8 string, failing with `InvalidData` if it is not text
+pub fn read_to_string(path: &Path) -> FlashgrepResult<String> {
+    read_decoded(path).map(|decoded| decoded.content)
+}
+
+/// Read a text file with its encoding, failing with `InvalidData` if it is not text
+pub fn read_decoded(path: &Path) -> FlashgrepResult<DecodedText> {
+    decode_file_bytes(path, &std::fs::read(path)?)
+}
+
+/// Decode bytes read from `path`, failing with `InvalidData` if they are not text
+pub fn decode_file_bytes(path: &Path, bytes: &[u8]) -> FlashgrepResult<DecodedText> {
+    decode(bytes).ok_or_else(|| {
+        FlashgrepError::Io(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{} is not a text file", path.display()),
+        ))
+    })
+}
+
+/// Write UTF-8 text to `path` in the given encoding
+pub fn write_text(path: &Path, text: &str, encoding: TextEncoding) -> FlashgrepResult<()> {
+    std::fs::write(path, encoding.encode(text)?)?;
+    Ok(())
+}
+
+/// Wrap a reader so it yields UTF-8 from content stored in `encoding`
+pub fn utf8_reader<R: Read>(reader: R, encoding: TextEncoding) -> impl Read {
+    encoding_rs_io::DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding.encoding))
+        .bom_override(true)
+        .build(reader)
+}
+
+/// Valid UTF-8, allowing a multi-byte sequence cut off at the end of a sample
+fn is_utf8_prefix(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => !text.contains('\0'),
+        Err(err) => err.error_len().is_none() && !bytes[..err.valid_up_to()].contains(&0),
+    }
+}
+
+/// Recognise BOM-less UTF-16 by zero bytes concentrated in one half of each code unit
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let units = bytes.len() / 2;
+    if units < 2 {
+        return None;
+    }
+    let (mut even_zeros, mut odd_zeros) = (0usize, 0usize);
+    for pair in bytes.chunks_exact(2) {
+        even_zeros += usize::from(pair[0] == 0);
+        odd_zeros += usize::from(pair[1] == 0);
+    }
+    let ratio = |zeros: usize| zeros as f64 / units as f64;
+    let encoding = if ratio(odd_zeros) >= UTF16_ZERO_RATIO && even_zeros == 0 {
+        UTF_16LE
+    } else if ratio(even_zeros) >= UTF16_ZERO_RATIO && odd_zeros == 0 {
+        UTF_16BE
+    } else {
+        return None;
+    };
+    let (_, malformed) = encoding.decode_without_bom_handling(&bytes[..units * 2]);
+    (!malformed).then_some(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+    fn utf16(text: &str, little_endian: bool, bom: bool) -> Vec<u8> {
+        let encoding = TextEncoding {
+            encoding: if little_endian { UTF_16LE } else { UTF_16BE },
+            bom,
+        };
+        encoding.encode(text).unwrap()
+    }
+
+    #[test]
+    fn detects_and_round_trips_encodings() {
+        let text = "fn main() {\n    println!(\"héllo\");\n}\n";
+        let cases = [
+            (text.as_bytes().to_vec(), "UTF-8", false),
+            (
+                [b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat(),
+                "UTF-8",
+                true,
+            ),
+            (utf16(text, true, true), "UTF-16LE", true),
+            (utf16(text, false, true), "UTF-16BE", true),
+            (utf16(text, true, false), "UTF-16LE", false),
+            (utf16(text, false, false), "UTF-16BE", false),
+        ];
+        for (bytes, name, bom) in cases {
+            let decoded = decode(&bytes).unwrap_or_else(|| panic!("decode {}", name));
+            assert_eq!(decoded.content, text, "{}", name);
+            assert_eq!(decoded.encoding.name(), name);
+            assert_eq!(decoded.encoding.bom, bom, "{}", name);
+            assert_eq!(decoded.encoding.encode(&decoded.content).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn detects_legacy_encodings() {
+        let latin1 = b"# Caf\xe9 cr\xe8me br\xfbl\xe9e, na\xefve fa\xe7ade\n";
+        let decoded = decode(latin1).unwrap();
+        assert_eq!(decoded.encoding.encoding, WINDOWS_1252);
+        assert!(decoded.content.contains("Café crème"));
+        assert_eq!(decoded.encoding.encode(&decoded.content).unwrap(), latin1);
+
+        let japanese = "// 日本語のコメントです。ファイルを読み込みます。\nlet x = 1;\n";
+        let (bytes, _, _) = SHIFT_JIS.encode(japanese);
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.encoding.encoding, SHIFT_JIS);
+        assert_eq!(decoded.content, japanese);
+    }
+
+    #[test]
+    fn rejects_binary_and_unmappable_text() {
+        assert!(decode(b"\x7fELF\x02\x01\x01\0\0\0\0\x03\0>\0\x01").is_none());
+        let latin1 = TextEncoding {
+            encoding: WINDOWS_1252,
+            bom: false,
+        };
+        assert!(latin1.encode("日本").is_err());
+    }
+}
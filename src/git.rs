@@ -0,0 +1,632 @@
+//! Read-only access to a git repository through the `git` command line.
+//!
+//! Revisions are resolved with `rev-parse`, trees are listed with `ls-tree` and
+//! blobs are streamed from the object database with `cat-file --batch`, so a
+//! revision can be indexed without checking it out.
+
+use crate::db::models::{BlameInfo, LineRange};
+use crate::db::Database;
+use crate::{FlashgrepError, FlashgrepResult};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use tracing::debug;
+
+/// A git working tree rooted at (or containing) a repository directory
+#[derive(Debug, Clone)]
+pub struct GitRepo {
+    root: PathBuf,
+}
+
+/// A blob in a revision's tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Path relative to the repository directory the [`GitRepo`] was opened at
+    pub path: PathBuf,
+    pub object_id: String,
+    pub size: u64,
+}
+
+/// A file that differs from a base revision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedFile {
+    /// Path relative to the repository directory the [`GitRepo`] was opened at
+    pub path: PathBuf,
+    /// Changed line ranges in the working-tree file; `None` for untracked files,
+    /// which are new in their entirety
+    pub hunks: Option<Vec<LineRange>>,
+}
+
+/// A commit from the repository's history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitRecord {
+    pub commit_id: String,
+    pub author: String,
+    /// Author date as a Unix timestamp
+    pub date: i64,
+    /// Full commit message
+    pub message: String,
+    /// Paths changed by the commit, relative to the repository directory
+    pub paths: Vec<String>,
+}
+
+impl GitRepo {
+    /// Open the git repository containing `root`
+    pub fn open(root: &Path) -> FlashgrepResult<Self> {
+        let repo = Self {
+            root: root.to_path_buf(),
+        };
+        repo.run(&["rev-parse", "--git-dir"]).map_err(|_| {
+            FlashgrepError::Git(format!("{} is not inside a git repository", root.display()))
+        })?;
+        Ok(repo)
+    }
+
+    /// Resolve a branch, tag or commit-ish to a full commit id
+    pub fn resolve_commit(&self, rev: &str) -> FlashgrepResult<String> {
+        let output = self
+            .run(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", rev),
+            ])
+            .map_err(|_| FlashgrepError::Git(format!("Unknown revision '{}'", rev)))?;
+        Ok(String::from_utf8_lossy(&output).trim().to_string())
+    }
+
+    /// Committer timestamp of a commit, in Unix seconds
+    pub fn commit_time(&self, commit: &str) -> FlashgrepResult<i64> {
+        let output = self.run(&["show", "-s", "--format=%ct", commit])?;
+        String::from_utf8_lossy(&output)
+            .trim()
+            .parse()
+            .map_err(|_| FlashgrepError::Git(format!("Bad commit time for {}", commit)))
+    }
+
+    /// List the blobs of a commit below the repository directory. Symlinks and
+    /// submodules are left out.
+    pub fn list_files(&self, commit: &str) -> FlashgrepResult<Vec<TreeEntry>> {
+        let output = self.run(&["ls-tree", "-r", "-z", "--long", commit])?;
+        let mut entries = Vec::new();
+        for record in output.split(|b| *b == 0).filter(|r| !r.is_empty()) {
+            let record = String::from_utf8_lossy(record);
+            // <mode> SP <type> SP <object> SP+ <size> TAB <path>
+            let Some((meta, path)) = record.split_once('\t') else {
+                continue;
+            };
+            let fields: Vec<&str> = meta.split_whitespace().collect();
+            if fields.len() != 4 || fields[1] != "blob" || fields[0] == "120000" {
+                continue;
+            }
+            entries.push(TreeEntry {
+                path: PathBuf::from(path),
+                object_id: fields[2].to_string(),
+                size: fields[3].parse().unwrap_or(0),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Files in the working tree that differ from where `base` forked off `HEAD`,
+    /// including uncommitted and untracked changes. Deleted files are left out.
+    ///
+    /// With `base` = `HEAD` this is just the uncommitted changes.
+    pub fn changed_files(&self, base: &str) -> FlashgrepResult<Vec<ChangedFile>> {
+        let base_commit = self.resolve_commit(base)?;
+        let fork_point = self
+            .run(&["merge-base", &base_commit, "HEAD"])
+            .map(|output| String::from_utf8_lossy(&output).trim().to_string())
+            .unwrap_or(base_commit);
+
+        let diff = self.run(&[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--relative",
+            "--diff-filter=d",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            "-U0",
+            &fork_point,
+        ])?;
+        let mut files = parse_diff_hunks(&String::from_utf8_lossy(&diff));
+
+        let untracked = self.run(&["ls-files", "--others", "--exclude-standard", "-z"])?;
+        for path in untracked.split(|b| *b == 0).filter(|p| !p.is_empty()) {
+            files.push(ChangedFile {
+                path: PathBuf::from(String::from_utf8_lossy(path).as_ref()),
+                hunks: None,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Blame lines `start_line..=end_line` of a file in the working tree, or as of
+    /// `revision`, returning the most recently committed of the commits touching them
+    pub fn blame(
+        &self,
+        file_path: &Path,
+        start_line: usize,
+        end_line: usize,
+        revision: Option<&str>,
+    ) -> FlashgrepResult<BlameInfo> {
+        let relative = file_path.strip_prefix(&self.root).unwrap_or(file_path);
+        let range = format!("{},{}", start_line.max(1), end_line.max(start_line).max(1));
+        let mut args = vec!["blame", "--porcelain", "-L", &range];
+        args.extend(revision);
+        let relative = relative.to_string_lossy();
+        args.extend(["--", relative.as_ref()]);
+
+        let output = self.run(&args)?;
+        parse_blame_porcelain(&String::from_utf8_lossy(&output)).ok_or_else(|| {
+            FlashgrepError::Git(format!("No blame output for {}", file_path.display()))
+        })
+    }
+
+    /// Commits reachable from `range` (e.g. `HEAD` or `<old>..HEAD`), newest first
+    pub fn log(&self, range: &str) -> FlashgrepResult<Vec<CommitRecord>> {
+        // Record separator before each commit, unit separators between fields;
+        // the changed paths follow the last field, one per line
+        let output = self.run(&[
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--no-color",
+            "--relative",
+            "--name-only",
+            "--format=%x1e%H%x1f%an%x1f%at%x1f%B%x1f",
+            range,
+        ])?;
+        let output = String::from_utf8_lossy(&output);
+        let mut commits = Vec::new();
+        for record in output.split('\x1e').filter(|r| !r.trim().is_empty()) {
+            let fields: Vec<&str> = record.splitn(5, '\x1f').collect();
+            let [commit_id, author, date, message, paths] = fields[..] else {
+                continue;
+            };
+            commits.push(CommitRecord {
+                commit_id: commit_id.to_string(),
+                author: author.to_string(),
+                date: date.parse().unwrap_or(0),
+                message: message.trim().to_string(),
+                paths: paths
+                    .lines()
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(ToString::to_string)
+                    .collect(),
+            });
+        }
+        Ok(commits)
+    }
+
+    /// Whether `ancestor` is reachable from `commit`
+    pub fn is_ancestor(&self, ancestor: &str, commit: &str) -> bool {
+        self.run(&["merge-base", "--is-ancestor", ancestor, commit])
+            .is_ok()
+    }
+
+    /// Start a reader that streams blob contents from the object database
+    pub fn blob_reader(&self) -> FlashgrepResult<BlobReader> {
+        let mut child = self
+            .command()
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+        Ok(BlobReader {
+            child,
+            stdin: Some(stdin),
+            stdout,
+        })
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.root);
+        command
+    }
+
+    fn run(&self, args: &[&str]) -> FlashgrepResult<Vec<u8>> {
+        let output = self.command().args(args).output()?;
+        if !output.status.success() {
+            return Err(FlashgrepError::Git(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// Blame a file's lines, reusing the answer cached for the same file content.
+/// Uncommitted lines are not cached since committing them changes the answer.
+pub fn blame_cached(
+    db: Option<&Database>,
+    repo: &GitRepo,
+    file_path: &Path,
+    content_hash: &str,
+    lines: LineRange,
+    revision: Option<&str>,
+) -> FlashgrepResult<BlameInfo> {
+    if let Some(cached) = db
+        .map(|db| db.get_cached_blame(file_path, content_hash))
+        .transpose()?
+        .flatten()
+    {
+        return Ok(cached);
+    }
+    let blame = repo.blame(file_path, lines.start_line, lines.end_line, revision)?;
+    if let Some(db) = db.filter(|_| !blame.is_uncommitted()) {
+        // The cache is best effort; it is skipped while an indexer holds the write lock
+        if let Err(err) = db.cache_blame(file_path, content_hash, &blame) {
+            debug!("Not caching blame for {}: {}", file_path.display(), err);
+        }
+    }
+    Ok(blame)
+}
+
+/// Pick the most recently committed commit from `git blame --porcelain` output
+fn parse_blame_porcelain(output: &str) -> Option<BlameInfo> {
+    let mut commits: HashMap<String, (BlameInfo, i64)> = HashMap::new();
+    // Commits that another blamed commit builds on, to order same-second commits
+    let mut superseded: HashSet<String> = HashSet::new();
+    let mut current: Option<String> = None;
+    for line in output.lines() {
+        // Each blamed line ends with its content, prefixed by a tab
+        if line.starts_with('\t') {
+            current = None;
+            continue;
+        }
+        let Some(commit_id) = &current else {
+            let commit_id = line.split_whitespace().next()?.to_string();
+            commits.entry(commit_id.clone()).or_insert_with(|| {
+                let blame = BlameInfo {
+                    commit_id: commit_id.clone(),
+                    ..BlameInfo::default()
+                };
+                (blame, 0)
+            });
+            current = Some(commit_id);
+            continue;
+        };
+        let Some((blame, committed_at)) = commits.get_mut(commit_id) else {
+            continue;
+        };
+        match line.split_once(' ') {
+            Some(("author", author)) => blame.author = author.to_string(),
+            Some(("author-time", time)) => blame.date = time.parse().unwrap_or(0),
+            Some(("committer-time", time)) => *committed_at = time.parse().unwrap_or(0),
+            Some(("summary", summary)) => blame.summary = summary.to_string(),
+            Some(("previous", previous)) => {
+                if let Some(previous_id) = previous.split_whitespace().next() {
+                    superseded.insert(previous_id.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    commits
+        .into_values()
+        .max_by_key(|(blame, committed_at)| {
+            (
+                blame.is_uncommitted(),
+                *committed_at,
+                !superseded.contains(&blame.commit_id),
+            )
+        })
+        .map(|(blame, _)| blame)
+}
+
+/// Collect the new-side line ranges of each file in a zero-context unified diff
+fn parse_diff_hunks(diff: &str) -> Vec<ChangedFile> {
+    let mut files: Vec<ChangedFile> = Vec::new();
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ b/") {
+            files.push(ChangedFile {
+                path: PathBuf::from(path),
+                hunks: Some(Vec::new()),
+            });
+            continue;
+        }
+        // @@ -<old>[,<count>] +<new>[,<count>] @@
+        let Some(header) = line.strip_prefix("@@ -") else {
+            continue;
+        };
+        let Some(new_side) = header
+            .split_whitespace()
+            .nth(1)
+            .and_then(|range| range.strip_prefix('+'))
+        else {
+            continue;
+        };
+        let (start, count) = match new_side.split_once(',') {
+            Some((start, count)) => (start.parse().unwrap_or(0), count.parse().unwrap_or(0)),
+            None => (new_side.parse().unwrap_or(0), 1),
+        };
+        // A pure deletion has no new lines; mark the line it follows
+        let range = if count == 0 {
+            LineRange::new(start.max(1), start.max(1))
+        } else {
+            LineRange::new(start, start + count - 1)
+        };
+        if let Some(hunks) = files.last_mut().and_then(|file| file.hunks.as_mut()) {
+            hunks.push(range);
+        }
+    }
+    files
+}
+
+/// A running `git cat-file --batch` process
+pub struct BlobReader {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    /// Read the full contents of a blob
+    pub fn read(&mut self, object_id: &str) -> FlashgrepResult<Vec<u8>> {
+        let stdin = self.stdin.as_mut().expect("stdin open until drop");
+        writeln!(stdin, "{}", object_id)?;
+        stdin.flush()?;
+
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        // <object> SP <type> SP <size> LF, or <object> SP missing LF
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let size = match fields.as_slice() {
+            [_, _, size] => size
+                .parse::<usize>()
+                .map_err(|_| FlashgrepError::Git(format!("Bad cat-file header: {}", header)))?,
+            _ => {
+                return Err(FlashgrepError::Git(format!(
+                    "Object {} not found",
+                    object_id
+                )))
+            }
+        };
+
+        let mut content = vec![0u8; size];
+        self.stdout.read_exact(&mut content)?;
+        let mut newline = [0u8; 1];
+        self.stdout.read_exact(&mut newline)?;
+        Ok(content)
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        // Closing stdin ends the batch
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Run git in `root` for test setup, panicking on failure
+    pub(crate) fn git(root: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args([
+                "-c",
+                "user.name=flashgrep",
+                "-c",
+                "user.email=flashgrep@example.com",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn parses_new_side_hunks_from_diff() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                    --- a/src/lib.rs\n\
+                    +++ b/src/lib.rs\n\
+                    @@ -3 +3,2 @@ fn a()\n\
+                    -old\n\
+                    +new\n\
+                    +more\n\
+                    @@ -10,2 +11,0 @@\n\
+                    -gone\n\
+                    -gone\n\
+                    diff --git a/new.rs b/new.rs\n\
+                    --- /dev/null\n\
+                    +++ b/new.rs\n\
+                    @@ -0,0 +1 @@\n\
+                    +fn new() {}\n";
+        let files = parse_diff_hunks(diff);
+        assert_eq!(
+            files,
+            vec![
+                ChangedFile {
+                    path: PathBuf::from("src/lib.rs"),
+                    hunks: Some(vec![LineRange::new(3, 4), LineRange::new(11, 11)]),
+                },
+                ChangedFile {
+                    path: PathBuf::from("new.rs"),
+                    hunks: Some(vec![LineRange::new(1, 1)]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn blame_prefers_the_descendant_of_same_second_commits() {
+        let older = "a".repeat(40);
+        let newer = "b".repeat(40);
+        let porcelain = |first: &str, second: &str| {
+            let header = |id: &str, line: usize| {
+                let mut header = format!(
+                    "{id} {line} {line} 1\nauthor A {id}\nauthor-time 1700000000\n\
+                     committer-time 1700000000\nsummary commit {id}\n"
+                );
+                if id == newer {
+                    header.push_str(&format!("previous {older} src/lib.rs\n"));
+                }
+                header.push_str("filename src/lib.rs\n\tline\n");
+                header
+            };
+            format!("{}{}", header(first, 1), header(second, 2))
+        };
+
+        // The winner must not depend on which commit blame lists first
+        for output in [porcelain(&older, &newer), porcelain(&newer, &older)] {
+            let blame = parse_blame_porcelain(&output).expect("blame");
+            assert_eq!(blame.commit_id, newer);
+            assert_eq!(blame.summary, format!("commit {}", newer));
+        }
+    }
+
+    #[test]
+    fn lists_changed_files_since_fork_point() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("a.rs"), "fn a() {}\n")?;
+        std::fs::write(root.join("b.rs"), "fn b() {}\n")?;
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "initial"]);
+        git(root, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(root.join("a.rs"), "fn a() {}\nfn a2() {}\n")?;
+        git(root, &["commit", "-q", "-am", "feature work"]);
+        std::fs::write(root.join("untracked.rs"), "fn u() {}\n")?;
+
+        let repo = GitRepo::open(root)?;
+        let mut changed = repo.changed_files("main")?;
+        changed.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            changed,
+            vec![
+                ChangedFile {
+                    path: PathBuf::from("a.rs"),
+                    hunks: Some(vec![LineRange::new(2, 2)]),
+                },
+                ChangedFile {
+                    path: PathBuf::from("untracked.rs"),
+                    hunks: None,
+                },
+            ]
+        );
+
+        let uncommitted = repo.changed_files("HEAD")?;
+        assert_eq!(uncommitted.len(), 1);
+        assert_eq!(uncommitted[0].path, PathBuf::from("untracked.rs"));
+        Ok(())
+    }
+
+    #[test]
+    fn blames_latest_commit_and_caches_committed_lines() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("a.rs"), "fn one() {}\nfn two() {}\n")?;
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "first"]);
+        std::fs::write(root.join("a.rs"), "fn one() {}\nfn two_renamed() {}\n")?;
+        git(root, &["commit", "-q", "-am", "rename two"]);
+
+        let repo = GitRepo::open(root)?;
+        let file = root.join("a.rs");
+        let head = repo.resolve_commit("HEAD")?;
+        let blame = repo.blame(&file, 1, 2, None)?;
+        assert_eq!(blame.commit_id, head);
+        assert_eq!(blame.author, "flashgrep");
+        assert_eq!(blame.summary, "rename two");
+        assert!(blame.date > 0);
+        assert_eq!(repo.blame(&file, 1, 1, None)?.summary, "first");
+        assert_eq!(repo.blame(&file, 1, 2, Some("HEAD~1"))?.summary, "first");
+
+        std::fs::write(root.join("a.rs"), "fn one() {}\nfn dirty() {}\n")?;
+        let db = Database::open(&root.join("meta.db"))?;
+        let lines = LineRange::new(1, 2);
+        let dirty = blame_cached(Some(&db), &repo, &file, "dirty", lines, None)?;
+        assert!(dirty.is_uncommitted());
+        assert!(db.get_cached_blame(&file, "dirty")?.is_none());
+        let committed = blame_cached(Some(&db), &repo, &file, "committed", lines, Some("HEAD"))?;
+        assert_eq!(db.get_cached_blame(&file, "committed")?, Some(committed));
+        Ok(())
+    }
+
+    #[test]
+    fn reads_log_with_messages_and_paths() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::write(root.join("src/retry.rs"), "fn retry() {}\n")?;
+        git(root, &["add", "."]);
+        git(
+            root,
+            &["commit", "-q", "-m", "Add retry\n\nWith a body line."],
+        );
+        let first = GitRepo::open(root)?.resolve_commit("HEAD")?;
+        std::fs::write(root.join("README.md"), "docs\n")?;
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "Document"]);
+
+        let repo = GitRepo::open(root)?;
+        let log = repo.log("HEAD")?;
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].message, "Document");
+        assert_eq!(log[0].paths, vec!["README.md"]);
+        assert_eq!(log[1].commit_id, first);
+        assert_eq!(log[1].author, "flashgrep");
+        assert_eq!(log[1].message, "Add retry\n\nWith a body line.");
+        assert_eq!(log[1].paths, vec!["src/retry.rs"]);
+
+        assert_eq!(repo.log(&format!("{}..HEAD", first))?.len(), 1);
+        assert!(repo.is_ancestor(&first, "HEAD"));
+        assert!(!repo.is_ancestor("HEAD", &first));
+        Ok(())
+    }
+
+    #[test]
+    fn lists_and_reads_committed_blobs() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::write(root.join("src/lib.rs"), "pub fn committed() {}\n")?;
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "initial"]);
+        std::fs::write(root.join("src/lib.rs"), "pub fn working_tree() {}\n")?;
+
+        let repo = GitRepo::open(root)?;
+        let commit = repo.resolve_commit("main")?;
+        assert_eq!(commit.len(), 40);
+        assert!(repo.commit_time(&commit)? > 0);
+        assert!(repo.resolve_commit("no-such-branch").is_err());
+
+        let entries = repo.list_files(&commit)?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, PathBuf::from("src/lib.rs"));
+
+        let mut reader = repo.blob_reader()?;
+        let blob = reader.read(&entries[0].object_id)?;
+        assert_eq!(blob, b"pub fn committed() {}\n");
+        assert!(reader
+            .read("0000000000000000000000000000000000000000")
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,333 @@
+use crate::config::paths::FlashgrepPaths;
+use crate::config::Config;
+use crate::db::models::{OrphanedRows, SkippedFile};
+use crate::db::Database;
+use crate::index::engine::Indexer;
+use crate::index::state::IndexState;
+use crate::FlashgrepResult;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tantivy::Index;
+use tracing::{info, warn};
+
+/// Inconsistencies between SQLite, Tantivy, `index_state.json` and the filesystem
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub repo_root: PathBuf,
+    pub index_exists: bool,
+    /// Indexed files that no longer exist on disk
+    pub missing_files: Vec<PathBuf>,
+    /// Files with Tantivy documents but no metadata record
+    pub orphaned_documents: Vec<PathBuf>,
+    /// Files whose chunk count differs from their Tantivy document count
+    pub document_mismatches: Vec<DocumentMismatch>,
+    /// Chunks, symbols and vectors without an owning file or chunk
+    pub orphaned_rows: OrphanedRows,
+    /// `index_state.json` entries for files that are not indexed
+    pub stale_state_entries: Vec<PathBuf>,
+    /// Skip records for files that no longer exist on disk
+    pub stale_skip_records: Vec<PathBuf>,
+    /// Files deliberately left out of the index; informational, not an issue
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocumentMismatch {
+    pub file_path: PathBuf,
+    pub chunks: usize,
+    pub documents: usize,
+}
+
+/// What [`DoctorReport::repair`] changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepairSummary {
+    pub removed_files: usize,
+    pub removed_documents: usize,
+    pub reindexed_files: usize,
+    pub failed_files: Vec<PathBuf>,
+    pub removed_rows: OrphanedRows,
+    pub removed_state_entries: usize,
+    pub removed_skip_records: usize,
+}
+
+impl DoctorReport {
+    /// Cross-check the index stores for `repo_root` without modifying them
+    pub fn check(repo_root: &Path) -> FlashgrepResult<Self> {
+        let paths = FlashgrepPaths::new(repo_root);
+        let mut report = Self {
+            repo_root: repo_root.to_path_buf(),
+            ..Self::default()
+        };
+        if !paths.exists() || !paths.metadata_db().exists() {
+            return Ok(report);
+        }
+        report.index_exists = true;
+
+        let db = Database::open_read_only(&paths.metadata_db())?;
+        let chunk_counts: HashMap<PathBuf, usize> = db.file_chunk_counts()?.into_iter().collect();
+        let documents = match Index::open_in_dir(paths.text_index_dir()) {
+            Ok(index) => tantivy_document_counts(&index)?,
+            Err(e) => {
+                warn!("Text index unavailable, treating it as empty: {}", e);
+                HashMap::new()
+            }
+        };
+
+        for (file_path, &chunks) in &chunk_counts {
+            if !file_path.exists() {
+                report.missing_files.push(file_path.clone());
+                continue;
+            }
+            let documents = documents.get(file_path).copied().unwrap_or(0);
+            if documents != chunks {
+                report.document_mismatches.push(DocumentMismatch {
+                    file_path: file_path.clone(),
+                    chunks,
+                    documents,
+                });
+            }
+        }
+        report.orphaned_documents = documents
+            .keys()
+            .filter(|path| !chunk_counts.contains_key(*path))
+            .cloned()
+            .collect();
+        report.orphaned_rows = db.orphaned_row_counts()?;
+
+        report.skipped_files = db.get_skipped_files()?;
+        report.stale_skip_records = report
+            .skipped_files
+            .iter()
+            .filter(|skipped| !skipped.file_path.exists())
+            .map(|skipped| skipped.file_path.clone())
+            .collect();
+
+        let indexed: HashSet<&PathBuf> = chunk_counts
+            .keys()
+            .chain(
+                report
+                    .skipped_files
+                    .iter()
+                    .map(|skipped| &skipped.file_path),
+            )
+            .collect();
+        if let Some(state) = load_index_state(&paths)? {
+            report.stale_state_entries = state
+                .get_all_paths()
+                .into_iter()
+                .filter(|rel| !indexed.contains(&repo_root.join(rel)))
+                .collect();
+        }
+
+        report.missing_files.sort();
+        report.orphaned_documents.sort();
+        report
+            .document_mismatches
+            .sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        report.stale_state_entries.sort();
+        Ok(report)
+    }
+
+    /// Total number of inconsistencies found
+    pub fn issue_count(&self) -> usize {
+        self.missing_files.len()
+            + self.orphaned_documents.len()
+            + self.document_mismatches.len()
+            + self.orphaned_rows.total()
+            + self.stale_state_entries.len()
+            + self.stale_skip_records.len()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.issue_count() == 0
+    }
+
+    /// Repair the reported inconsistencies file by file, without clearing the index
+    pub fn repair(&self, indexer: &mut Indexer) -> FlashgrepResult<RepairSummary> {
+        let mut summary = RepairSummary::default();
+
+        for file_path in &self.missing_files {
+            indexer.purge_file(file_path)?;
+            summary.removed_files += 1;
+        }
+        for file_path in &self.orphaned_documents {
+            indexer.purge_file(file_path)?;
+            summary.removed_documents += 1;
+        }
+        for mismatch in &self.document_mismatches {
+            match indexer.force_reindex_file(&mismatch.file_path) {
+                Ok(_) => summary.reindexed_files += 1,
+                Err(e) => {
+                    warn!("Failed to reindex {}: {}", mismatch.file_path.display(), e);
+                    summary.failed_files.push(mismatch.file_path.clone());
+                }
+            }
+        }
+        indexer.commit()?;
+
+        summary.removed_rows = indexer.db().delete_orphaned_rows()?;
+        for file_path in &self.stale_skip_records {
+            indexer.db().clear_skipped_file(file_path)?;
+            summary.removed_skip_records += 1;
+        }
+
+        if !self.stale_state_entries.is_empty() {
+            let paths = FlashgrepPaths::new(&self.repo_root);
+            if let Some(mut state) = load_index_state(&paths)? {
+                for rel in &self.stale_state_entries {
+                    state.remove_file(rel);
+                    summary.removed_state_entries += 1;
+                }
+                state.save(&index_state_path(&paths))?;
+            }
+        }
+
+        info!(
+            "Doctor repaired {} issue(s), {} file(s) failed to reindex",
+            self.issue_count() - summary.failed_files.len(),
+            summary.failed_files.len()
+        );
+        Ok(summary)
+    }
+}
+
+fn index_state_path(paths: &FlashgrepPaths) -> PathBuf {
+    let config = Config::from_file(&paths.config_file()).unwrap_or_default();
+    paths.root().join(config.index_state_path)
+}
+
+fn load_index_state(paths: &FlashgrepPaths) -> FlashgrepResult<Option<IndexState>> {
+    let path = index_state_path(paths);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(IndexState::load(&path)?))
+}
+
+/// Number of live Tantivy documents per stored file path
+fn tantivy_document_counts(index: &Index) -> FlashgrepResult<HashMap<PathBuf, usize>> {
+    let schema = index.schema();
+    let field = schema.get_field("file_path")?;
+    let revision_field = schema.get_field("revision").ok();
+    let searcher = index.reader()?.searcher();
+    let mut counts = HashMap::new();
+
+    for segment_reader in searcher.segment_readers() {
+        let store = segment_reader.get_store_reader(1)?;
+        for doc in store.iter(segment_reader.alive_bitset()) {
+            let doc = doc?;
+            // Documents indexed from git revisions have no metadata rows to compare with
+            if revision_field.is_some_and(|f| doc.get_first(f).is_some()) {
+                continue;
+            }
+            if let Some(path) = doc.get_first(field).and_then(|v| v.as_text()) {
+                *counts.entry(PathBuf::from(path)).or_insert(0) += 1;
+            }
+        }
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::state::FileMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn doctor_detects_and_repairs_divergence() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::write(root.join("keep.rs"), "fn keep() {}\n")?;
+        std::fs::write(root.join("gone.rs"), "fn gone() {}\n")?;
+        std::fs::write(root.join("orphan.rs"), "fn orphan() {}\n")?;
+
+        let mut indexer = Indexer::new(root.clone())?;
+        indexer.index_repository(&root)?;
+
+        // Diverge the stores: a deleted file, a metadata row dropped behind
+        // Tantivy's back, and a stale index_state entry.
+        std::fs::remove_file(root.join("gone.rs"))?;
+        indexer.db().delete_file(&root.join("orphan.rs"))?;
+        let paths = FlashgrepPaths::new(&root);
+        let mut state = IndexState::new();
+        state.update_file(
+            PathBuf::from("never-indexed.rs"),
+            FileMetadata {
+                size: 1,
+                mtime: 1,
+                content_hash: "x".to_string(),
+            },
+        );
+        state.save(&index_state_path(&paths))?;
+
+        let report = DoctorReport::check(&root)?;
+        assert!(report.index_exists);
+        assert_eq!(report.missing_files, vec![root.join("gone.rs")]);
+        assert_eq!(report.orphaned_documents, vec![root.join("orphan.rs")]);
+        assert!(report.document_mismatches.is_empty());
+        assert_eq!(
+            report.stale_state_entries,
+            vec![PathBuf::from("never-indexed.rs")]
+        );
+        assert_eq!(report.issue_count(), 3);
+
+        let summary = report.repair(&mut indexer)?;
+        assert_eq!(summary.removed_files, 1);
+        assert_eq!(summary.removed_documents, 1);
+        assert_eq!(summary.removed_state_entries, 1);
+
+        let after = DoctorReport::check(&root)?;
+        assert!(after.is_healthy(), "remaining issues: {:?}", after);
+        assert_eq!(indexer.db().get_all_files()?, vec![root.join("keep.rs")]);
+        Ok(())
+    }
+
+    #[test]
+    fn doctor_reindexes_files_missing_documents() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::write(root.join("lib.rs"), "pub fn lib() {}\n")?;
+
+        // Metadata written but the Tantivy commit never happened, as after a crash
+        let mut indexer = Indexer::new(root.clone())?;
+        indexer.index_file(&root.join("lib.rs"))?;
+
+        let report = DoctorReport::check(&root)?;
+        assert_eq!(report.document_mismatches.len(), 1);
+        assert_eq!(report.document_mismatches[0].documents, 0);
+
+        let summary = report.repair(&mut indexer)?;
+        assert_eq!(summary.reindexed_files, 1);
+        assert!(DoctorReport::check(&root)?.is_healthy());
+        Ok(())
+    }
+
+    #[test]
+    fn doctor_lists_skipped_files_and_clears_stale_records() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::write(root.join("lib.rs"), "pub fn lib() {}\n")?;
+        std::fs::write(root.join("blob.rs"), b"fn x() {}\0\x01")?;
+
+        let mut indexer = Indexer::new(root.clone())?;
+        indexer.index_repository(&root)?;
+
+        let report = DoctorReport::check(&root)?;
+        assert!(report.is_healthy());
+        assert_eq!(report.skipped_files.len(), 1);
+        assert_eq!(report.skipped_files[0].reason, "binary");
+
+        std::fs::remove_file(root.join("blob.rs"))?;
+        let report = DoctorReport::check(&root)?;
+        assert_eq!(report.stale_skip_records, vec![root.join("blob.rs")]);
+
+        let summary = report.repair(&mut indexer)?;
+        assert_eq!(summary.removed_skip_records, 1);
+        let report = DoctorReport::check(&root)?;
+        assert!(report.is_healthy());
+        assert!(report.skipped_files.is_empty());
+        Ok(())
+    }
+}
@@ -1,25 +1,68 @@
 use crate::chunking::Chunker;
+use crate::config::languages::LanguageDef;
 use crate::config::paths::FlashgrepPaths;
 use crate::config::Config;
-use crate::db::models::{Chunk, FileMetadata, Symbol};
+use crate::db::models::{Chunk, FileMetadata, IndexedRevision, JournalEntry, OrphanedRows, Symbol};
 use crate::db::Database;
-use crate::index::scanner::{FileScanner, FlashgrepIgnore};
+use crate::encoding;
+use crate::git::GitRepo;
+use crate::index::scanner::{
+    classify_file, is_minified, should_index_content, FileScanner, FileTier, FlashgrepIgnore,
+    SkipReason,
+};
+use crate::index::schema::{
+    has_text_index_version, text_index_version, text_schema, write_text_index_version,
+    TEXT_SCHEMA_VERSION,
+};
 use crate::neural::{build_knowledge_graph_edges, pseudo_embedding};
-use crate::symbols::SymbolDetector;
-use crate::FlashgrepResult;
+use crate::path_utils::{normalize_glob_pattern, normalize_path_for_matching};
+use crate::progress::ProgressReporter;
+use crate::symbols::{SymbolDetector, SymbolExtractor};
+use crate::{FlashgrepError, FlashgrepResult};
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
-use std::io::IsTerminal;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, IsTerminal};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tantivy::directory::error::LockError;
 use tantivy::schema::*;
-use tantivy::{Index, IndexWriter, Term};
-use tracing::{debug, error, info};
+use tantivy::{Index, IndexWriter, TantivyError, Term};
+use tracing::{debug, error, info, warn};
+
+/// Number of processed files between indexing progress notifications
+const INDEX_PROGRESS_INTERVAL: u64 = 100;
+
+/// Metadata key holding the chunking settings the index was built with
+const CHUNKING_META_KEY: &str = "chunking";
+
+/// Memory budget of the Tantivy writer
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+
+/// Pause between attempts to take the index write lock held by another process
+const WRITER_LOCK_RETRY: Duration = Duration::from_millis(100);
+
+/// Documents whose bodies are loaded together while rebuilding the text index
+const REBUILD_BATCH_DOCUMENTS: usize = 512;
 
 struct FileIndexPlan {
     file_path: PathBuf,
     metadata: FileMetadata,
 }
 
+enum FilePlan {
+    Unchanged,
+    Index(FileIndexPlan),
+    Stream(FileMetadata),
+    Skip(SkipReason, u64),
+}
+
+enum PreparedFile {
+    Index(PreparedFileIndex),
+    Skip(SkipReason, u64),
+}
+
 struct PreparedFileIndex {
     file_path: PathBuf,
     metadata: FileMetadata,
@@ -28,14 +71,21 @@ struct PreparedFileIndex {
 }
 
 /// Main indexing engine
+///
+/// Only one process can write to an index at a time. The Tantivy writer, and
+/// with it the index write lock, is taken on the first change and released by
+/// [`Indexer::commit`], so an idle indexer never blocks other processes.
 pub struct Indexer {
     paths: FlashgrepPaths,
     db: Database,
     index: Index,
-    writer: IndexWriter,
+    writer: Option<IndexWriter>,
     config: Config,
     symbol_detector: SymbolDetector,
     chunker: Chunker,
+    /// Updates that failed and could not be undone either; they stay in the
+    /// journal past the next commit so the next open replays them
+    unfinished_updates: Vec<JournalEntry>,
 }
 
 impl Indexer {
@@ -61,78 +111,293 @@ impl Indexer {
         let db = Database::open(&paths.metadata_db())?;
 
         // Create or open Tantivy index
-        let index = Self::create_or_open_index(&paths.text_index_dir())?;
-        let writer = index.writer(50_000_000)?; // 50MB buffer
+        Self::finish_text_index_swap(&paths)?;
+        let (index, text_version) = Self::create_or_open_index(&paths.text_index_dir())?;
 
-        Ok(Self {
+        let chunker = Chunker::with_settings(config.chunk_settings());
+        let mut indexer = Self {
             paths,
             db,
             index,
-            writer,
+            writer: None,
             config,
             symbol_detector: SymbolDetector::new(),
-            chunker: Chunker::new(),
-        })
+            chunker,
+            unfinished_updates: Vec::new(),
+        };
+        if text_version < TEXT_SCHEMA_VERSION {
+            info!(
+                "Migrating text index from schema version {} to {}",
+                text_version, TEXT_SCHEMA_VERSION
+            );
+            indexer.rebuild_text_index()?;
+        }
+        indexer.recover_interrupted_updates()?;
+        indexer.rechunk_if_settings_changed()?;
+        Ok(indexer)
+    }
+
+    /// Re-chunk every indexed file when the chunking settings differ from the
+    /// ones the index was built with. Returns the number of files re-chunked.
+    fn rechunk_if_settings_changed(&mut self) -> FlashgrepResult<usize> {
+        let fingerprint = self.config.chunking_fingerprint();
+        let stored = self.db.get_meta(CHUNKING_META_KEY)?;
+        if stored.as_deref() == Some(fingerprint.as_str()) {
+            return Ok(0);
+        }
+
+        let mut rechunked = 0;
+        // Indexes from before the settings were recorded were chunked with the defaults
+        let previous = stored.unwrap_or_else(|| Config::default().chunking_fingerprint());
+        if previous != fingerprint {
+            let files: Vec<PathBuf> = self
+                .db
+                .get_all_files()?
+                .into_iter()
+                .filter(|path| path.exists())
+                .collect();
+            if !files.is_empty() {
+                info!(
+                    "Chunking settings changed, re-chunking {} files",
+                    files.len()
+                );
+                let counts = self.index_files_parallel(
+                    &files,
+                    true,
+                    "rechunk",
+                    &ProgressReporter::disabled(),
+                )?;
+                rechunked = counts.indexed;
+            }
+        }
+        self.db.set_meta(CHUNKING_META_KEY, &fingerprint)?;
+        Ok(rechunked)
     }
 
-    /// Create the Tantivy index schema
-    fn create_schema() -> Schema {
-        let mut schema_builder = Schema::builder();
+    /// Bring both stores back in line after a process stopped between touching
+    /// them and committing the text index. Journaled files are reindexed from
+    /// disk, or purged if they are gone, journaled revisions are discarded and an
+    /// interrupted clear is finished. Returns the number of journal entries replayed.
+    fn recover_interrupted_updates(&mut self) -> FlashgrepResult<usize> {
+        if self.db.get_journal_entries()?.is_empty() {
+            return Ok(0);
+        }
+        // The entries may belong to another process still writing; they are only
+        // interrupted if they are still there once its write lock is released
+        self.writer()?;
+        let entries = self.db.get_journal_entries()?;
+        if entries.is_empty() {
+            return self.release_writer().map(|_| 0);
+        }
+        warn!(
+            "Recovering {} index updates interrupted before commit",
+            entries.len()
+        );
+
+        if entries.contains(&JournalEntry::Clear) {
+            self.writer()?.delete_all_documents()?;
+            self.writer()?.commit()?;
+            self.db.clear_all()?;
+            self.commit()?;
+            return Ok(entries.len());
+        }
 
-        // File path field
-        schema_builder.add_text_field("file_path", TEXT | STORED);
+        let scanner = FileScanner::new(self.paths.repo_root().to_path_buf(), self.config.clone());
+        for entry in &entries {
+            match entry {
+                JournalEntry::File(path) if path.exists() && scanner.allows_path(path) => {
+                    if let Err(err) = self.index_file_tracked(path, true) {
+                        // Leave no half-written state behind for a file that cannot be read
+                        self.purge_file(path)?;
+                        self.record_failure(path, &err);
+                    }
+                }
+                JournalEntry::File(path) => self.purge_file(path)?,
+                JournalEntry::Revision(commit_id) => {
+                    if let Ok(revision_field) = self.index.schema().get_field("revision") {
+                        self.writer()?
+                            .delete_term(Term::from_field_text(revision_field, commit_id));
+                    }
+                    self.db.delete_revision(commit_id)?;
+                    warn!(
+                        "Discarded interrupted index of revision {}; index it again",
+                        commit_id
+                    );
+                }
+                JournalEntry::Clear => {}
+            }
+        }
+        self.commit()?;
+        Ok(entries.len())
+    }
 
-        // Content field (tokenized for search)
-        schema_builder.add_text_field("content", TEXT | STORED);
+    /// Record updates in the journal before touching either store, so an
+    /// interrupted update is replayed when the index is next opened. Takes the
+    /// write lock first so no other process writes while the entry is open.
+    fn journal(&mut self, entry: JournalEntry) -> FlashgrepResult<()> {
+        self.journal_all(std::slice::from_ref(&entry))
+    }
 
-        // Start and end line numbers
-        schema_builder.add_u64_field("start_line", STORED | FAST);
-        schema_builder.add_u64_field("end_line", STORED | FAST);
+    fn journal_all(&mut self, entries: &[JournalEntry]) -> FlashgrepResult<()> {
+        self.writer()?;
+        self.db.record_journal_entries(entries)
+    }
 
-        // Content hash for deduplication
-        schema_builder.add_text_field("content_hash", STRING | STORED);
+    /// The Tantivy writer, taking the index write lock if this indexer does not
+    /// hold it yet
+    fn writer(&mut self) -> FlashgrepResult<&mut IndexWriter> {
+        let writer = match self.writer.take() {
+            Some(writer) => writer,
+            None => Self::acquire_writer(
+                &self.index,
+                Duration::from_millis(self.config.writer_lock_timeout_ms),
+            )?,
+        };
+        Ok(self.writer.insert(writer))
+    }
 
-        // Last modified timestamp
-        schema_builder.add_u64_field("last_modified", FAST);
+    /// Open a Tantivy writer, waiting up to `timeout` for another process to
+    /// release the index write lock
+    fn acquire_writer(index: &Index, timeout: Duration) -> FlashgrepResult<IndexWriter> {
+        try_acquire_writer(index, WRITER_MEMORY_BYTES, timeout)?.ok_or_else(|| {
+            FlashgrepError::Index(format!(
+                "the index is being written by another flashgrep process (is a watcher running?); \
+                 gave up after {} ms. Retry later or raise writer_lock_timeout_ms",
+                timeout.as_millis()
+            ))
+        })
+    }
 
-        schema_builder.build()
+    /// Drop the Tantivy writer once its merges finish, releasing the index write lock.
+    /// Uncommitted changes are discarded.
+    fn release_writer(&mut self) -> FlashgrepResult<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.wait_merging_threads()?;
+        }
+        Ok(())
     }
 
-    /// Create or open the Tantivy index
-    fn create_or_open_index(index_dir: &Path) -> FlashgrepResult<Index> {
-        let schema = Self::create_schema();
+    /// Complete a text index migration interrupted between moving the old
+    /// index aside and moving the rebuilt one into its place, restoring the
+    /// rebuilt index if it was finished and the old one otherwise
+    fn finish_text_index_swap(paths: &FlashgrepPaths) -> FlashgrepResult<()> {
+        let index_dir = paths.text_index_dir();
+        let staging_dir = paths.text_index_staging_dir();
+        let retired_dir = paths.text_index_retired_dir();
+        if !index_dir.join("meta.json").exists() {
+            let restored = if has_text_index_version(&staging_dir) {
+                &staging_dir
+            } else {
+                &retired_dir
+            };
+            if restored.join("meta.json").exists() {
+                warn!(
+                    "Restoring text index from interrupted migration at {}",
+                    restored.display()
+                );
+                if index_dir.exists() {
+                    std::fs::remove_dir_all(&index_dir)?;
+                }
+                std::fs::rename(restored, &index_dir)?;
+            }
+        }
+        if index_dir.join("meta.json").exists() && retired_dir.exists() {
+            std::fs::remove_dir_all(&retired_dir)?;
+        }
+        Ok(())
+    }
 
+    /// Create or open the Tantivy index, returning it with its schema version
+    fn create_or_open_index(index_dir: &Path) -> FlashgrepResult<(Index, u32)> {
         if index_dir.exists() && index_dir.join("meta.json").exists() {
             // Open existing index
-            Ok(Index::open_in_dir(index_dir)?)
+            let index = Index::open_in_dir(index_dir)?;
+            let version = text_index_version(&index, index_dir)?;
+            if version == TEXT_SCHEMA_VERSION {
+                // Record the version for indexes written before it was kept
+                write_text_index_version(index_dir)?;
+            }
+            Ok((index, version))
         } else {
             // Create new index
             std::fs::create_dir_all(index_dir)?;
-            Ok(Index::create_in_dir(index_dir, schema)?)
+            let index = Index::create_in_dir(index_dir, text_schema())?;
+            write_text_index_version(index_dir)?;
+            Ok((index, TEXT_SCHEMA_VERSION))
         }
     }
 
     /// Index a single file with batch inserts for better performance
     /// Skips files that haven't changed since last indexing
     pub fn index_file(&mut self, file_path: &Path) -> FlashgrepResult<bool> {
+        self.index_file_tracked(file_path, false)
+    }
+
+    /// Index a single file, recording the failure reason if it cannot be indexed
+    fn index_file_tracked(&mut self, file_path: &Path, force: bool) -> FlashgrepResult<bool> {
+        match self.index_file_inner(file_path, force) {
+            Ok(indexed) => {
+                self.db.clear_index_failure(file_path)?;
+                Ok(indexed)
+            }
+            Err(err) => {
+                if let Err(record_err) = self.db.record_index_failure(file_path, &err.to_string()) {
+                    error!(
+                        "Failed to record index failure for {}: {}",
+                        file_path.display(),
+                        record_err
+                    );
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn index_file_inner(&mut self, file_path: &Path, force: bool) -> FlashgrepResult<bool> {
         debug!("Checking file: {}", file_path.display());
 
         // Get file metadata first to check modification time
-        let metadata = FileMetadata::from_path(file_path)?;
+        let mut metadata = FileMetadata::from_path(file_path)?;
         let last_modified = metadata.last_modified;
 
         // Check if file needs reindexing
-        if !self.db.needs_reindex(file_path, last_modified)? {
+        if !force && !self.db.needs_reindex(file_path, last_modified)? {
             debug!("Skipping unchanged file: {}", file_path.display());
             return Ok(false); // File unchanged, skipped
         }
 
+        self.journal(JournalEntry::File(file_path.to_path_buf()))?;
+        match classify_file(file_path, &self.config)? {
+            FileTier::Skipped(reason) => {
+                self.skip_file(file_path, reason, metadata.file_size)?;
+                return Ok(false);
+            }
+            FileTier::Large => {
+                self.index_large_file(metadata)?;
+                return Ok(true);
+            }
+            FileTier::Normal => {}
+        }
+
         debug!("Indexing file: {}", file_path.display());
 
         // Read file content
-        let content = std::fs::read_to_string(file_path)?;
+        let Some(decoded) = encoding::read_text(file_path)? else {
+            self.skip_file(file_path, SkipReason::InvalidEncoding, metadata.file_size)?;
+            return Ok(false);
+        };
+        metadata.encoding = Some(decoded.encoding.name().to_string());
+        let content = decoded.content;
+        let (chunks, all_symbols) = Self::analyze_content(
+            &self.config,
+            &self.chunker,
+            &self.symbol_detector,
+            &mut metadata,
+            &content,
+        );
 
-        // Delete existing chunks and symbols for this file
+        // Delete existing documents, chunks and symbols for this file
+        self.delete_file_documents(file_path)?;
         self.db.delete_file_chunks(file_path)?;
         self.db.delete_file_symbols(file_path)?;
         self.db.delete_file_vectors(file_path)?;
@@ -141,25 +406,8 @@ impl Indexer {
         // Insert/update file record
         self.db.insert_file(&metadata)?;
 
-        // Chunk the file
-        let chunks = self
-            .chunker
-            .chunk_file(file_path.to_path_buf(), &content, last_modified);
-
-        // Collect all symbols from all chunks
-        let mut all_symbols = Vec::new();
-
-        // Index each chunk and collect symbols
+        // Add each chunk to the Tantivy index
         for chunk in &chunks {
-            // Detect symbols
-            let symbols = self.symbol_detector.detect_in_chunk(
-                &chunk.content,
-                file_path.to_path_buf(),
-                chunk.start_line,
-            );
-            all_symbols.extend(symbols);
-
-            // Add to Tantivy index
             self.add_chunk_to_tantivy(chunk)?;
         }
 
@@ -174,29 +422,142 @@ impl Indexer {
         }
 
         self.persist_neural_artifacts(file_path, &chunks, &all_symbols, last_modified)?;
+        self.db.clear_skipped_file(file_path)?;
 
         Ok(true) // File was indexed
     }
 
+    /// Remove a file from the index and record why it was left out, without committing
+    fn skip_file(
+        &mut self,
+        file_path: &Path,
+        reason: SkipReason,
+        file_size: u64,
+    ) -> FlashgrepResult<()> {
+        debug!("Skipping {} file: {}", reason, file_path.display());
+        self.journal(JournalEntry::File(file_path.to_path_buf()))?;
+        self.delete_file_documents(file_path)?;
+        self.db.delete_file(file_path)?;
+        self.db
+            .record_skipped_file(file_path, reason.as_str(), file_size)
+    }
+
+    /// Index a file above `max_file_size` by streaming it in chunk batches instead of
+    /// reading it whole. Streamed files use line-split chunks and get no neural artifacts.
+    fn index_large_file(&mut self, mut metadata: FileMetadata) -> FlashgrepResult<()> {
+        let file_path = metadata.file_path.clone();
+        debug!("Streaming large file: {}", file_path.display());
+
+        let language = self.config.languages.detect_file(&file_path).cloned();
+        if let Some(def) = &language {
+            metadata.language = Some(def.id.clone());
+        }
+        let extractor = language.map(|def| def.symbols).unwrap_or_default();
+        let text_encoding = encoding::detect_file(&file_path)?.unwrap_or_default();
+        metadata.encoding = Some(text_encoding.name().to_string());
+
+        self.journal(JournalEntry::File(file_path.clone()))?;
+        let result = self.stream_large_file(metadata, extractor, text_encoding);
+        if result.is_err() {
+            self.discard_partial_update(&file_path);
+        }
+        result
+    }
+
+    fn stream_large_file(
+        &mut self,
+        metadata: FileMetadata,
+        extractor: SymbolExtractor,
+        text_encoding: encoding::TextEncoding,
+    ) -> FlashgrepResult<()> {
+        let file_path = metadata.file_path.clone();
+        self.delete_file_documents(&file_path)?;
+        self.db.delete_file_chunks(&file_path)?;
+        self.db.delete_file_symbols(&file_path)?;
+        self.db.delete_file_vectors(&file_path)?;
+        self.db.delete_file_graph(&file_path)?;
+        self.db.insert_file(&metadata)?;
+
+        let reader = BufReader::new(encoding::utf8_reader(
+            File::open(&file_path)?,
+            text_encoding,
+        ));
+        let chunk_count = Chunker::with_settings(self.chunker.settings()).stream_chunks(
+            reader,
+            file_path.clone(),
+            metadata.last_modified,
+            |batch| self.persist_chunk_batch(&batch, extractor),
+        )?;
+        debug!(
+            "Streamed {} chunks from {}",
+            chunk_count,
+            file_path.display()
+        );
+
+        self.db.clear_skipped_file(&file_path)
+    }
+
+    /// Store a batch of streamed chunks and the symbols found in them
+    fn persist_chunk_batch(
+        &mut self,
+        chunks: &[Chunk],
+        extractor: SymbolExtractor,
+    ) -> FlashgrepResult<()> {
+        let mut symbols = Vec::new();
+        for chunk in chunks {
+            symbols.extend(self.symbol_detector.detect_with_extractor(
+                extractor,
+                &chunk.content,
+                chunk.file_path.clone(),
+                chunk.start_line,
+            ));
+            self.add_chunk_to_tantivy(chunk)?;
+        }
+        if !chunks.is_empty() {
+            self.db.insert_chunks_batch(chunks)?;
+        }
+        if !symbols.is_empty() {
+            self.db.insert_symbols_batch(&symbols)?;
+        }
+        Ok(())
+    }
+
     /// Add a chunk to the Tantivy index
     fn add_chunk_to_tantivy(&mut self, chunk: &Chunk) -> FlashgrepResult<()> {
+        self.add_chunk_document(chunk, None)
+    }
+
+    /// Add a chunk to the Tantivy index, tagged with the commit it was read from.
+    /// Revision documents get a `<commit>:<path>` key so working-tree updates
+    /// never delete them.
+    fn add_chunk_document(&mut self, chunk: &Chunk, revision: Option<&str>) -> FlashgrepResult<()> {
         let schema = self.index.schema();
-        let file_path_field = schema.get_field("file_path").unwrap();
-        let content_field = schema.get_field("content").unwrap();
-        let start_line_field = schema.get_field("start_line").unwrap();
-        let end_line_field = schema.get_field("end_line").unwrap();
-        let content_hash_field = schema.get_field("content_hash").unwrap();
-        let last_modified_field = schema.get_field("last_modified").unwrap();
+        let file_path_field = schema.get_field("file_path")?;
+        let content_field = schema.get_field("content")?;
+        let start_line_field = schema.get_field("start_line")?;
+        let end_line_field = schema.get_field("end_line")?;
+        let content_hash_field = schema.get_field("content_hash")?;
+        let last_modified_field = schema.get_field("last_modified")?;
+
+        let file_path_key_field = schema.get_field("file_path_key")?;
 
         let mut doc = Document::default();
         doc.add_text(file_path_field, chunk.file_path.to_string_lossy());
+        let key = match revision {
+            Some(commit_id) => format!("{}:{}", commit_id, chunk.file_path.display()),
+            None => chunk.file_path.to_string_lossy().to_string(),
+        };
+        doc.add_text(file_path_key_field, key);
+        if let (Some(commit_id), Ok(revision_field)) = (revision, schema.get_field("revision")) {
+            doc.add_text(revision_field, commit_id);
+        }
         doc.add_text(content_field, &chunk.content);
         doc.add_u64(start_line_field, chunk.start_line as u64);
         doc.add_u64(end_line_field, chunk.end_line as u64);
         doc.add_text(content_hash_field, &chunk.content_hash);
         doc.add_u64(last_modified_field, chunk.last_modified as u64);
 
-        self.writer.add_document(doc)?;
+        self.writer()?.add_document(doc)?;
 
         Ok(())
     }
@@ -204,6 +565,18 @@ impl Indexer {
     /// Index the entire repository
     /// Only reindexes files that have changed since last indexing
     pub fn index_repository(&mut self, repo_root: &Path) -> FlashgrepResult<IndexStats> {
+        self.index_repository_with_progress(repo_root, &ProgressReporter::disabled())
+    }
+
+    /// Index the repository, reporting per-file progress and honouring cancellation.
+    ///
+    /// On cancellation the files persisted so far are committed so SQLite and
+    /// Tantivy stay consistent, and `FlashgrepError::Cancelled` is returned.
+    pub fn index_repository_with_progress(
+        &mut self,
+        repo_root: &Path,
+        reporter: &ProgressReporter,
+    ) -> FlashgrepResult<IndexStats> {
         info!("Starting repository indexing: {}", repo_root.display());
 
         let scanner = FileScanner::new(repo_root.to_path_buf(), self.config.clone());
@@ -212,59 +585,127 @@ impl Indexer {
 
         info!("Found {} files to check", total_files);
 
+        let counts = self.index_files_parallel(&files, false, "index", reporter)?;
+        info!(
+            "Indexing complete: {} indexed, {} skipped (unchanged), {} excluded, {} failed",
+            counts.indexed, counts.skipped, counts.excluded, counts.failed
+        );
+
+        self.get_stats()
+    }
+
+    /// Index a burst of changed files, e.g. from a branch switch or rebase, through
+    /// the parallel pipeline with a single commit at the end.
+    ///
+    /// Paths that no longer exist, or that are now excluded from the index, are
+    /// removed from it. Unchanged files are skipped unless `force` is set.
+    pub fn index_changed_paths(
+        &mut self,
+        paths: &[PathBuf],
+        force: bool,
+        reporter: &ProgressReporter,
+    ) -> FlashgrepResult<ReindexSummary> {
+        let scanner = FileScanner::new(self.paths.repo_root().to_path_buf(), self.config.clone());
+        let mut summary = ReindexSummary {
+            matched: paths.len(),
+            ..ReindexSummary::default()
+        };
+        let mut files = Vec::new();
+        for path in paths {
+            if path.is_file() && scanner.should_include(path) {
+                files.push(path.clone());
+            } else if !path.is_dir() {
+                self.purge_file(path)?;
+                summary.removed += 1;
+            }
+        }
+
+        let counts = self.index_files_parallel(&files, force, "index changes", reporter)?;
+        summary.indexed = counts.indexed;
+        summary.unchanged = counts.skipped + counts.excluded;
+        summary.failed = counts.failed;
+        info!(
+            "Indexed {} changed paths: {} indexed, {} unchanged, {} removed, {} failed",
+            paths.len(),
+            summary.indexed,
+            summary.unchanged,
+            summary.removed,
+            summary.failed
+        );
+        Ok(summary)
+    }
+
+    /// Plan, prepare in parallel and persist `files`, committing once at the end.
+    /// With `force`, files are reindexed even if their mtime is unchanged.
+    ///
+    /// On cancellation the files persisted so far are committed and
+    /// `FlashgrepError::Cancelled` is returned.
+    fn index_files_parallel(
+        &mut self,
+        files: &[PathBuf],
+        force: bool,
+        operation: &str,
+        reporter: &ProgressReporter,
+    ) -> FlashgrepResult<FileIndexCounts> {
+        let total_files = files.len();
         let progress = Self::create_progress_bar(total_files as u64);
         let mut indexed = 0;
         let mut skipped = 0;
         let mut failed = 0;
+        let mut excluded = 0;
         let mut plans = Vec::new();
+        let mut large_files = Vec::new();
 
-        for file_path in &files {
-            match FileMetadata::from_path(file_path) {
-                Ok(metadata) => match self.db.needs_reindex(file_path, metadata.last_modified) {
-                    Ok(true) => plans.push(FileIndexPlan {
-                        file_path: file_path.clone(),
-                        metadata,
-                    }),
-                    Ok(false) => {
-                        skipped += 1;
-                        if let Some(pb) = &progress {
-                            pb.inc(1);
-                        }
-                    }
-                    Err(err) => {
-                        failed += 1;
-                        error!(
-                            "Failed to check {} for reindex eligibility: {}",
-                            file_path.display(),
-                            err
-                        );
-                        if let Some(pb) = &progress {
-                            pb.inc(1);
+        for file_path in files {
+            match self.plan_file(file_path, force) {
+                Ok(FilePlan::Index(plan)) => {
+                    plans.push(plan);
+                    continue;
+                }
+                Ok(FilePlan::Stream(metadata)) => {
+                    large_files.push(metadata);
+                    continue;
+                }
+                Ok(FilePlan::Unchanged) => skipped += 1,
+                Ok(FilePlan::Skip(reason, file_size)) => {
+                    match self.skip_file(file_path, reason, file_size) {
+                        Ok(()) => excluded += 1,
+                        Err(err) => {
+                            failed += 1;
+                            error!("Failed to skip {}: {}", file_path.display(), err);
+                            self.record_failure(file_path, &err);
                         }
                     }
-                },
+                }
                 Err(err) => {
                     failed += 1;
-                    error!(
-                        "Failed to read metadata for {}: {}",
-                        file_path.display(),
-                        err
-                    );
-                    if let Some(pb) = &progress {
-                        pb.inc(1);
-                    }
+                    error!("Failed to plan {}: {}", file_path.display(), err);
+                    self.record_failure(file_path, &err);
                 }
             }
+            if let Some(pb) = &progress {
+                pb.inc(1);
+            }
         }
 
         let progress_for_workers = progress.clone();
-        let prepared_results: Vec<(PathBuf, FlashgrepResult<PreparedFileIndex>)> = plans
+        let config = &self.config;
+        let prepared_results: Vec<(PathBuf, FlashgrepResult<PreparedFile>)> = plans
             .into_par_iter()
             .map_init(
-                || (Chunker::new(), SymbolDetector::new()),
+                || {
+                    (
+                        Chunker::with_settings(config.chunk_settings()),
+                        SymbolDetector::new(),
+                    )
+                },
                 |(chunker, symbol_detector), plan| {
                     let path = plan.file_path.clone();
-                    let result = Self::prepare_file_for_indexing(chunker, symbol_detector, plan);
+                    if reporter.is_cancelled() {
+                        return (path, Err(FlashgrepError::Cancelled(operation.to_string())));
+                    }
+                    let result =
+                        Self::prepare_file_for_indexing(config, chunker, symbol_detector, plan);
                     if let Some(pb) = &progress_for_workers {
                         pb.inc(1);
                     }
@@ -273,39 +714,93 @@ impl Indexer {
             )
             .collect();
 
+        let mut cancelled = false;
         for (file_path, prepared_result) in prepared_results {
-            match prepared_result {
-                Ok(prepared) => {
-                    if let Err(err) = self.persist_prepared_file(prepared) {
-                        error!("Failed to index {}: {}", file_path.display(), err);
-                        failed += 1;
-                    } else {
-                        indexed += 1;
+            if reporter.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            reporter.report_every(
+                operation,
+                (indexed + skipped + excluded + failed) as u64,
+                Some(total_files as u64),
+                INDEX_PROGRESS_INTERVAL,
+            );
+            let result = match prepared_result {
+                Ok(PreparedFile::Index(prepared)) => self.persist_prepared_file(prepared),
+                Ok(PreparedFile::Skip(reason, file_size)) => {
+                    match self.skip_file(&file_path, reason, file_size) {
+                        Ok(()) => {
+                            excluded += 1;
+                            continue;
+                        }
+                        Err(err) => Err(err),
                     }
                 }
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => indexed += 1,
+                Err(err) => {
+                    error!("Failed to index {}: {}", file_path.display(), err);
+                    self.record_failure(&file_path, &err);
+                    failed += 1;
+                }
+            }
+        }
+
+        // Large files are streamed one at a time to bound memory use
+        for metadata in large_files {
+            if cancelled || reporter.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            let file_path = metadata.file_path.clone();
+            match self.index_large_file(metadata) {
+                Ok(()) => {
+                    self.db.clear_index_failure(&file_path)?;
+                    indexed += 1;
+                }
                 Err(err) => {
                     error!("Failed to index {}: {}", file_path.display(), err);
+                    self.record_failure(&file_path, &err);
                     failed += 1;
                 }
             }
+            if let Some(pb) = &progress {
+                pb.inc(1);
+            }
         }
 
         if let Some(pb) = &progress {
             pb.finish_with_message(format!(
-                "Indexed {}/{} files ({} skipped, {} failed)",
-                indexed, total_files, skipped, failed
+                "Indexed {}/{} files ({} skipped, {} excluded, {} failed)",
+                indexed, total_files, skipped, excluded, failed
             ));
         }
 
         // Commit the Tantivy writer
-        self.writer.commit()?;
+        self.commit()?;
 
-        info!(
-            "Indexing complete: {} indexed, {} skipped (unchanged), {} failed",
-            indexed, skipped, failed
+        if cancelled {
+            info!(
+                "Indexing cancelled after {} indexed, {} skipped, {} failed",
+                indexed, skipped, failed
+            );
+            return Err(FlashgrepError::Cancelled(operation.to_string()));
+        }
+        reporter.report(
+            total_files as u64,
+            Some(total_files as u64),
+            Some(operation),
         );
 
-        self.get_stats()
+        Ok(FileIndexCounts {
+            indexed,
+            skipped,
+            excluded,
+            failed,
+        })
     }
 
     fn create_progress_bar(total_files: u64) -> Option<ProgressBar> {
@@ -325,36 +820,132 @@ impl Indexer {
         Some(progress_bar)
     }
 
+    /// Decide how a scanned file should be handled by the indexer
+    fn plan_file(&self, file_path: &Path, force: bool) -> FlashgrepResult<FilePlan> {
+        let metadata = FileMetadata::from_path(file_path)?;
+        if !force && !self.db.needs_reindex(file_path, metadata.last_modified)? {
+            return Ok(FilePlan::Unchanged);
+        }
+        Ok(match classify_file(file_path, &self.config)? {
+            FileTier::Normal => FilePlan::Index(FileIndexPlan {
+                file_path: file_path.to_path_buf(),
+                metadata,
+            }),
+            FileTier::Large => FilePlan::Stream(metadata),
+            FileTier::Skipped(reason) => FilePlan::Skip(reason, metadata.file_size),
+        })
+    }
+
     fn prepare_file_for_indexing(
+        config: &Config,
         chunker: &Chunker,
         symbol_detector: &SymbolDetector,
-        plan: FileIndexPlan,
-    ) -> FlashgrepResult<PreparedFileIndex> {
-        let content = std::fs::read_to_string(&plan.file_path)?;
-        let chunks = chunker.chunk_file(
-            plan.file_path.clone(),
+        mut plan: FileIndexPlan,
+    ) -> FlashgrepResult<PreparedFile> {
+        let Some(decoded) = encoding::read_text(&plan.file_path)? else {
+            return Ok(PreparedFile::Skip(
+                SkipReason::InvalidEncoding,
+                plan.metadata.file_size,
+            ));
+        };
+        plan.metadata.encoding = Some(decoded.encoding.name().to_string());
+        let content = decoded.content;
+        let (chunks, symbols) = Self::analyze_content(
+            config,
+            chunker,
+            symbol_detector,
+            &mut plan.metadata,
             &content,
-            plan.metadata.last_modified,
         );
 
+        Ok(PreparedFile::Index(PreparedFileIndex {
+            file_path: plan.file_path,
+            metadata: plan.metadata,
+            chunks,
+            symbols,
+        }))
+    }
+
+    /// Detect the file's language and chunk it and extract symbols accordingly.
+    /// Content-based detection (shebang, modeline) overrides the path-based guess
+    /// stored in `metadata.language`. Minified content is line-split instead.
+    fn analyze_content(
+        config: &Config,
+        chunker: &Chunker,
+        symbol_detector: &SymbolDetector,
+        metadata: &mut FileMetadata,
+        content: &str,
+    ) -> (Vec<Chunk>, Vec<Symbol>) {
+        let language = config.languages.detect(&metadata.file_path, content);
+        if let Some(def) = language {
+            metadata.language = Some(def.id.clone());
+        }
+        let extractor = language.map(|def| def.symbols).unwrap_or_default();
+
+        let chunks = Self::chunk_content(
+            config,
+            chunker,
+            language,
+            &metadata.file_path,
+            content,
+            metadata.last_modified,
+        );
         let mut symbols = Vec::new();
         for chunk in &chunks {
-            symbols.extend(symbol_detector.detect_in_chunk(
+            symbols.extend(symbol_detector.detect_with_extractor(
+                extractor,
                 &chunk.content,
-                plan.file_path.clone(),
+                metadata.file_path.clone(),
                 chunk.start_line,
             ));
         }
+        (chunks, symbols)
+    }
 
-        Ok(PreparedFileIndex {
-            file_path: plan.file_path,
-            metadata: plan.metadata,
-            chunks,
-            symbols,
-        })
+    /// Chunk content with its language's strategy, or line-split if it is minified
+    fn chunk_content(
+        config: &Config,
+        chunker: &Chunker,
+        language: Option<&LanguageDef>,
+        file_path: &Path,
+        content: &str,
+        last_modified: i64,
+    ) -> Vec<Chunk> {
+        if is_minified(content, config) {
+            return chunker.chunk_split_lines(file_path.to_path_buf(), content, last_modified);
+        }
+        let strategy = language.map(|def| def.chunker).unwrap_or_default();
+        chunker.chunk_with_strategy(strategy, file_path.to_path_buf(), content, last_modified)
     }
 
+    /// Replace a file's documents and rows with a prepared index of it. If any
+    /// step fails, the file is removed from the index altogether.
     fn persist_prepared_file(&mut self, prepared: PreparedFileIndex) -> FlashgrepResult<()> {
+        let file_path = prepared.file_path.clone();
+        self.journal(JournalEntry::File(file_path.clone()))?;
+        let result = self.write_prepared_file(prepared);
+        if result.is_err() {
+            self.discard_partial_update(&file_path);
+        }
+        result
+    }
+
+    /// Undo a file update that failed partway. Its file row goes too, so the next
+    /// scan indexes it again instead of finding a current mtime with chunks missing.
+    fn discard_partial_update(&mut self, file_path: &Path) {
+        if let Err(err) = self.purge_file(file_path) {
+            error!(
+                "Failed to remove partially indexed {}: {}",
+                file_path.display(),
+                err
+            );
+            self.unfinished_updates
+                .push(JournalEntry::File(file_path.to_path_buf()));
+        }
+    }
+
+    fn write_prepared_file(&mut self, prepared: PreparedFileIndex) -> FlashgrepResult<()> {
+        self.delete_file_documents(&prepared.file_path)?;
         self.db.delete_file_chunks(&prepared.file_path)?;
         self.db.delete_file_symbols(&prepared.file_path)?;
         self.db.delete_file_vectors(&prepared.file_path)?;
@@ -379,6 +970,8 @@ impl Indexer {
             &prepared.symbols,
             prepared.metadata.last_modified,
         )?;
+        self.db.clear_index_failure(&prepared.file_path)?;
+        self.db.clear_skipped_file(&prepared.file_path)?;
 
         Ok(())
     }
@@ -398,6 +991,9 @@ impl Indexer {
             "provider:{}:{}",
             self.config.neural.provider.provider, self.config.neural.provider.model
         );
+        // Bodies already embedded, here or in another file, reuse the stored vector
+        let content_hashes: Vec<String> = chunks.iter().map(|c| c.content_hash.clone()).collect();
+        let mut embeddings = self.db.get_embeddings(&model_id, &content_hashes)?;
         let vectors = chunks
             .iter()
             .map(|chunk| crate::db::models::ChunkVector {
@@ -406,7 +1002,10 @@ impl Indexer {
                 start_line: chunk.start_line,
                 end_line: chunk.end_line,
                 content_hash: chunk.content_hash.clone(),
-                embedding: pseudo_embedding(&chunk.content, 64),
+                embedding: embeddings
+                    .entry(chunk.content_hash.clone())
+                    .or_insert_with(|| pseudo_embedding(&chunk.content, 64))
+                    .clone(),
                 model_id: model_id.clone(),
                 last_modified,
             })
@@ -471,14 +1070,16 @@ impl Indexer {
     /// Clear the entire index
     pub fn clear_index(&mut self) -> FlashgrepResult<()> {
         info!("Clearing index...");
+        self.journal(JournalEntry::Clear)?;
 
         // Clear Tantivy index (text search)
-        self.writer.delete_all_documents()?;
-        self.writer.commit()?;
+        self.writer()?.delete_all_documents()?;
+        self.writer()?.commit()?;
         info!("Text index cleared");
 
-        // Clear metadata database (file records, chunks, symbols)
+        // Clear metadata database (file records, chunks, symbols, journal)
         self.db.clear_all()?;
+        self.release_writer()?;
         info!("Metadata database cleared");
 
         let vectors_dir = self.paths.vectors_dir();
@@ -493,56 +1094,437 @@ impl Indexer {
 
     /// Remove one file from both Tantivy and metadata store.
     pub fn remove_file_from_index(&mut self, file_path: &Path) -> FlashgrepResult<()> {
-        let schema = self.index.schema();
-        let file_path_field = schema.get_field("file_path").unwrap();
-        self.writer.delete_term(Term::from_field_text(
-            file_path_field,
-            &file_path.to_string_lossy(),
-        ));
-        self.writer.commit()?;
+        self.purge_file(file_path)?;
+        self.commit()?;
+        Ok(())
+    }
+
+    /// Drop a file's Tantivy documents and metadata without committing.
+    ///
+    /// The Tantivy deletion only becomes visible after [`Indexer::commit`].
+    pub fn purge_file(&mut self, file_path: &Path) -> FlashgrepResult<()> {
+        self.journal(JournalEntry::File(file_path.to_path_buf()))?;
+        self.delete_file_documents(file_path)?;
         self.db.delete_file(file_path)?;
+        self.db.clear_index_failure(file_path)?;
+        self.db.clear_skipped_file(file_path)?;
+        Ok(())
+    }
+
+    /// Move an indexed file to a new path without re-chunking or re-embedding it.
+    ///
+    /// SQLite rows, vectors and graph nodes are re-keyed and the Tantivy documents
+    /// are rewritten from the stored chunks, without committing. Returns `false`,
+    /// changing nothing, if `from` is not indexed.
+    pub fn rename_file(&mut self, from: &Path, to: &Path) -> FlashgrepResult<bool> {
+        self.journal_all(&[
+            JournalEntry::File(from.to_path_buf()),
+            JournalEntry::File(to.to_path_buf()),
+        ])?;
+        if !self.db.rename_file(from, to)? {
+            return Ok(false);
+        }
+        self.delete_file_documents(from)?;
+        self.delete_file_documents(to)?;
+        for chunk in self.db.get_file_chunks(to)? {
+            self.add_chunk_to_tantivy(&chunk)?;
+        }
+        debug!("Moved {} to {}", from.display(), to.display());
+        Ok(true)
+    }
+
+    /// Reindex one file even if it is unchanged, without committing
+    pub fn force_reindex_file(&mut self, file_path: &Path) -> FlashgrepResult<bool> {
+        self.index_file_tracked(file_path, true)
+    }
+
+    /// Queue deletion of every Tantivy document for a file, by its exact
+    /// `file_path_key`. Text indexes written before the key existed are
+    /// migrated when the indexer opens them, so every document has one.
+    fn delete_file_documents(&mut self, file_path: &Path) -> FlashgrepResult<()> {
+        let field = self.index.schema().get_field("file_path_key")?;
+        self.writer()?
+            .delete_term(Term::from_field_text(field, &file_path.to_string_lossy()));
         Ok(())
     }
 
-    /// Remove indexed files that are now ignored by ignore patterns.
+    fn record_failure(&self, file_path: &Path, err: &dyn std::fmt::Display) {
+        if let Err(record_err) = self.db.record_index_failure(file_path, &err.to_string()) {
+            error!(
+                "Failed to record index failure for {}: {}",
+                file_path.display(),
+                record_err
+            );
+        }
+    }
+
+    /// Commit pending Tantivy changes, completing every journaled update, and
+    /// release the index write lock
+    pub fn commit(&mut self) -> FlashgrepResult<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        writer.commit()?;
+        self.db.clear_journal()?;
+        if !self.unfinished_updates.is_empty() {
+            self.db
+                .record_journal_entries(&std::mem::take(&mut self.unfinished_updates))?;
+        }
+        self.release_writer()
+    }
+
+    /// Reindex a selection of files without rescanning the whole repository.
+    ///
+    /// Each selector is a path (file or directory, absolute or relative to the
+    /// repository root) or a glob matched against repository-relative paths.
+    /// Selected files that no longer exist are removed from the index. With
+    /// `force`, unchanged files are reindexed too.
+    pub fn reindex_paths(
+        &mut self,
+        repo_root: &Path,
+        selectors: &[String],
+        force: bool,
+        reporter: &ProgressReporter,
+    ) -> FlashgrepResult<ReindexSummary> {
+        let scanner = FileScanner::new(repo_root.to_path_buf(), self.config.clone());
+        let candidates: Vec<PathBuf> = scanner.scan().collect();
+        let (targets, ignored) = select_reindex_targets(repo_root, &candidates, selectors)?;
+        let total = targets.len() as u64;
+        let mut summary = ReindexSummary {
+            matched: targets.len(),
+            ignored,
+            ..ReindexSummary::default()
+        };
+
+        for (done, path) in targets.iter().enumerate() {
+            if reporter.is_cancelled() {
+                self.commit()?;
+                return Err(FlashgrepError::Cancelled("reindex".to_string()));
+            }
+            reporter.report_every("reindex", done as u64, Some(total), INDEX_PROGRESS_INTERVAL);
+
+            if !path.exists() {
+                self.purge_file(path)?;
+                summary.removed += 1;
+                continue;
+            }
+
+            match self.index_file_tracked(path, force) {
+                Ok(true) => summary.indexed += 1,
+                Ok(false) => summary.unchanged += 1,
+                Err(err) => {
+                    error!("Failed to reindex {}: {}", path.display(), err);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        self.commit()?;
+        reporter.report(total, Some(total), Some("reindex"));
+        Ok(summary)
+    }
+
+    /// Bring the index in line with changed ignore patterns: indexed files they
+    /// now ignore are removed and files they no longer ignore are indexed.
+    /// Other files and revision documents are left untouched.
     /// Returns (removed, kept) counts.
     pub fn reconcile_ignored_files(
         &mut self,
         repo_root: &Path,
         ignore_patterns: &FlashgrepIgnore,
     ) -> FlashgrepResult<(usize, usize)> {
-        let indexed_files = self.db.get_all_files()?;
+        let indexed_files: HashSet<PathBuf> = self.db.get_all_files()?.into_iter().collect();
         let mut to_remove = Vec::new();
         let mut kept = 0usize;
 
-        for path in indexed_files {
-            if ignore_patterns.is_ignored(&path, repo_root) {
-                to_remove.push(path);
+        for path in &indexed_files {
+            if ignore_patterns.is_ignored(path, repo_root) {
+                to_remove.push(path.clone());
             } else {
                 kept += 1;
             }
         }
 
-        if to_remove.is_empty() {
-            return Ok((0, kept));
+        // Documents are deleted by exact path key, which leaves revision
+        // documents and their stored bodies in place
+        for path in &to_remove {
+            self.purge_file(path)?;
         }
 
-        let removed = self.db.delete_files_bulk(&to_remove)?;
-
-        // Rebuild index content to guarantee text index and metadata consistency
-        // regardless of existing schema term/tokenization behavior.
-        self.writer.delete_all_documents()?;
-        self.writer.commit()?;
-        self.db.clear_all()?;
-        let _ = self.index_repository(repo_root)?;
+        // Files the new patterns no longer ignore are the walked ones the
+        // index has not seen, neither indexed nor skipped
+        let skipped: HashSet<PathBuf> = self
+            .db
+            .get_skipped_files()?
+            .into_iter()
+            .map(|file| file.file_path)
+            .collect();
+        let scanner = FileScanner::with_ignore_patterns(
+            repo_root.to_path_buf(),
+            self.config.clone(),
+            ignore_patterns.clone(),
+        );
+        let added: Vec<PathBuf> = scanner
+            .scan()
+            .filter(|path| !indexed_files.contains(path) && !skipped.contains(path))
+            .collect();
+        if added.is_empty() {
+            self.commit()?;
+        } else {
+            info!("Indexing {} files no longer ignored", added.len());
+            self.index_files_parallel(&added, false, "index", &ProgressReporter::disabled())?;
+        }
 
-        Ok((removed, kept))
+        Ok((to_remove.len(), kept))
     }
 
-    /// Get the database reference
-    pub fn db(&self) -> &Database {
-        &self.db
-    }
+    /// Index the tree of a git branch, tag or commit, reading blobs straight from
+    /// the object database without checking the revision out.
+    ///
+    /// Revision documents live in the text index next to the working tree's and
+    /// are only searched when a query asks for that revision. Reindexing a
+    /// revision replaces its documents; file metadata, symbols and neural
+    /// artifacts are only kept for the working tree.
+    pub fn index_revision(&mut self, rev: &str) -> FlashgrepResult<RevisionIndexSummary> {
+        let revision_field = self.index.schema().get_field("revision").map_err(|_| {
+            FlashgrepError::Config(format!(
+                "The text index predates revision support; delete {} and reindex",
+                self.paths.text_index_dir().display()
+            ))
+        })?;
+        let repo_root = self.paths.repo_root().to_path_buf();
+        let git = GitRepo::open(&repo_root)?;
+        let commit_id = git.resolve_commit(rev)?;
+        let commit_time = git.commit_time(&commit_id)?;
+        info!("Indexing revision {} ({})", rev, commit_id);
+
+        self.journal(JournalEntry::Revision(commit_id.clone()))?;
+        self.writer()?
+            .delete_term(Term::from_field_text(revision_field, &commit_id));
+        self.db.clear_revision_contents(&commit_id)?;
+
+        let scanner = FileScanner::new(repo_root.clone(), self.config.clone());
+        let mut blobs = git.blob_reader()?;
+        let mut summary = RevisionIndexSummary {
+            name: rev.to_string(),
+            commit_id: commit_id.clone(),
+            ..RevisionIndexSummary::default()
+        };
+
+        for entry in git.list_files(&commit_id)? {
+            let file_path = repo_root.join(&entry.path);
+            if !scanner.allows_path(&file_path) {
+                continue;
+            }
+            if entry.size > self.config.skip_file_size {
+                summary.skipped += 1;
+                continue;
+            }
+            let Some(decoded) = encoding::decode(&blobs.read(&entry.object_id)?) else {
+                summary.skipped += 1;
+                continue;
+            };
+            if !should_index_content(&file_path, &decoded.content, &self.config) {
+                continue;
+            }
+
+            let language = self.config.languages.detect(&file_path, &decoded.content);
+            let chunks = Self::chunk_content(
+                &self.config,
+                &self.chunker,
+                language,
+                &file_path,
+                &decoded.content,
+                commit_time,
+            );
+            self.db.add_revision_contents(&commit_id, &chunks)?;
+            for chunk in &chunks {
+                self.add_chunk_document(chunk, Some(&commit_id))?;
+            }
+            summary.files += 1;
+            summary.chunks += chunks.len();
+        }
+
+        self.db.record_revision(&IndexedRevision {
+            commit_id,
+            name: rev.to_string(),
+            file_count: summary.files,
+            chunk_count: summary.chunks,
+            indexed_at: chrono::Utc::now().timestamp(),
+        })?;
+        self.commit()?;
+        info!(
+            "Revision indexing complete: {} files, {} chunks, {} skipped",
+            summary.files, summary.chunks, summary.skipped
+        );
+        Ok(summary)
+    }
+
+    /// Reclaim space in the index directory.
+    ///
+    /// Orphaned rows are deleted, chunk bodies stored as plain text by older
+    /// versions are compressed, Tantivy segments are merged to drop deleted
+    /// documents and SQLite is vacuumed.
+    pub fn compact(&mut self) -> FlashgrepResult<CompactReport> {
+        let mut report = CompactReport {
+            bytes_before: self.paths.size_bytes(),
+            ..CompactReport::default()
+        };
+        info!("Compacting index ({} bytes)", report.bytes_before);
+
+        report.removed_rows = self.db.delete_orphaned_rows()?;
+        report.compressed_bodies = self.db.compress_plain_contents()?;
+
+        let segment_ids = self.index.searchable_segment_ids()?;
+        if !segment_ids.is_empty() {
+            self.writer()?.merge(&segment_ids).wait()?;
+        }
+        self.writer()?.garbage_collect_files().wait()?;
+        self.release_writer()?;
+        self.db.vacuum()?;
+
+        report.bytes_after = self.paths.size_bytes();
+        info!(
+            "Compaction complete: {} -> {} bytes",
+            report.bytes_before, report.bytes_after
+        );
+        Ok(report)
+    }
+
+    /// Migrate the text index to the current schema version by recreating it from
+    /// the live documents of the existing one, moving revision chunk bodies stored
+    /// by older versions into the metadata store. Documents whose body is not in
+    /// the metadata store are orphans and dropped. Returns the number of
+    /// documents written.
+    ///
+    /// The new index is built beside the live one while the live one stays
+    /// locked, and only swapped into its place once complete, so an
+    /// interrupted migration leaves either index intact for the next open.
+    fn rebuild_text_index(&mut self) -> FlashgrepResult<usize> {
+        let index_dir = self.paths.text_index_dir();
+        self.writer()?;
+        let current = Index::open_in_dir(&index_dir)?;
+        if text_index_version(&current, &index_dir)? == TEXT_SCHEMA_VERSION {
+            // Another process migrated it while we waited for the lock
+            self.release_writer()?;
+            self.index = current;
+            return Ok(0);
+        }
+        drop(current);
+
+        let schema = self.index.schema();
+        let file_path_field = schema.get_field("file_path")?;
+        let content_field = schema.get_field("content")?;
+        let start_line_field = schema.get_field("start_line")?;
+        let end_line_field = schema.get_field("end_line")?;
+        let content_hash_field = schema.get_field("content_hash")?;
+        let revision_field = schema.get_field("revision").ok();
+
+        let mut documents: Vec<(Chunk, Option<String>)> = Vec::new();
+        let searcher = self.index.reader()?.searcher();
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader.get_store_reader(1)?;
+            // The first schema version had no last_modified fast field
+            let last_modified = segment_reader.fast_fields().u64("last_modified").ok();
+            let mut revision_chunks: HashMap<String, Vec<Chunk>> = HashMap::new();
+            for doc_id in segment_reader.doc_ids_alive() {
+                let doc = store.get(doc_id)?;
+                let text = |field: Field| {
+                    doc.get_first(field)
+                        .and_then(|v| v.as_text())
+                        .unwrap_or("")
+                        .to_string()
+                };
+                let line = |field: Field| {
+                    doc.get_first(field).and_then(|v| v.as_u64()).unwrap_or(0) as usize
+                };
+                let chunk = Chunk {
+                    id: None,
+                    file_path: PathBuf::from(text(file_path_field)),
+                    start_line: line(start_line_field),
+                    end_line: line(end_line_field),
+                    content: text(content_field),
+                    content_hash: text(content_hash_field),
+                    last_modified: last_modified
+                        .as_ref()
+                        .and_then(|column| column.first(doc_id))
+                        .unwrap_or(0) as i64,
+                };
+                let revision = revision_field
+                    .and_then(|field| doc.get_first(field))
+                    .and_then(|v| v.as_text())
+                    .map(str::to_string);
+                let mut located = chunk.clone();
+                located.content.clear();
+                if let Some(commit_id) = &revision {
+                    revision_chunks
+                        .entry(commit_id.clone())
+                        .or_default()
+                        .push(chunk);
+                }
+                documents.push((located, revision));
+            }
+            for (commit_id, chunks) in revision_chunks {
+                self.db.add_revision_contents(&commit_id, &chunks)?;
+            }
+        }
+        drop(searcher);
+
+        // A staged index left by an interrupted migration is incomplete,
+        // or the live index would have been replaced by it
+        let staging_dir = self.paths.text_index_staging_dir();
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir)?;
+        }
+        std::fs::create_dir_all(&staging_dir)?;
+        let live_writer = self.writer.take();
+        self.index = Index::create_in_dir(&staging_dir, text_schema())?;
+
+        let mut written = 0usize;
+        for batch in documents.chunks_mut(REBUILD_BATCH_DOCUMENTS) {
+            let hashes: Vec<String> = batch
+                .iter()
+                .map(|(chunk, _)| chunk.content_hash.clone())
+                .collect();
+            let contents = self.db.get_chunk_contents(&hashes)?;
+            for (chunk, revision) in batch.iter_mut() {
+                let Some(content) = contents.get(&chunk.content_hash) else {
+                    debug!(
+                        "Dropping orphaned document for {}",
+                        chunk.file_path.display()
+                    );
+                    continue;
+                };
+                chunk.content = content.clone();
+                self.add_chunk_document(chunk, revision.as_deref())?;
+                chunk.content.clear();
+                written += 1;
+            }
+        }
+        // Journaled updates are left for recovery, which replays them next
+        self.writer()?.commit()?;
+        self.release_writer()?;
+        write_text_index_version(&staging_dir)?;
+
+        // The lock is kept in the live directory by path, so it is released
+        // before the directory is moved
+        drop(live_writer);
+        let retired_dir = self.paths.text_index_retired_dir();
+        if retired_dir.exists() {
+            std::fs::remove_dir_all(&retired_dir)?;
+        }
+        std::fs::rename(&index_dir, &retired_dir)?;
+        std::fs::rename(&staging_dir, &index_dir)?;
+        self.index = Index::open_in_dir(&index_dir)?;
+        std::fs::remove_dir_all(&retired_dir)?;
+        info!("Rebuilt text index with {} documents", written);
+        Ok(written)
+    }
+
+    /// Get the database reference
+    pub fn db(&self) -> &Database {
+        &self.db
+    }
 
     /// Get the Tantivy index
     pub fn tantivy_index(&self) -> &Index {
@@ -552,6 +1534,142 @@ impl Indexer {
 
 use crate::db::models::IndexStats;
 
+/// Per-file outcomes of [`Indexer::index_files_parallel`]
+#[derive(Debug, Clone, Copy, Default)]
+struct FileIndexCounts {
+    indexed: usize,
+    /// Unchanged since they were last indexed
+    skipped: usize,
+    /// Left out and recorded as skipped files
+    excluded: usize,
+    failed: usize,
+}
+
+/// Outcome of [`Indexer::reindex_paths`] and [`Indexer::index_changed_paths`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ReindexSummary {
+    /// Files selected by the selectors
+    pub matched: usize,
+    /// Files (re)indexed
+    pub indexed: usize,
+    /// Files skipped because they were unchanged
+    pub unchanged: usize,
+    /// Selected files that no longer exist and were removed
+    pub removed: usize,
+    /// Explicitly named files skipped because the scanner excludes them
+    pub ignored: usize,
+    /// Files that failed to index
+    pub failed: usize,
+}
+
+/// Outcome of [`Indexer::compact`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct CompactReport {
+    /// Size of the index directory before compaction
+    pub bytes_before: u64,
+    /// Size of the index directory after compaction
+    pub bytes_after: u64,
+    /// Chunk bodies compressed that were stored as plain text
+    pub compressed_bodies: usize,
+    /// Orphaned rows deleted from the metadata store
+    pub removed_rows: OrphanedRows,
+}
+
+impl CompactReport {
+    /// Bytes reclaimed, zero if the index grew
+    pub fn saved_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// Outcome of [`Indexer::index_revision`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RevisionIndexSummary {
+    /// Revision as given
+    pub name: String,
+    /// Commit id the revision resolved to
+    pub commit_id: String,
+    /// Files indexed from the revision's tree
+    pub files: usize,
+    /// Chunks indexed from those files
+    pub chunks: usize,
+    /// Files left out for size or binary content
+    pub skipped: usize,
+}
+
+fn select_reindex_targets(
+    repo_root: &Path,
+    candidates: &[PathBuf],
+    selectors: &[String],
+) -> FlashgrepResult<(Vec<PathBuf>, usize)> {
+    let mut targets = std::collections::BTreeSet::new();
+    let mut ignored = 0usize;
+
+    for selector in selectors {
+        let selector = selector.trim();
+        if selector.is_empty() {
+            continue;
+        }
+
+        if selector.contains(['*', '?', '[']) {
+            let pattern = glob::Pattern::new(&normalize_glob_pattern(selector)).map_err(|e| {
+                FlashgrepError::Config(format!("Invalid glob pattern '{}': {}", selector, e))
+            })?;
+            for candidate in candidates {
+                let rel = candidate.strip_prefix(repo_root).unwrap_or(candidate);
+                if pattern.matches(&normalize_path_for_matching(rel)) {
+                    targets.insert(candidate.clone());
+                }
+            }
+            continue;
+        }
+
+        let path = if Path::new(selector).is_absolute() {
+            PathBuf::from(selector)
+        } else {
+            repo_root.join(selector)
+        };
+        if path.is_dir() {
+            targets.extend(candidates.iter().filter(|c| c.starts_with(&path)).cloned());
+        } else if !path.exists() || candidates.contains(&path) {
+            targets.insert(path);
+        } else {
+            // Exists but is excluded by ignore rules, extension or size limits
+            ignored += 1;
+        }
+    }
+
+    Ok((targets.into_iter().collect(), ignored))
+}
+
+/// Open a Tantivy writer with `memory_bytes` of heap, waiting up to `timeout` for
+/// another process to release the index write lock. Returns `None` if it is still
+/// held after that.
+pub(crate) fn try_acquire_writer(
+    index: &Index,
+    memory_bytes: usize,
+    timeout: Duration,
+) -> FlashgrepResult<Option<IndexWriter>> {
+    let started = Instant::now();
+    let mut waiting = false;
+    loop {
+        match index.writer(memory_bytes) {
+            Ok(writer) => return Ok(Some(writer)),
+            Err(TantivyError::LockFailure(LockError::LockBusy, _))
+                if started.elapsed() < timeout =>
+            {
+                if !waiting {
+                    info!("Waiting for another flashgrep process to finish writing the index");
+                    waiting = true;
+                }
+                std::thread::sleep(WRITER_LOCK_RETRY);
+            }
+            Err(TantivyError::LockFailure(LockError::LockBusy, _)) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -586,6 +1704,127 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_index_repository_detects_languages_without_extensions() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::create_dir_all(repo_root.join("bin"))?;
+        std::fs::write(
+            repo_root.join("Dockerfile"),
+            "FROM rust:1.80 AS builder\nRUN cargo build\n",
+        )?;
+        std::fs::write(
+            repo_root.join("bin/release"),
+            "#!/bin/sh\npublish_crate() {\n  cargo publish\n}\n",
+        )?;
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        indexer.index_repository(&repo_root)?;
+
+        let languages = indexer.db().language_breakdown()?;
+        assert!(languages
+            .iter()
+            .any(|(lang, files, _)| lang == "dockerfile" && *files == 1));
+        assert!(languages
+            .iter()
+            .any(|(lang, files, _)| lang == "shell" && *files == 1));
+        assert_eq!(indexer.db().find_symbols_by_name("builder")?.len(), 1);
+        assert_eq!(indexer.db().find_symbols_by_name("publish_crate")?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_repository_transcodes_non_utf8_files() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let utf16 = encoding::TextEncoding {
+            encoding: encoding_rs::UTF_16LE,
+            bom: true,
+        };
+        std::fs::write(
+            repo_root.join("wide.rs"),
+            utf16.encode("fn wide_function() {}\n")?,
+        )?;
+        std::fs::write(
+            repo_root.join("legacy.py"),
+            b"# caf\xe9 cr\xe8me\ndef legacy_function():\n    pass\n",
+        )?;
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        indexer.index_repository(&repo_root)?;
+
+        assert_eq!(indexer.db().find_symbols_by_name("wide_function")?.len(), 1);
+        assert_eq!(
+            indexer.db().find_symbols_by_name("legacy_function")?.len(),
+            1
+        );
+        assert_eq!(
+            indexer.db().get_file_encoding(&repo_root.join("wide.rs"))?,
+            Some("UTF-16LE".to_string())
+        );
+        assert_eq!(
+            indexer
+                .db()
+                .get_file_encoding(&repo_root.join("legacy.py"))?,
+            Some("windows-1252".to_string())
+        );
+        assert!(indexer.db().get_skipped_files()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_repository_applies_file_tiers() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        drop(Indexer::new(repo_root.clone())?);
+        let config = Config {
+            max_file_size: 1024,
+            skip_file_size: 64 * 1024,
+            ..Config::default()
+        };
+        config.to_file(&FlashgrepPaths::new(&repo_root).config_file())?;
+
+        let large: String = (0..400)
+            .map(|i| format!("fn large_{}() {{}}\n", i))
+            .collect();
+        std::fs::write(repo_root.join("large.rs"), &large)?;
+        let minified = format!("var a=1;{}\n", "b();".repeat(3000));
+        std::fs::write(repo_root.join("app.min.js"), &minified)?;
+        std::fs::write(repo_root.join("blob.rs"), b"fn x() {}\0\x01\x02")?;
+        std::fs::write(repo_root.join("huge.rs"), "x".repeat(64 * 1024 + 1))?;
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        let stats = indexer.index_repository(&repo_root)?;
+        assert_eq!(stats.total_files, 2);
+
+        let chunk_counts: HashMap<PathBuf, usize> =
+            indexer.db().file_chunk_counts()?.into_iter().collect();
+        assert!(chunk_counts[&repo_root.join("large.rs")] > 1);
+        assert!(chunk_counts[&repo_root.join("app.min.js")] > 1);
+        assert_eq!(indexer.db().find_symbols_by_name("large_399")?.len(), 1);
+
+        let skipped = indexer.db().get_skipped_files()?;
+        let reasons: Vec<(PathBuf, &str)> = skipped
+            .iter()
+            .map(|s| (s.file_path.clone(), s.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (repo_root.join("blob.rs"), "binary"),
+                (repo_root.join("huge.rs"), "too_large"),
+            ]
+        );
+
+        // A skipped file that becomes indexable loses its skip record
+        std::fs::write(repo_root.join("blob.rs"), "fn blob() {}\n")?;
+        assert!(indexer.force_reindex_file(&repo_root.join("blob.rs"))?);
+        assert_eq!(indexer.db().get_skipped_files()?.len(), 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_index_file_builds_neural_artifacts_when_enabled() -> FlashgrepResult<()> {
         let temp_dir = TempDir::new()?;
@@ -613,4 +1852,548 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_rename_file_keeps_vectors_and_moves_documents() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+
+        let paths = FlashgrepPaths::new(&repo_root);
+        paths.create()?;
+        let mut config = Config::default();
+        config.neural.enabled = true;
+        config.neural.initialized = true;
+        config.to_file(&paths.config_file())?;
+
+        let old_path = repo_root.join("old.rs");
+        let new_path = repo_root.join("new.rs");
+        std::fs::write(&old_path, "fn relocated_handler() {}\n")?;
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        indexer.index_file(&old_path)?;
+        indexer.commit()?;
+
+        let model_id = format!(
+            "provider:{}:{}",
+            config.neural.provider.provider, config.neural.provider.model
+        );
+        let before = indexer.db().get_semantic_chunks(&model_id)?;
+
+        std::fs::rename(&old_path, &new_path)?;
+        assert!(indexer.rename_file(&old_path, &new_path)?);
+        indexer.commit()?;
+        assert!(!indexer.rename_file(&old_path, &new_path)?);
+
+        // The moved file is up to date, so it is not chunked or embedded again
+        assert!(!indexer.index_file(&new_path)?);
+        let after = indexer.db().get_semantic_chunks(&model_id)?;
+        assert_eq!(after.len(), before.len());
+        assert!(after.iter().all(|chunk| chunk.file_path == new_path));
+        assert_eq!(after[0].embedding, before[0].embedding);
+
+        let reader = indexer.tantivy_index().reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), before.len() as u64);
+        let file_path_field = indexer.tantivy_index().schema().get_field("file_path")?;
+        let (_, address) = searcher.search(
+            &tantivy::query::AllQuery,
+            &tantivy::collector::TopDocs::with_limit(1),
+        )?[0];
+        let doc = searcher.doc(address)?;
+        assert_eq!(
+            doc.get_first(file_path_field).and_then(|v| v.as_text()),
+            Some(new_path.to_string_lossy().as_ref())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_chunk_settings_rechunk_the_index() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let file_path = repo_root.join("lib.rs");
+        let content = (1..=12)
+            .map(|i| format!("fn f{}() {{}}\n", i))
+            .collect::<String>();
+        std::fs::write(&file_path, &content)?;
+        {
+            let mut indexer = Indexer::new(repo_root.clone())?;
+            indexer.index_repository(&repo_root)?;
+            assert_eq!(indexer.db().get_file_chunks(&file_path)?.len(), 1);
+        }
+        // Reopening with the same settings leaves the chunks alone
+        assert_eq!(
+            Indexer::new(repo_root.clone())?.rechunk_if_settings_changed()?,
+            0
+        );
+
+        let paths = FlashgrepPaths::new(&repo_root);
+        let mut config = Config::from_file(&paths.config_file())?;
+        config.max_chunk_lines = 6;
+        config.chunk_overlap_lines = 2;
+        config.to_file(&paths.config_file())?;
+
+        let indexer = Indexer::new(repo_root.clone())?;
+        let chunks = indexer.db().get_file_chunks(&file_path)?;
+        let ranges: Vec<_> = chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, vec![(1, 4), (3, 8), (7, 12)]);
+        let searcher = indexer.tantivy_index().reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(
+            indexer.db().get_meta(CHUNKING_META_KEY)?,
+            Some(config.chunking_fingerprint())
+        );
+        Ok(())
+    }
+
+    /// Replace the text index in `index_dir` with a schema version 2 index
+    /// holding `chunk` as its only working tree document
+    fn create_legacy_text_index(index_dir: &Path, chunk: &Chunk) -> FlashgrepResult<Index> {
+        if index_dir.exists() {
+            std::fs::remove_dir_all(index_dir)?;
+        }
+        std::fs::create_dir_all(index_dir)?;
+        let mut builder = Schema::builder();
+        builder.add_text_field("file_path", TEXT | STORED);
+        builder.add_text_field("file_path_key", STRING);
+        builder.add_text_field("content", TEXT | STORED);
+        builder.add_u64_field("start_line", STORED | FAST);
+        builder.add_u64_field("end_line", STORED | FAST);
+        builder.add_text_field("content_hash", STRING | STORED);
+        builder.add_u64_field("last_modified", FAST);
+        builder.add_text_field("revision", STRING | STORED);
+        let legacy = Index::create_in_dir(index_dir, builder.build())?;
+        let schema = legacy.schema();
+        let mut writer = legacy.writer(15_000_000)?;
+        let mut working_tree = Document::default();
+        working_tree.add_text(
+            schema.get_field("file_path")?,
+            chunk.file_path.to_string_lossy(),
+        );
+        working_tree.add_text(schema.get_field("content")?, &chunk.content);
+        working_tree.add_u64(schema.get_field("start_line")?, 1);
+        working_tree.add_u64(schema.get_field("end_line")?, 1);
+        working_tree.add_text(schema.get_field("content_hash")?, &chunk.content_hash);
+        working_tree.add_u64(schema.get_field("last_modified")?, 1000);
+        writer.add_document(working_tree)?;
+        writer.commit()?;
+        Ok(legacy)
+    }
+
+    #[test]
+    fn test_text_index_without_path_keys_is_migrated_before_files_are_replaced(
+    ) -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::create_dir_all(repo_root.join("src/nested dir"))?;
+        let file_path = repo_root.join("src/nested dir/multi_token-path.rs");
+        std::fs::write(&file_path, "fn before_edit() {}\n")?;
+        let chunk = {
+            let mut indexer = Indexer::new(repo_root.clone())?;
+            indexer.index_repository(&repo_root)?;
+            indexer.db().get_file_chunks(&file_path)?.remove(0)
+        };
+
+        // The first schema version had no `file_path_key` to delete by
+        let paths = FlashgrepPaths::new(&repo_root);
+        std::fs::remove_dir_all(paths.text_index_dir())?;
+        std::fs::create_dir_all(paths.text_index_dir())?;
+        let mut builder = Schema::builder();
+        builder.add_text_field("file_path", TEXT | STORED);
+        builder.add_text_field("content", TEXT | STORED);
+        builder.add_u64_field("start_line", STORED | FAST);
+        builder.add_u64_field("end_line", STORED | FAST);
+        builder.add_text_field("content_hash", STRING | STORED);
+        let legacy = Index::create_in_dir(paths.text_index_dir(), builder.build())?;
+        {
+            let schema = legacy.schema();
+            let mut writer = legacy.writer(15_000_000)?;
+            let mut doc = Document::default();
+            doc.add_text(schema.get_field("file_path")?, file_path.to_string_lossy());
+            doc.add_text(schema.get_field("content")?, &chunk.content);
+            doc.add_u64(schema.get_field("start_line")?, 1);
+            doc.add_u64(schema.get_field("end_line")?, 1);
+            doc.add_text(schema.get_field("content_hash")?, &chunk.content_hash);
+            writer.add_document(doc)?;
+            writer.commit()?;
+        }
+        drop(legacy);
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        std::fs::write(&file_path, "fn after_edit() {}\nfn second() {}\n")?;
+        indexer.index_file_tracked(&file_path, true)?;
+        indexer.commit()?;
+
+        let field = indexer
+            .tantivy_index()
+            .schema()
+            .get_field("file_path_key")?;
+        let searcher = indexer.tantivy_index().reader()?.searcher();
+        let documents = searcher.search(
+            &tantivy::query::TermQuery::new(
+                Term::from_field_text(field, &file_path.to_string_lossy()),
+                tantivy::schema::IndexRecordOption::Basic,
+            ),
+            &tantivy::collector::Count,
+        )?;
+        assert_eq!(documents, indexer.db().get_file_chunks(&file_path)?.len());
+        assert_eq!(searcher.num_docs() as usize, documents);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interrupted_text_index_migration_is_recovered() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let file_path = repo_root.join("lib.rs");
+        std::fs::write(&file_path, "fn migrated_handler() {}\n")?;
+        let chunk = {
+            let mut indexer = Indexer::new(repo_root.clone())?;
+            indexer.index_repository(&repo_root)?;
+            indexer.db().get_file_chunks(&file_path)?.remove(0)
+        };
+        let paths = FlashgrepPaths::new(&repo_root);
+        let assert_recovered = |indexer: &Indexer| -> FlashgrepResult<()> {
+            assert_eq!(
+                text_index_version(indexer.tantivy_index(), &paths.text_index_dir())?,
+                TEXT_SCHEMA_VERSION
+            );
+            assert!(!paths.text_index_staging_dir().exists());
+            assert!(!paths.text_index_retired_dir().exists());
+            let schema = indexer.tantivy_index().schema();
+            let parser = tantivy::query::QueryParser::for_index(
+                indexer.tantivy_index(),
+                vec![schema.get_field("content")?],
+            );
+            let searcher = indexer.tantivy_index().reader()?.searcher();
+            let query = parser.parse_query("migrated_handler")?;
+            assert_eq!(searcher.search(&query, &tantivy::collector::Count)?, 1);
+            Ok(())
+        };
+
+        // Interrupted while building: the old index is still live beside a
+        // partial rebuild without its version file
+        drop(create_legacy_text_index(&paths.text_index_dir(), &chunk)?);
+        std::fs::create_dir_all(paths.text_index_staging_dir())?;
+        Index::create_in_dir(paths.text_index_staging_dir(), text_schema())?;
+        assert_recovered(&Indexer::new(repo_root.clone())?)?;
+
+        // Interrupted between the renames: the finished rebuild is staged and
+        // the old index moved aside, leaving no live index
+        std::fs::rename(paths.text_index_dir(), paths.text_index_staging_dir())?;
+        drop(create_legacy_text_index(
+            &paths.text_index_retired_dir(),
+            &chunk,
+        )?);
+        std::fs::create_dir_all(paths.text_index_dir())?;
+        assert_recovered(&Indexer::new(repo_root.clone())?)?;
+
+        // Interrupted before anything was staged: the old index is restored
+        drop(create_legacy_text_index(
+            &paths.text_index_retired_dir(),
+            &chunk,
+        )?);
+        std::fs::remove_dir_all(paths.text_index_dir())?;
+        assert_recovered(&Indexer::new(repo_root.clone())?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_opening_older_text_index_migrates_it() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let file_path = repo_root.join("lib.rs");
+        std::fs::write(&file_path, "fn compacted_handler() {}\n")?;
+        let chunk = {
+            let mut indexer = Indexer::new(repo_root.clone())?;
+            indexer.index_repository(&repo_root)?;
+            indexer.db().get_file_chunks(&file_path)?.remove(0)
+        };
+
+        // Recreate the text index the way older versions stored it, with bodies
+        let paths = FlashgrepPaths::new(&repo_root);
+        let legacy = create_legacy_text_index(&paths.text_index_dir(), &chunk)?;
+        {
+            let schema = legacy.schema();
+            let mut writer = legacy.writer(15_000_000)?;
+            let old = Chunk::new(file_path.clone(), 1, 1, "fn retired() {}".to_string(), 500);
+            let mut revision = Document::default();
+            revision.add_text(schema.get_field("file_path")?, file_path.to_string_lossy());
+            revision.add_text(schema.get_field("content")?, &old.content);
+            revision.add_u64(schema.get_field("start_line")?, 1);
+            revision.add_u64(schema.get_field("end_line")?, 1);
+            revision.add_text(schema.get_field("content_hash")?, &old.content_hash);
+            revision.add_u64(schema.get_field("last_modified")?, 500);
+            revision.add_text(schema.get_field("revision")?, "abc123");
+            writer.add_document(revision)?;
+            writer.commit()?;
+        }
+        drop(legacy);
+
+        let reopened = Index::open_in_dir(paths.text_index_dir())?;
+        assert_eq!(text_index_version(&reopened, &paths.text_index_dir())?, 2);
+        drop(reopened);
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        assert_eq!(
+            text_index_version(indexer.tantivy_index(), &paths.text_index_dir())?,
+            TEXT_SCHEMA_VERSION
+        );
+        let schema = indexer.tantivy_index().schema();
+        assert!(!schema
+            .get_field_entry(schema.get_field("content")?)
+            .is_stored());
+        let retired = Chunk::new(file_path.clone(), 1, 1, "fn retired() {}".to_string(), 500);
+        let bodies = indexer
+            .db()
+            .get_chunk_contents(std::slice::from_ref(&retired.content_hash))?;
+        assert_eq!(bodies[&retired.content_hash], "fn retired() {}");
+
+        // Both documents are still searchable, bodies now coming from the store
+        let searcher = indexer.tantivy_index().reader()?.searcher();
+        let parser = tantivy::query::QueryParser::for_index(
+            indexer.tantivy_index(),
+            vec![schema.get_field("content")?],
+        );
+        for term in ["compacted_handler", "retired"] {
+            let hits = searcher.search(&parser.parse_query(term)?, &tantivy::collector::Count)?;
+            assert_eq!(hits, 1, "{}", term);
+        }
+
+        let report = indexer.compact()?;
+        assert_eq!(report.compressed_bodies, 0);
+        assert_eq!(
+            report.saved_bytes(),
+            report.bytes_before.saturating_sub(report.bytes_after)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_interrupted_updates_are_replayed_on_open() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let kept = repo_root.join("kept.rs");
+        let removed = repo_root.join("removed.rs");
+        std::fs::write(&kept, "fn before_crash() {}\n")?;
+        std::fs::write(&removed, "fn removed_handler() {}\n")?;
+        {
+            let mut indexer = Indexer::new(repo_root.clone())?;
+            indexer.index_repository(&repo_root)?;
+            assert!(indexer.db().get_journal_entries()?.is_empty());
+
+            // SQLite is updated, then the process stops before the text index commit
+            std::fs::write(&kept, "fn after_crash() {}\nfn second() {}\n")?;
+            assert!(indexer.force_reindex_file(&kept)?);
+            std::fs::remove_file(&removed)?;
+            indexer.purge_file(&removed)?;
+            assert_eq!(indexer.db().get_journal_entries()?.len(), 2);
+        }
+
+        let indexer = Indexer::new(repo_root.clone())?;
+        assert!(indexer.db().get_journal_entries()?.is_empty());
+        let chunks = indexer.db().get_file_chunks(&kept)?;
+        assert!(chunks[0].content.contains("after_crash"));
+        assert!(indexer.db().get_file_chunks(&removed)?.is_empty());
+
+        let schema = indexer.tantivy_index().schema();
+        let searcher = indexer.tantivy_index().reader()?.searcher();
+        assert_eq!(searcher.num_docs() as usize, chunks.len());
+        let parser = tantivy::query::QueryParser::for_index(
+            indexer.tantivy_index(),
+            vec![schema.get_field("content")?],
+        );
+        for (term, expected) in [
+            ("after_crash", 1),
+            ("before_crash", 0),
+            ("removed_handler", 0),
+        ] {
+            let hits = searcher.search(&parser.parse_query(term)?, &tantivy::collector::Count)?;
+            assert_eq!(hits, expected, "{}", term);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_lock_is_held_only_until_commit() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let paths = FlashgrepPaths::new(&repo_root);
+        paths.create()?;
+        let config = Config {
+            writer_lock_timeout_ms: 200,
+            ..Config::default()
+        };
+        config.to_file(&paths.config_file())?;
+        std::fs::write(repo_root.join("a.rs"), "fn alpha() {}\n")?;
+        std::fs::write(repo_root.join("b.rs"), "fn beta() {}\n")?;
+
+        let mut first = Indexer::new(repo_root.clone())?;
+        let mut second = Indexer::new(repo_root.clone())?;
+        assert!(first.index_file(&repo_root.join("a.rs"))?);
+
+        // The first indexer holds the lock until it commits
+        let err = second.index_file(&repo_root.join("b.rs")).unwrap_err();
+        assert!(matches!(err, FlashgrepError::Index(_)), "{}", err);
+        assert!(err.to_string().contains("writer_lock_timeout_ms"));
+        // Its open journal entries are not replayed by another process
+        assert!(Indexer::new(repo_root.clone()).is_err());
+
+        first.commit()?;
+        assert!(second.index_file(&repo_root.join("b.rs"))?);
+        second.commit()?;
+
+        let searcher = first.tantivy_index().reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interrupted_revision_index_is_discarded_on_open() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let chunk = Chunk::new(
+            repo_root.join("old.rs"),
+            1,
+            1,
+            "fn old() {}".to_string(),
+            500,
+        );
+        {
+            let indexer = Indexer::new(repo_root.clone())?;
+            let db = indexer.db();
+            db.record_journal_entries(&[JournalEntry::Revision("abc123".to_string())])?;
+            db.add_revision_contents("abc123", std::slice::from_ref(&chunk))?;
+            db.record_revision(&IndexedRevision {
+                commit_id: "abc123".to_string(),
+                name: "main".to_string(),
+                file_count: 1,
+                chunk_count: 1,
+                indexed_at: 1000,
+            })?;
+        }
+
+        let indexer = Indexer::new(repo_root)?;
+        assert!(indexer.db().get_revisions()?.is_empty());
+        assert!(indexer
+            .db()
+            .get_chunk_contents(std::slice::from_ref(&chunk.content_hash))?
+            .is_empty());
+        assert!(indexer.db().get_journal_entries()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancelled_indexing_commits_persisted_files() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        for i in 0..150 {
+            std::fs::write(
+                repo_root.join(format!("f{i}.rs")),
+                format!("fn f{i}() {{}}\n"),
+            )?;
+        }
+
+        // Cancel as soon as the first progress update arrives, mid-persist
+        let token = crate::progress::CancellationToken::new();
+        let sink_token = token.clone();
+        let sink: crate::progress::ProgressSink = std::sync::Arc::new(move |_, _, _| {
+            sink_token.cancel();
+        });
+        let reporter = ProgressReporter::new(Some(sink), token);
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        let result = indexer.index_repository_with_progress(&repo_root, &reporter);
+        assert!(matches!(result, Err(FlashgrepError::Cancelled(_))));
+
+        let persisted = indexer.get_stats()?.total_files;
+        assert!(persisted > 0 && persisted < 150, "{}", persisted);
+        let reader = indexer.tantivy_index().reader()?;
+        assert_eq!(reader.searcher().num_docs(), persisted as u64);
+        assert!(indexer.db.get_journal_entries()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_file_update_is_removed_and_retried() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let good = repo_root.join("good.rs");
+        let broken = repo_root.join("broken.rs");
+        std::fs::write(&good, "fn good() {}\n")?;
+        std::fs::write(&broken, "fn broken() {}\n")?;
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        indexer.index_repository(&repo_root)?;
+        std::fs::write(&broken, "fn broken_again() {}\n")?;
+
+        // Fail the chunk insert, after the file row has been rewritten
+        let conn = rusqlite::Connection::open(indexer.paths.metadata_db())?;
+        conn.execute_batch(
+            "CREATE TRIGGER fail_broken BEFORE INSERT ON chunks
+             WHEN NEW.file_path LIKE '%broken.rs'
+             BEGIN SELECT RAISE(ABORT, 'injected failure'); END;",
+        )?;
+        indexer.index_files_parallel(
+            std::slice::from_ref(&broken),
+            true,
+            "index",
+            &ProgressReporter::disabled(),
+        )?;
+
+        assert_eq!(indexer.db.get_all_files()?, vec![good.clone()]);
+        assert!(indexer.db.get_journal_entries()?.is_empty());
+        let reader = indexer.tantivy_index().reader()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+
+        // The next scan finds the file missing and indexes it, not "unchanged"
+        conn.execute_batch("DROP TRIGGER fail_broken")?;
+        let counts = indexer.index_files_parallel(
+            &[good, broken.clone()],
+            false,
+            "index",
+            &ProgressReporter::disabled(),
+        )?;
+        assert_eq!((counts.indexed, counts.skipped), (1, 1));
+        assert_eq!(indexer.db.get_file_chunks(&broken)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_paths_replaces_documents_and_prunes_missing() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::write(repo_root.join("a.rs"), "fn alpha() {}\n")?;
+        std::fs::write(repo_root.join("b.py"), "def beta(): pass\n")?;
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        indexer.index_repository(&repo_root)?;
+
+        std::fs::write(repo_root.join("a.rs"), "fn gamma() {}\n")?;
+        let summary = indexer.reindex_paths(
+            &repo_root,
+            &["**/*.rs".to_string()],
+            true,
+            &ProgressReporter::disabled(),
+        )?;
+        assert_eq!(summary.matched, 1);
+        assert_eq!(summary.indexed, 1);
+
+        let reader = indexer.tantivy_index().reader()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+
+        std::fs::remove_file(repo_root.join("b.py"))?;
+        let summary = indexer.reindex_paths(
+            &repo_root,
+            &["b.py".to_string()],
+            false,
+            &ProgressReporter::disabled(),
+        )?;
+        assert_eq!(summary.removed, 1);
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(indexer.get_stats()?.total_files, 1);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,339 @@
+//! Search over commit messages, authors and changed paths from local git history.
+//!
+//! Commits live in their own Tantivy index under `.flashgrep/history_index`. The
+//! last indexed commit is stored as the index's commit payload, so an update only
+//! adds the commits made since then; rewritten history triggers a rebuild.
+
+use crate::config::paths::FlashgrepPaths;
+use crate::config::Config;
+use crate::db::Database;
+use crate::git::GitRepo;
+use crate::index::engine::try_acquire_writer;
+use crate::FlashgrepResult;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::*;
+use tantivy::{Index, Term};
+use tracing::{info, warn};
+
+/// Writer memory budget; commit documents are small
+const HISTORY_WRITER_HEAP_BYTES: usize = 15_000_000;
+
+/// Tantivy index of the repository's commits
+pub struct HistoryIndex {
+    repo_root: PathBuf,
+    index: Index,
+}
+
+/// Outcome of [`HistoryIndex::update`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HistoryUpdate {
+    /// Commit the index is now up to date with
+    pub head: String,
+    /// Commits added by this update
+    pub added_commits: usize,
+    /// Whether the index was rebuilt from scratch
+    pub rebuilt: bool,
+    /// Whether another process kept the index write lock, so the index was left
+    /// at `head` instead of being brought up to date
+    pub stale: bool,
+}
+
+/// A commit matching a history search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryHit {
+    pub commit_id: String,
+    pub author: String,
+    /// Author date as a Unix timestamp
+    pub date: i64,
+    /// First line of the commit message
+    pub summary: String,
+    pub message: String,
+    pub score: f32,
+    /// Paths changed by the commit, relative to the repository root
+    pub paths: Vec<String>,
+    /// Changed paths that are currently indexed, as absolute paths
+    pub indexed_files: Vec<PathBuf>,
+}
+
+impl HistoryIndex {
+    /// Open the history index for a repository, creating it if needed
+    pub fn open(paths: &FlashgrepPaths) -> FlashgrepResult<Self> {
+        let dir = paths.history_index_dir();
+        let index = if dir.join("meta.json").exists() {
+            Index::open_in_dir(&dir)?
+        } else {
+            std::fs::create_dir_all(&dir)?;
+            Index::create_in_dir(&dir, Self::create_schema())?
+        };
+        Ok(Self {
+            repo_root: paths.repo_root().to_path_buf(),
+            index,
+        })
+    }
+
+    fn create_schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("commit_id", STRING | STORED);
+        schema_builder.add_text_field("author", TEXT | STORED);
+        schema_builder.add_i64_field("date", STORED | FAST);
+        schema_builder.add_text_field("message", TEXT | STORED);
+        // One value per changed path
+        schema_builder.add_text_field("paths", TEXT | STORED);
+        schema_builder.build()
+    }
+
+    /// Commit the index was last brought up to date with
+    pub fn indexed_head(&self) -> FlashgrepResult<Option<String>> {
+        Ok(self.index.load_metas()?.payload)
+    }
+
+    /// Add the commits made since the last update, rebuilding if history was rewritten.
+    ///
+    /// The write lock is only taken when the index is behind HEAD. If another
+    /// process holds it for longer than `lock_timeout`, the index is left as is
+    /// and the update is reported as stale.
+    pub fn update(&self, repo: &GitRepo, lock_timeout: Duration) -> FlashgrepResult<HistoryUpdate> {
+        let head = repo.resolve_commit("HEAD")?;
+        if self.indexed_head()?.as_deref() == Some(head.as_str()) {
+            return Ok(HistoryUpdate {
+                head,
+                ..HistoryUpdate::default()
+            });
+        }
+
+        let Some(mut writer) =
+            try_acquire_writer(&self.index, HISTORY_WRITER_HEAP_BYTES, lock_timeout)?
+        else {
+            warn!("History index is being updated by another process; searching it as is");
+            return Ok(HistoryUpdate {
+                head: self.indexed_head()?.unwrap_or_default(),
+                stale: true,
+                ..HistoryUpdate::default()
+            });
+        };
+        // Another process may have caught the index up while this one waited
+        let previous = self.indexed_head()?;
+        if previous.as_deref() == Some(head.as_str()) {
+            return Ok(HistoryUpdate {
+                head,
+                ..HistoryUpdate::default()
+            });
+        }
+        let incremental = previous.filter(|previous| repo.is_ancestor(previous, &head));
+        let range = match &incremental {
+            Some(previous) => format!("{}..{}", previous, head),
+            None => head.clone(),
+        };
+        let commits = repo.log(&range)?;
+
+        let schema = self.index.schema();
+        let commit_id_field = schema.get_field("commit_id")?;
+        let author_field = schema.get_field("author")?;
+        let date_field = schema.get_field("date")?;
+        let message_field = schema.get_field("message")?;
+        let paths_field = schema.get_field("paths")?;
+
+        if incremental.is_none() {
+            writer.delete_all_documents()?;
+        }
+        for commit in &commits {
+            writer.delete_term(Term::from_field_text(commit_id_field, &commit.commit_id));
+            let mut doc = Document::default();
+            doc.add_text(commit_id_field, &commit.commit_id);
+            doc.add_text(author_field, &commit.author);
+            doc.add_i64(date_field, commit.date);
+            doc.add_text(message_field, &commit.message);
+            for path in &commit.paths {
+                doc.add_text(paths_field, path);
+            }
+            writer.add_document(doc)?;
+        }
+        let mut prepared = writer.prepare_commit()?;
+        prepared.set_payload(&head);
+        prepared.commit()?;
+
+        info!(
+            "History index updated to {}: {} commits added{}",
+            head,
+            commits.len(),
+            if incremental.is_none() {
+                " (rebuilt)"
+            } else {
+                ""
+            }
+        );
+        Ok(HistoryUpdate {
+            head,
+            added_commits: commits.len(),
+            rebuilt: incremental.is_none(),
+            stale: false,
+        })
+    }
+
+    /// Search commit messages, authors and changed paths, best matches first.
+    /// `indexed_files` is the set of absolute paths currently in the code index.
+    pub fn search(
+        &self,
+        text: &str,
+        limit: usize,
+        indexed_files: &HashSet<PathBuf>,
+    ) -> FlashgrepResult<Vec<HistoryHit>> {
+        let schema = self.index.schema();
+        let commit_id_field = schema.get_field("commit_id")?;
+        let author_field = schema.get_field("author")?;
+        let date_field = schema.get_field("date")?;
+        let message_field = schema.get_field("message")?;
+        let paths_field = schema.get_field("paths")?;
+
+        let searcher = self.index.reader()?.searcher();
+        let parser =
+            QueryParser::for_index(&self.index, vec![message_field, author_field, paths_field]);
+        // Questions like "when did we change the retry logic?" are not query syntax
+        let (query, _) = parser.parse_query_lenient(text);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit.max(1)))?;
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs {
+            let doc = searcher.doc(address)?;
+            let text_of = |field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_text())
+                    .unwrap_or("")
+                    .to_string()
+            };
+            let message = text_of(message_field);
+            let paths: Vec<String> = doc
+                .get_all(paths_field)
+                .filter_map(|v| v.as_text())
+                .map(ToString::to_string)
+                .collect();
+            let indexed = paths
+                .iter()
+                .map(|path| self.repo_root.join(path))
+                .filter(|path| indexed_files.contains(path))
+                .collect();
+            hits.push(HistoryHit {
+                commit_id: text_of(commit_id_field),
+                author: text_of(author_field),
+                date: doc
+                    .get_first(date_field)
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0),
+                summary: message.lines().next().unwrap_or("").to_string(),
+                message,
+                score,
+                paths,
+                indexed_files: indexed,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+/// Bring the repository's history index up to date, then search it
+pub fn search_history(
+    paths: &FlashgrepPaths,
+    text: &str,
+    limit: usize,
+) -> FlashgrepResult<(HistoryUpdate, Vec<HistoryHit>)> {
+    let repo = GitRepo::open(paths.repo_root())?;
+    let history = HistoryIndex::open(paths)?;
+    let config = Config::from_file(&paths.config_file()).unwrap_or_default();
+    let update = history.update(&repo, Duration::from_millis(config.writer_lock_timeout_ms))?;
+
+    let indexed_files: HashSet<PathBuf> = if paths.metadata_db().exists() {
+        Database::open_read_only(&paths.metadata_db())?
+            .get_all_files()?
+            .into_iter()
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let hits = history.search(text, limit, &indexed_files)?;
+    Ok((update, hits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::git;
+    use crate::index::engine::Indexer;
+    use tempfile::TempDir;
+
+    #[test]
+    fn history_search_updates_incrementally_and_links_indexed_files() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        git(&root, &["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("retry.rs"), "fn retry() {}\n")?;
+        git(&root, &["add", "retry.rs"]);
+        git(&root, &["commit", "-q", "-m", "Add retry logic"]);
+        std::fs::write(root.join("notes.txt"), "notes\n")?;
+        git(&root, &["add", "notes.txt"]);
+        git(&root, &["commit", "-q", "-m", "Write notes"]);
+
+        Indexer::new(root.clone())?.index_repository(&root)?;
+        let paths = FlashgrepPaths::new(&root);
+
+        let (update, hits) = search_history(&paths, "when did we change the retry logic?", 5)?;
+        assert!(update.rebuilt);
+        assert_eq!(update.added_commits, 2);
+        assert_eq!(hits[0].summary, "Add retry logic");
+        assert_eq!(hits[0].author, "flashgrep");
+        assert_eq!(hits[0].paths, vec!["retry.rs"]);
+        assert_eq!(hits[0].indexed_files, vec![root.join("retry.rs")]);
+
+        let (update, _) = search_history(&paths, "notes", 5)?;
+        assert_eq!(update.added_commits, 0);
+
+        std::fs::write(root.join("retry.rs"), "fn retry_with_backoff() {}\n")?;
+        git(&root, &["commit", "-q", "-am", "Tune retry backoff"]);
+        let (update, hits) = search_history(&paths, "backoff", 5)?;
+        assert!(!update.rebuilt);
+        assert_eq!(update.added_commits, 1);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].summary, "Tune retry backoff");
+
+        // Rewriting history rebuilds the index instead of keeping dropped commits
+        git(&root, &["reset", "-q", "--hard", "HEAD~1"]);
+        let (update, hits) = search_history(&paths, "backoff", 5)?;
+        assert!(update.rebuilt);
+        assert!(hits.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn busy_history_index_is_searched_as_is() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        git(&root, &["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("retry.rs"), "fn retry() {}\n")?;
+        git(&root, &["add", "retry.rs"]);
+        git(&root, &["commit", "-q", "-m", "Add retry logic"]);
+        let paths = FlashgrepPaths::new(&root);
+        let repo = GitRepo::open(&root)?;
+        let history = HistoryIndex::open(&paths)?;
+        let first = history.update(&repo, Duration::ZERO)?;
+
+        std::fs::write(root.join("retry.rs"), "fn retry_with_backoff() {}\n")?;
+        git(&root, &["commit", "-q", "-am", "Tune retry backoff"]);
+        let other_process = history.index.writer(HISTORY_WRITER_HEAP_BYTES)?;
+
+        let update = history.update(&repo, Duration::from_millis(50))?;
+        assert!(update.stale);
+        assert_eq!(update.head, first.head);
+        let hits = history.search("retry", 5, &HashSet::new())?;
+        assert_eq!(hits.len(), 1);
+
+        drop(other_process);
+        let update = history.update(&repo, Duration::from_millis(50))?;
+        assert!(!update.stale);
+        assert_eq!(update.added_commits, 1);
+        Ok(())
+    }
+}
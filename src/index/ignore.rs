@@ -0,0 +1,551 @@
+use crate::index::scanner::{normalize_repo_relative_path, DEFAULT_IGNORED_DIRS};
+use crate::FlashgrepResult;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use tracing::warn;
+
+/// Ignore rules following git's semantics, shared by the scanner, watcher and glob tool.
+///
+/// Sources, from highest to lowest precedence:
+/// 1. `.flashgrepignore` at the repository root
+/// 2. `.gitignore` files, deepest directory first
+/// 3. `.git/info/exclude`
+/// 4. the global excludes file (`core.excludesFile`, or `$XDG_CONFIG_HOME/git/ignore`)
+///
+/// Within a source the last matching pattern wins. As in git, a path inside an
+/// ignored directory stays ignored even if a later pattern negates it.
+///
+/// `.gitignore` files are read the first time a path below their directory is
+/// checked, so only the directories a walk actually enters are visited.
+#[derive(Debug, Default, Clone)]
+pub struct FlashgrepIgnore {
+    overrides: Option<IgnoreFile>,
+    /// Repository root the `.gitignore` files are read from, if any
+    root: Option<PathBuf>,
+    /// `.gitignore` files read so far by repository-relative directory, `None` when absent
+    gitignores: Arc<RwLock<HashMap<String, Option<Arc<IgnoreFile>>>>>,
+    excludes: Vec<IgnoreFile>,
+}
+
+/// Patterns from one ignore file, relative to the directory that holds it
+#[derive(Debug, Clone)]
+struct IgnoreFile {
+    /// Repository-relative directory of the file ("" for the root)
+    base: String,
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    regex: Regex,
+    is_negation: bool,
+    is_directory_only: bool,
+}
+
+impl FlashgrepIgnore {
+    /// Load `.flashgrepignore`, `.git/info/exclude` and the global git excludes
+    /// file; `.gitignore` files below `root` are read as paths are checked.
+    pub fn from_root(root: &Path) -> Self {
+        Self::load(root, global_excludes_file(root).as_deref())
+    }
+
+    /// Load ignore patterns from a single file, applied relative to the repository root
+    pub fn from_file(path: &Path) -> FlashgrepResult<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self {
+            overrides: Some(IgnoreFile::parse(String::new(), &content)),
+            ..Self::default()
+        })
+    }
+
+    fn load(root: &Path, global_excludes: Option<&Path>) -> Self {
+        let mut ignore = Self {
+            overrides: read_ignore_file(&root.join(".flashgrepignore"), ""),
+            root: Some(root.to_path_buf()),
+            ..Self::default()
+        };
+
+        if let Some(info_exclude) = git_dir(root).map(|dir| dir.join("info").join("exclude")) {
+            ignore.excludes.extend(read_ignore_file(&info_exclude, ""));
+        }
+        if let Some(global) = global_excludes {
+            ignore.excludes.extend(read_ignore_file(global, ""));
+        }
+
+        ignore
+    }
+
+    /// The `.gitignore` in a repository-relative directory, read on first use.
+    /// Directories the scanner never enters, such as `.git`, have none.
+    fn gitignore(&self, dir: &str) -> Option<Arc<IgnoreFile>> {
+        let root = self.root.as_ref()?;
+        if let Some(cached) = self
+            .gitignores
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(dir)
+        {
+            return cached.clone();
+        }
+
+        let skipped = dir
+            .split('/')
+            .any(|component| DEFAULT_IGNORED_DIRS.contains(&component));
+        let file = if skipped {
+            None
+        } else {
+            read_ignore_file(&root.join(dir).join(".gitignore"), dir).map(Arc::new)
+        };
+        self.gitignores
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(dir.to_string(), file.clone());
+        file
+    }
+
+    /// Check if a path is ignored
+    pub fn is_ignored(&self, path: &Path, root: &Path) -> bool {
+        let relative_str = normalize_repo_relative_path(path, root);
+        if relative_str.is_empty() {
+            return false;
+        }
+        self.is_relative_ignored(&relative_str, path.is_dir())
+    }
+
+    /// Check a normalized repository-relative path, including its parent directories
+    fn is_relative_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        for (idx, _) in rel.match_indices('/') {
+            if self.decide(&rel[..idx], true) == Some(true) {
+                return true;
+            }
+        }
+        self.decide(rel, is_dir) == Some(true)
+    }
+
+    /// Verdict of the highest-precedence source with a matching pattern:
+    /// `Some(true)` ignored, `Some(false)` re-included, `None` no match
+    fn decide(&self, rel: &str, is_dir: bool) -> Option<bool> {
+        if let Some(verdict) = self.overrides.as_ref().and_then(|f| f.matched(rel, is_dir)) {
+            return Some(verdict);
+        }
+
+        let mut dir = rel;
+        loop {
+            dir = match dir.rfind('/') {
+                Some(idx) => &dir[..idx],
+                None if dir.is_empty() => break,
+                None => "",
+            };
+            if let Some(verdict) = self.gitignore(dir).and_then(|f| f.matched(rel, is_dir)) {
+                return Some(verdict);
+            }
+            if dir.is_empty() {
+                break;
+            }
+        }
+
+        self.excludes.iter().find_map(|f| f.matched(rel, is_dir))
+    }
+}
+
+impl IgnoreFile {
+    fn parse(base: String, content: &str) -> Self {
+        let rules = content.lines().filter_map(IgnoreRule::parse).collect();
+        Self { base, rules }
+    }
+
+    fn matched(&self, rel: &str, is_dir: bool) -> Option<bool> {
+        let sub = if self.base.is_empty() {
+            rel
+        } else {
+            rel.strip_prefix(&self.base)?.strip_prefix('/')?
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.is_directory_only) && rule.regex.is_match(sub))
+            .map(|rule| !rule.is_negation)
+    }
+}
+
+impl IgnoreRule {
+    /// Parse one gitignore line; blank lines and comments yield `None`
+    fn parse(line: &str) -> Option<Self> {
+        let mut line = line.strip_suffix('\r').unwrap_or(line);
+        if line.starts_with('#') {
+            return None;
+        }
+        while line.ends_with(' ') && !line.ends_with("\\ ") {
+            line = &line[..line.len() - 1];
+        }
+
+        let is_negation = line.starts_with('!');
+        // `!` negates; a leading backslash escapes a literal `!` or `#`
+        let mut pattern = if is_negation || line.starts_with("\\!") || line.starts_with("\\#") {
+            &line[1..]
+        } else {
+            line
+        };
+
+        let is_directory_only = pattern.ends_with('/');
+        if is_directory_only {
+            pattern = &pattern[..pattern.len() - 1];
+        }
+        if pattern.is_empty() {
+            return None;
+        }
+
+        // A slash anywhere but the end anchors the pattern to the ignore file's directory
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        let prefix = if anchored { "" } else { "(?:.*/)?" };
+        let source = format!("^{}{}$", prefix, glob_to_regex(pattern));
+
+        match Regex::new(&source) {
+            Ok(regex) => Some(Self {
+                regex,
+                is_negation,
+                is_directory_only,
+            }),
+            Err(e) => {
+                warn!("Skipping invalid ignore pattern '{}': {}", line, e);
+                None
+            }
+        }
+    }
+}
+
+/// Translate a gitignore glob into a regex body (without anchors)
+fn glob_to_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_segment_start = i == 0 || chars[i - 1] == '/';
+                match chars.get(i + 2) {
+                    Some('/') if at_segment_start => {
+                        out.push_str("(?:.*/)?");
+                        i += 3;
+                    }
+                    None if at_segment_start => {
+                        out.push_str(".*");
+                        i += 2;
+                    }
+                    _ => {
+                        // Consecutive stars elsewhere behave like a single `*`
+                        while chars.get(i) == Some(&'*') {
+                            i += 1;
+                        }
+                        out.push_str("[^/]*");
+                    }
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match character_class(&chars, i) {
+                Some((class, end)) => {
+                    out.push_str(&class);
+                    i = end;
+                }
+                None => out.push_str("\\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                out.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    out
+}
+
+/// POSIX character classes git's wildmatch accepts inside brackets, such as `[:digit:]`
+const POSIX_CLASSES: [&str; 12] = [
+    "alnum", "alpha", "blank", "cntrl", "digit", "graph", "lower", "print", "punct", "space",
+    "upper", "xdigit",
+];
+
+/// Translate the bracket expression starting at `start`; returns the regex class
+/// and the index of the closing `]`, or `None` if the bracket is unterminated.
+/// Like git, no class matches `/`.
+fn character_class(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut body = String::new();
+    let mut first = true;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == ']' && !first {
+            let class = if negated {
+                format!("[^/{}]", body)
+            } else {
+                format!("[{}&&[^/]]", body)
+            };
+            return Some((class, i));
+        }
+        if let Some((name, end)) = posix_class(chars, i) {
+            body.push_str(&format!("[:{}:]", name));
+            first = false;
+            i = end + 1;
+            continue;
+        }
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                body.push('\\');
+                body.push(chars[i]);
+            }
+            '[' | ']' | '\\' | '&' | '~' | '^' => {
+                body.push('\\');
+                body.push(c);
+            }
+            _ => body.push(c),
+        }
+        first = false;
+        i += 1;
+    }
+    None
+}
+
+/// The POSIX class name at `start` if it opens a `[:name:]` git knows, with
+/// the index of its closing `]`
+fn posix_class(chars: &[char], start: usize) -> Option<(String, usize)> {
+    if chars.get(start) != Some(&'[') || chars.get(start + 1) != Some(&':') {
+        return None;
+    }
+    let rest = &chars[start + 2..];
+    let len = rest.windows(2).position(|pair| pair == [':', ']'])?;
+    let name: String = rest[..len].iter().collect();
+    POSIX_CLASSES
+        .contains(&name.as_str())
+        .then_some((name, start + 2 + len + 1))
+}
+
+fn read_ignore_file(path: &Path, base: &str) -> Option<IgnoreFile> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(IgnoreFile::parse(base.to_string(), &content))
+}
+
+/// The repository's git directory, following `gitdir:` files used by worktrees and submodules
+fn git_dir(root: &Path) -> Option<PathBuf> {
+    let dot_git = root.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = std::fs::read_to_string(&dot_git).ok()?;
+    let target = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(root.join(target))
+}
+
+/// Resolve `core.excludesFile` from the repository and user git configs, falling
+/// back to git's default `$XDG_CONFIG_HOME/git/ignore`.
+fn global_excludes_file(root: &Path) -> Option<PathBuf> {
+    let home = dirs::home_dir();
+    let xdg_config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home.as_ref().map(|h| h.join(".config")));
+
+    let mut configs = Vec::new();
+    configs.extend(git_dir(root).map(|dir| dir.join("config")));
+    configs.extend(home.as_ref().map(|h| h.join(".gitconfig")));
+    configs.extend(xdg_config.as_ref().map(|x| x.join("git").join("config")));
+
+    for config in configs {
+        if let Some(value) = read_core_excludes_file(&config) {
+            return Some(match (value.strip_prefix("~/"), &home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(value),
+            });
+        }
+    }
+    xdg_config.map(|x| x.join("git").join("ignore"))
+}
+
+/// Minimal git-config reader for the `excludesfile` key of the `[core]` section
+fn read_core_excludes_file(config: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(config).ok()?;
+    let mut in_core = false;
+    let mut value = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_core = line
+                .trim_matches(|c| c == '[' || c == ']')
+                .trim()
+                .eq_ignore_ascii_case("core");
+            continue;
+        }
+        if !in_core {
+            continue;
+        }
+        if let Some((key, val)) = line.split_once('=') {
+            if key.trim().eq_ignore_ascii_case("excludesfile") {
+                value = Some(val.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    value.filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rule_matches(pattern: &str, path: &str) -> bool {
+        let rule = IgnoreRule::parse(pattern).expect("valid pattern");
+        rule.regex.is_match(path)
+    }
+
+    #[test]
+    fn double_star_and_character_classes() {
+        assert!(rule_matches("**/logs", "logs"));
+        assert!(rule_matches("**/logs", "a/b/logs"));
+        assert!(rule_matches("logs/**", "logs/a/b.txt"));
+        assert!(!rule_matches("logs/**", "logs"));
+        assert!(rule_matches("a/**/b", "a/b"));
+        assert!(rule_matches("a/**/b", "a/x/y/b"));
+        assert!(rule_matches("*.py[co]", "pkg/mod.pyc"));
+        assert!(!rule_matches("*.py[co]", "pkg/mod.py"));
+        assert!(rule_matches("file[!0-9].txt", "filea.txt"));
+        assert!(!rule_matches("file[!0-9].txt", "file1.txt"));
+        assert!(rule_matches("v[[:digit:]].txt", "v7.txt"));
+        assert!(!rule_matches("v[[:digit:]].txt", "vx.txt"));
+        assert!(rule_matches("a[[:space:]_]b", "a b"));
+        assert!(rule_matches("a[[:space:]_]b", "a_b"));
+        assert!(!rule_matches("[![:alpha:]]*", "notes"));
+        assert!(rule_matches("[![:alpha:]]*", "1notes"));
+        assert!(!rule_matches("a[/]b", "a/b"));
+        assert!(!rule_matches("a[.-0]b", "a/b"));
+        assert!(rule_matches("a[.-0]b", "a.b"));
+        assert!(!rule_matches("a[!x]b", "a/b"));
+        assert!(!rule_matches("*.rs", "src/nested/lib.txt"));
+        assert!(!rule_matches("a*b", "a/b"));
+        assert!(rule_matches("\\#notes", "#notes"));
+        assert!(rule_matches("trailing\\ ", "trailing "));
+    }
+
+    #[test]
+    fn slash_anchors_pattern_to_its_directory() {
+        assert!(rule_matches("/build", "build"));
+        assert!(!rule_matches("/build", "src/build"));
+        assert!(rule_matches("doc/frotz", "doc/frotz"));
+        assert!(!rule_matches("doc/frotz", "a/doc/frotz"));
+        assert!(rule_matches("frotz", "a/doc/frotz"));
+    }
+
+    #[test]
+    fn nested_gitignore_is_relative_to_its_directory() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("pkg/gen"))?;
+        std::fs::create_dir_all(root.join("gen"))?;
+        std::fs::write(root.join("pkg/.gitignore"), "/gen/\n*.tmp\n")?;
+
+        let ignore = FlashgrepIgnore::load(root, None);
+        assert!(ignore.is_ignored(&root.join("pkg/gen/out.rs"), root));
+        assert!(!ignore.is_ignored(&root.join("gen/out.rs"), root));
+        assert!(ignore.is_ignored(&root.join("pkg/deep/x.tmp"), root));
+        assert!(!ignore.is_ignored(&root.join("x.tmp"), root));
+        Ok(())
+    }
+
+    #[test]
+    fn nested_gitignores_are_read_only_when_their_directory_is_reached() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("a/b/c"))?;
+        std::fs::create_dir_all(root.join("other"))?;
+        std::fs::write(root.join("a/b/c/.gitignore"), "*.log\n")?;
+
+        let ignore = FlashgrepIgnore::load(root, None);
+        assert!(!ignore.is_ignored(&root.join("other/x.log"), root));
+        assert!(!ignore.gitignores.read().unwrap().contains_key("a/b/c"));
+
+        assert!(ignore.is_ignored(&root.join("a/b/c/x.log"), root));
+        assert!(ignore.gitignores.read().unwrap()["a/b/c"].is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn negation_precedence_follows_git() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("logs"))?;
+        std::fs::create_dir_all(root.join("sub"))?;
+        std::fs::write(
+            root.join(".gitignore"),
+            "*.log\n!keep.log\nlogs/\n!logs/important.log\n",
+        )?;
+        std::fs::write(root.join("sub/.gitignore"), "!debug.log\n")?;
+
+        let ignore = FlashgrepIgnore::load(root, None);
+        assert!(ignore.is_ignored(&root.join("app.log"), root));
+        assert!(!ignore.is_ignored(&root.join("keep.log"), root));
+        // A deeper .gitignore overrides its parent
+        assert!(!ignore.is_ignored(&root.join("sub/debug.log"), root));
+        assert!(ignore.is_ignored(&root.join("sub/other.log"), root));
+        // Files cannot be re-included once their directory is excluded
+        assert!(ignore.is_ignored(&root.join("logs/important.log"), root));
+        Ok(())
+    }
+
+    #[test]
+    fn info_exclude_and_global_excludes_have_lowest_precedence() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        std::fs::create_dir_all(root.join(".git/info"))?;
+        std::fs::write(root.join(".git/info/exclude"), "local-only.rs\n")?;
+        let global = temp.path().join("global-ignore");
+        std::fs::write(&global, "*.bak\nshared.rs\n")?;
+        std::fs::write(root.join(".gitignore"), "!shared.rs\n")?;
+
+        let ignore = FlashgrepIgnore::load(root, Some(&global));
+        assert!(ignore.is_ignored(&root.join("local-only.rs"), root));
+        assert!(ignore.is_ignored(&root.join("src/old.bak"), root));
+        assert!(!ignore.is_ignored(&root.join("shared.rs"), root));
+        Ok(())
+    }
+
+    #[test]
+    fn flashgrepignore_overrides_gitignore() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        std::fs::write(root.join(".gitignore"), "generated.rs\n")?;
+        std::fs::write(root.join(".flashgrepignore"), "!generated.rs\nnotes.md\n")?;
+
+        let ignore = FlashgrepIgnore::load(root, None);
+        assert!(!ignore.is_ignored(&root.join("generated.rs"), root));
+        assert!(ignore.is_ignored(&root.join("notes.md"), root));
+        Ok(())
+    }
+
+    #[test]
+    fn core_excludes_file_is_read_from_git_config() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("config");
+        std::fs::write(
+            &config,
+            "[user]\n\tname = someone\n[core]\n\texcludesFile = \"/etc/gitignore\"\n",
+        )?;
+        assert_eq!(
+            read_core_excludes_file(&config),
+            Some("/etc/gitignore".to_string())
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,13 @@
 use crate::config::Config;
-use crate::index::scanner::{
-    is_binary_file, is_oversized_file, should_ignore_directory, should_index_file, FlashgrepIgnore,
-};
-use crate::index::state::{FileMetadata, ThreadSafeIndexState};
+use crate::index::scanner::{should_ignore_directory, should_index_file, FlashgrepIgnore};
+use crate::index::state::{content_hash, FileMetadata, ThreadSafeIndexState};
 use crate::FlashgrepResult;
-use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
-/// Maximum bytes to read for content hash (for performance)
-const MAX_HASH_BYTES: usize = 8 * 1024; // 8KB
-
 /// Initial scanner for building the file index on watcher startup
 pub struct InitialScanner {
     root: PathBuf,
@@ -20,6 +15,8 @@ pub struct InitialScanner {
     ignore_patterns: FlashgrepIgnore,
     progress_interval: usize,
     index_state: ThreadSafeIndexState,
+    /// Log start, progress and summary at info level rather than debug
+    verbose: bool,
 }
 
 /// Represents a file that needs to be processed
@@ -46,6 +43,28 @@ pub struct ScanMetrics {
     pub end_time: i64,
 }
 
+/// A change made while the watcher was not running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntheticEvent {
+    FileCreated(PathBuf),
+    FileModified(PathBuf),
+    FileDeleted(PathBuf),
+    /// A deleted file reappeared with the same content at a new path: `(from, to)`
+    FileRenamed(PathBuf, PathBuf),
+}
+
+impl SyntheticEvent {
+    /// Absolute path of the changed file; the new path for a rename
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::FileCreated(path)
+            | Self::FileModified(path)
+            | Self::FileDeleted(path)
+            | Self::FileRenamed(_, path) => path,
+        }
+    }
+}
+
 /// Result of the initial scan
 #[derive(Debug)]
 pub struct ScanResult {
@@ -53,6 +72,10 @@ pub struct ScanResult {
     pub files_added: usize,
     pub files_modified: usize,
     pub files_deleted: usize,
+    /// Deleted files matched by content to an added file
+    pub files_renamed: usize,
+    /// Added, modified, deleted and renamed files, to be replayed into the index
+    pub events: Vec<SyntheticEvent>,
     pub errors: Vec<String>,
     pub metrics: Option<ScanMetrics>,
 }
@@ -72,6 +95,7 @@ impl InitialScanner {
             ignore_patterns,
             progress_interval,
             index_state,
+            verbose: true,
         }
     }
 
@@ -81,9 +105,19 @@ impl InitialScanner {
         self
     }
 
+    /// Log at debug level only, for scans repeated by the polling watcher
+    pub fn quiet(mut self) -> Self {
+        self.verbose = false;
+        self
+    }
+
     /// Perform the initial scan asynchronously
     pub async fn scan(&self) -> FlashgrepResult<ScanResult> {
-        info!("Starting initial scan of {}", self.root.display());
+        if self.verbose {
+            info!("Starting initial scan of {}", self.root.display());
+        } else {
+            debug!("Starting scan of {}", self.root.display());
+        }
 
         let start_time = std::time::Instant::now();
         let start_timestamp = chrono::Utc::now().timestamp();
@@ -94,6 +128,8 @@ impl InitialScanner {
             files_added: 0,
             files_modified: 0,
             files_deleted: 0,
+            files_renamed: 0,
+            events: Vec::new(),
             errors: Vec::new(),
             metrics: None,
         };
@@ -103,6 +139,8 @@ impl InitialScanner {
         let previous_paths_set: std::collections::HashSet<_> =
             previous_paths.iter().cloned().collect();
         let mut current_paths = std::collections::HashSet::new();
+        // Added files by content hash, with their event index, to pair with deletions
+        let mut added: HashMap<String, Vec<(usize, FileMetadata)>> = HashMap::new();
 
         // Scan all files
         let walker = WalkDir::new(&self.root)
@@ -143,20 +181,35 @@ impl InitialScanner {
                                 false
                             };
 
+                            if is_new {
+                                added
+                                    .entry(metadata.content_hash.clone())
+                                    .or_default()
+                                    .push((result.events.len(), metadata.clone()));
+                            }
+
                             // Update index state
                             self.index_state.update_file(rel_path.clone(), metadata)?;
 
                             result.files_scanned += 1;
                             if is_new {
                                 result.files_added += 1;
+                                result
+                                    .events
+                                    .push(SyntheticEvent::FileCreated(path.to_path_buf()));
                                 debug!("New file detected: {}", rel_path.display());
                             } else if is_modified {
                                 result.files_modified += 1;
+                                result
+                                    .events
+                                    .push(SyntheticEvent::FileModified(path.to_path_buf()));
                                 debug!("Modified file detected: {}", rel_path.display());
                             }
 
                             // Log progress
-                            if result.files_scanned.is_multiple_of(self.progress_interval) {
+                            if self.verbose
+                                && result.files_scanned.is_multiple_of(self.progress_interval)
+                            {
                                 info!(
                                     "Initial indexing progress: {} files scanned",
                                     result.files_scanned
@@ -178,11 +231,28 @@ impl InitialScanner {
             }
         }
 
-        // Detect deleted files
+        // Detect deleted files, and moves where an added file has the same content
         for path in &previous_paths {
             if !current_paths.contains(path) {
-                result.files_deleted += 1;
+                let previous = self.index_state.get_file(path)?;
                 self.index_state.remove_file(path)?;
+                let from = self.root.join(path);
+                if let Some(index) =
+                    previous.and_then(|previous| take_moved_file(&mut added, &previous))
+                {
+                    let to = result.events[index].path().to_path_buf();
+                    debug!(
+                        "Moved file detected: {} -> {}",
+                        from.display(),
+                        to.display()
+                    );
+                    result.events[index] = SyntheticEvent::FileRenamed(from, to);
+                    result.files_added -= 1;
+                    result.files_renamed += 1;
+                    continue;
+                }
+                result.files_deleted += 1;
+                result.events.push(SyntheticEvent::FileDeleted(from));
                 debug!("Deleted file detected: {}", path.display());
             }
         }
@@ -210,11 +280,23 @@ impl InitialScanner {
             end_time: chrono::Utc::now().timestamp(),
         });
 
-        info!(
-            "Initial scan complete: {} scanned, {} added, {} modified, {} deleted (took {:?}, {:.1} files/sec)",
-            result.files_scanned, result.files_added, result.files_modified, result.files_deleted,
-            duration, files_per_second
-        );
+        if self.verbose {
+            info!(
+                "Initial scan complete: {} scanned, {} added, {} modified, {} deleted, {} moved (took {:?}, {:.1} files/sec)",
+                result.files_scanned, result.files_added, result.files_modified, result.files_deleted,
+                result.files_renamed, duration, files_per_second
+            );
+        } else {
+            debug!(
+                "Scan complete: {} scanned, {} added, {} modified, {} deleted, {} moved (took {:?})",
+                result.files_scanned,
+                result.files_added,
+                result.files_modified,
+                result.files_deleted,
+                result.files_renamed,
+                duration
+            );
+        }
 
         Ok(result)
     }
@@ -248,26 +330,8 @@ impl InitialScanner {
             }
         }
 
-        // Check file extension
-        if !should_index_file(path, &self.config) {
-            return false;
-        }
-
-        // Check file size
-        match is_oversized_file(path, self.config.max_file_size) {
-            Ok(true) => return false,
-            Ok(false) => {}
-            Err(_) => return false,
-        }
-
-        // Check if binary
-        match is_binary_file(path) {
-            Ok(true) => return false,
-            Ok(false) => {}
-            Err(_) => return false,
-        }
-
-        true
+        // Size and content are classified by the indexer, which records skipped files
+        should_index_file(path, &self.config)
     }
 
     /// Extract metadata from a file
@@ -300,15 +364,7 @@ impl InitialScanner {
         let content = tokio::fs::read(path)
             .await
             .map_err(crate::FlashgrepError::Io)?;
-
-        let hash_input = if content.len() > MAX_HASH_BYTES {
-            &content[..MAX_HASH_BYTES]
-        } else {
-            &content
-        };
-
-        let hash = Sha256::digest(hash_input);
-        Ok(hex::encode(hash))
+        Ok(content_hash(&content))
     }
 
     /// Get the relative path from the repository root
@@ -317,6 +373,18 @@ impl InitialScanner {
     }
 }
 
+/// Take an added file with the same content as a deleted one, returning its event index
+fn take_moved_file(
+    added: &mut HashMap<String, Vec<(usize, FileMetadata)>>,
+    deleted: &FileMetadata,
+) -> Option<usize> {
+    let candidates = added.get_mut(&deleted.content_hash)?;
+    let position = candidates
+        .iter()
+        .position(|(_, metadata)| deleted.is_same_content(metadata))?;
+    Some(candidates.swap_remove(position).0)
+}
+
 /// Run initial scan in background and return a channel for results
 pub async fn run_initial_scan(
     root: PathBuf,
@@ -456,6 +524,54 @@ mod tests {
         let scanner = InitialScanner::new(root.clone(), config, ignore_patterns, index_state);
         let result = scanner.scan().await?;
         assert_eq!(result.files_modified, 1);
+        assert_eq!(
+            result.events,
+            vec![SyntheticEvent::FileModified(root.join("test.rs"))]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_pairs_moved_files_by_content() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().to_path_buf();
+        std::fs::write(root.join("old.rs"), "fn moved() {}")?;
+        std::fs::write(root.join("gone.rs"), "fn gone() {}")?;
+
+        let config = Config::default();
+        let ignore_patterns = FlashgrepIgnore::from_root(&root);
+        let index_state = ThreadSafeIndexState::new();
+        InitialScanner::new(
+            root.clone(),
+            config.clone(),
+            ignore_patterns.clone(),
+            index_state.clone(),
+        )
+        .scan()
+        .await?;
+
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::rename(root.join("old.rs"), root.join("src/new.rs"))?;
+        std::fs::remove_file(root.join("gone.rs"))?;
+        std::fs::write(root.join("fresh.rs"), "fn fresh() {}")?;
+
+        let result = InitialScanner::new(root.clone(), config, ignore_patterns, index_state)
+            .scan()
+            .await?;
+        assert_eq!(result.files_added, 1);
+        assert_eq!(result.files_deleted, 1);
+        assert_eq!(result.files_renamed, 1);
+        assert!(result.events.contains(&SyntheticEvent::FileRenamed(
+            root.join("old.rs"),
+            root.join("src/new.rs")
+        )));
+        assert!(result
+            .events
+            .contains(&SyntheticEvent::FileDeleted(root.join("gone.rs"))));
+        assert!(result
+            .events
+            .contains(&SyntheticEvent::FileCreated(root.join("fresh.rs"))));
 
         Ok(())
     }
@@ -1,12 +1,21 @@
+pub mod doctor;
 pub mod engine;
+pub mod history;
+pub mod ignore;
 pub mod initial_scanner;
 pub mod scanner;
+pub mod schema;
 pub mod state;
+pub mod stats;
+pub mod status;
 
+pub use doctor::DoctorReport;
 pub use engine::Indexer;
-pub use initial_scanner::{InitialScanner, ScanResult};
+pub use initial_scanner::{InitialScanner, ScanResult, SyntheticEvent};
 pub use scanner::{
     is_binary_file, is_oversized_file, should_ignore_directory, should_index_file, FileScanner,
     FlashgrepIgnore,
 };
 pub use state::{FileMetadata, IndexState, ThreadSafeIndexState};
+pub use stats::DetailedStats;
+pub use status::IndexStatus;
@@ -1,8 +1,12 @@
+use crate::config::languages::sniffs_content;
 use crate::config::Config;
+use crate::encoding::{self, TextEncoding};
 use crate::FlashgrepResult;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+pub use crate::index::ignore::FlashgrepIgnore;
+
 /// Build a normalized repository-relative path for ignore checks.
 /// Uses '/' separators across platforms.
 pub fn normalize_repo_relative_path(path: &Path, root: &Path) -> String {
@@ -32,35 +36,111 @@ pub const DEFAULT_EXTENSIONS: &[&str; 11] = &[
     "go", "rs", "js", "ts", "py", "sol", "json", "md", "yaml", "yml", "toml",
 ];
 
+/// Average line length above which a file is treated as minified
+const MINIFIED_AVERAGE_LINE_LENGTH: usize = 300;
+
+/// Why a file was left out of the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Not text in any detectable encoding
+    Binary,
+    /// Larger than `skip_file_size`
+    TooLarge,
+    /// Malformed in its detected text encoding
+    InvalidEncoding,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::Binary => "binary",
+            SkipReason::TooLarge => "too_large",
+            SkipReason::InvalidEncoding => "invalid_encoding",
+        }
+    }
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How a file is indexed, decided from its size and the first few kilobytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileTier {
+    /// Read whole and chunked by language (minified content is line-split)
+    Normal,
+    /// Larger than `max_file_size`: streamed line by line in fixed-size chunks
+    Large,
+    /// Left out of the index and recorded with the reason
+    Skipped(SkipReason),
+}
+
+/// Classify a file into an indexing tier without reading it whole
+pub fn classify_file(path: &Path, config: &Config) -> FlashgrepResult<FileTier> {
+    let size = std::fs::metadata(path)?.len();
+    if size > config.skip_file_size {
+        return Ok(FileTier::Skipped(SkipReason::TooLarge));
+    }
+
+    if encoding::detect_file(path)?.is_none() {
+        return Ok(FileTier::Skipped(SkipReason::Binary));
+    }
+
+    if size > config.max_file_size {
+        Ok(FileTier::Large)
+    } else {
+        Ok(FileTier::Normal)
+    }
+}
+
+/// Line-length heuristic for minified or generated single-line content
+pub fn is_minified(content: &str, config: &Config) -> bool {
+    let mut lines = 0usize;
+    let mut longest = 0usize;
+    for line in content.lines() {
+        lines += 1;
+        longest = longest.max(line.len());
+    }
+    if lines == 0 {
+        return false;
+    }
+    longest > config.minified_line_length
+        || (lines > 1 && content.len() / lines > MINIFIED_AVERAGE_LINE_LENGTH)
+}
+
 /// Check if a directory should be ignored
 pub fn should_ignore_directory(dir_name: &str, config: &Config) -> bool {
     config.ignored_dirs.contains(&dir_name.to_string()) || DEFAULT_IGNORED_DIRS.contains(&dir_name)
 }
 
-/// Check if a file should be indexed based on extension
+/// Check if a file should be indexed based on its extension or detected language.
+/// Files the extension list does not cover are indexed when the language map
+/// recognises them by name, or, for extension-less files, by shebang or modeline.
 pub fn should_index_file(path: &Path, config: &Config) -> bool {
-    if let Some(ext) = path.extension() {
-        let ext_str = ext.to_string_lossy().to_lowercase();
-        config.extensions.contains(&ext_str)
-    } else {
-        false
-    }
+    has_indexed_extension(path, config) || config.languages.detect_file(path).is_some()
 }
 
-/// Check if a file appears to be binary
-pub fn is_binary_file(path: &Path) -> FlashgrepResult<bool> {
-    let content = std::fs::read(path)?;
+/// Like [`should_index_file`], for content that is already in memory rather than on disk
+pub fn should_index_content(path: &Path, content: &str, config: &Config) -> bool {
+    has_indexed_extension(path, config)
+        || config.languages.detect_path(path).is_some()
+        || (sniffs_content(path) && config.languages.detect(path, content).is_some())
+}
 
-    // Check for null bytes (common in binary files)
-    if content.contains(&0) {
-        return Ok(true);
-    }
+fn has_indexed_extension(path: &Path, config: &Config) -> bool {
+    path.extension().is_some_and(|ext| {
+        config
+            .extensions
+            .contains(&ext.to_string_lossy().to_lowercase())
+    })
+}
 
-    // Check if content is valid UTF-8
-    match String::from_utf8(content) {
-        Ok(_) => Ok(false),
-        Err(_) => Ok(true),
-    }
+/// Check if a file appears to be binary, i.e. not text in any detectable encoding
+pub fn is_binary_file(path: &Path) -> FlashgrepResult<bool> {
+    let content = std::fs::read(path)?;
+    Ok(TextEncoding::detect(&content).is_none())
 }
 
 /// Check if a file exceeds the size limit
@@ -87,10 +167,28 @@ impl FileScanner {
         }
     }
 
+    /// Create a file scanner that applies already loaded ignore patterns
+    pub fn with_ignore_patterns(
+        root: PathBuf,
+        config: Config,
+        ignore_patterns: FlashgrepIgnore,
+    ) -> Self {
+        Self {
+            root,
+            config,
+            ignore_patterns,
+        }
+    }
+
     /// Scan the repository and return indexable files
     pub fn scan(&self) -> impl Iterator<Item = PathBuf> + '_ {
         WalkDir::new(&self.root)
             .into_iter()
+            .filter_entry(move |e| {
+                e.depth() == 0
+                    || !e.file_type().is_dir()
+                    || !self.ignore_patterns.is_ignored(e.path(), &self.root)
+            })
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
             .filter(move |e| self.should_include(e.path()))
@@ -98,7 +196,14 @@ impl FileScanner {
     }
 
     /// Check if a path should be included in the index
-    fn should_include(&self, path: &Path) -> bool {
+    pub fn should_include(&self, path: &Path) -> bool {
+        // Size and content are classified by the indexer, which records skipped files
+        self.allows_path(path) && should_index_file(path, &self.config)
+    }
+
+    /// Check a path against `.flashgrep`, ignore patterns and ignored directories only.
+    /// The path does not need to exist, so this also applies to files in git revisions.
+    pub fn allows_path(&self, path: &Path) -> bool {
         // Check if it's in the flashgrep directory
         if path.components().any(|c| {
             if let std::path::Component::Normal(name) = c {
@@ -126,194 +231,10 @@ impl FileScanner {
             }
         }
 
-        // Check if we should index this file type
-        if !should_index_file(path, &self.config) {
-            return false;
-        }
-
-        // Check file size
-        match is_oversized_file(path, self.config.max_file_size) {
-            Ok(true) => return false,
-            Ok(false) => {}
-            Err(_) => return false,
-        }
-
-        // Check if binary
-        match is_binary_file(path) {
-            Ok(true) => return false,
-            Ok(false) => {}
-            Err(_) => return false,
-        }
-
         true
     }
 }
 
-/// Represents ignore patterns loaded from .gitignore and .flashgrepignore
-#[derive(Debug, Default, Clone)]
-pub struct FlashgrepIgnore {
-    patterns: Vec<IgnorePattern>,
-}
-
-#[derive(Debug, Clone)]
-struct IgnorePattern {
-    pattern: String,
-    is_negation: bool,
-    is_directory_only: bool,
-}
-
-impl FlashgrepIgnore {
-    /// Load ignore patterns from root .gitignore and .flashgrepignore files.
-    ///
-    /// Patterns from `.flashgrepignore` are applied after `.gitignore`, so
-    /// repository-local flashgrep overrides can refine behavior when needed.
-    pub fn from_root(root: &Path) -> Self {
-        let mut patterns = Vec::new();
-
-        for ignore_name in [".gitignore", ".flashgrepignore"] {
-            let ignore_file = root.join(ignore_name);
-            if ignore_file.exists() {
-                if let Ok(content) = std::fs::read_to_string(&ignore_file) {
-                    patterns.extend(Self::parse_patterns(&content));
-                }
-            }
-        }
-
-        if patterns.is_empty() {
-            Self::default()
-        } else {
-            Self { patterns }
-        }
-    }
-
-    /// Load ignore patterns from a file
-    pub fn from_file(path: &Path) -> FlashgrepResult<Self> {
-        let content = std::fs::read_to_string(path)?;
-        Ok(Self {
-            patterns: Self::parse_patterns(&content),
-        })
-    }
-
-    fn parse_patterns(content: &str) -> Vec<IgnorePattern> {
-        let mut patterns = Vec::new();
-
-        for line in content.lines() {
-            let line = line.trim();
-
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            let is_negation = line.starts_with('!');
-            let is_directory_only = line.ends_with('/');
-
-            let pattern = if is_negation { &line[1..] } else { line };
-
-            let pattern = if is_directory_only {
-                &pattern[..pattern.len() - 1]
-            } else {
-                pattern
-            };
-
-            patterns.push(IgnorePattern {
-                pattern: pattern.to_string(),
-                is_negation,
-                is_directory_only,
-            });
-        }
-
-        patterns
-    }
-
-    /// Check if a path is ignored
-    pub fn is_ignored(&self, path: &Path, root: &Path) -> bool {
-        let relative_str = normalize_repo_relative_path(path, root);
-
-        let mut ignored = false;
-
-        for pattern in &self.patterns {
-            let matches = if pattern.is_directory_only {
-                Self::directory_match(&relative_str, &pattern.pattern)
-            } else {
-                Self::match_pattern(&relative_str, &pattern.pattern)
-            };
-
-            if matches {
-                ignored = !pattern.is_negation;
-            }
-        }
-
-        ignored
-    }
-
-    /// Match directory-only patterns against a normalized path.
-    fn directory_match(path: &str, pattern: &str) -> bool {
-        path == pattern || path.starts_with(&format!("{}/", pattern))
-    }
-
-    /// Match a path against a gitignore-style pattern
-    fn match_pattern(path: &str, pattern: &str) -> bool {
-        let path_parts: Vec<&str> = path.split('/').collect();
-        let _pattern_parts: Vec<&str> = pattern.split('/').collect();
-
-        // Simple glob matching
-        if pattern.contains('*') || pattern.contains('?') {
-            return Self::glob_match(path, pattern);
-        }
-
-        // Exact match or directory prefix match
-        if let Some(anchored_pattern) = pattern.strip_prefix('/') {
-            // Anchored to root
-            path == anchored_pattern || path.starts_with(&format!("{}/", anchored_pattern))
-        } else {
-            // Match at any level
-            path == pattern
-                || path_parts.contains(&pattern)
-                || path.starts_with(&format!("{}/", pattern))
-                || path.ends_with(&format!("/{}", pattern))
-        }
-    }
-
-    /// Simple glob pattern matching
-    fn glob_match(path: &str, pattern: &str) -> bool {
-        let mut pattern_chars = pattern.chars().peekable();
-        let mut path_chars = path.chars().peekable();
-
-        while let Some(p) = pattern_chars.next() {
-            match p {
-                '*' => {
-                    // Match zero or more characters
-                    if pattern_chars.peek().is_none() {
-                        return true; // * at end matches everything
-                    }
-                    let next_p = pattern_chars.peek().copied().unwrap();
-                    for c in path_chars.by_ref() {
-                        if c == next_p {
-                            break;
-                        }
-                    }
-                }
-                '?' => {
-                    // Match exactly one character
-                    if path_chars.next().is_none() {
-                        return false;
-                    }
-                }
-                c => {
-                    // Match exact character
-                    match path_chars.next() {
-                        Some(pc) if pc == c => {}
-                        _ => return false,
-                    }
-                }
-            }
-        }
-
-        path_chars.next().is_none()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -335,6 +256,23 @@ mod tests {
         assert!(!should_index_file(Path::new("test.exe"), &config));
     }
 
+    #[test]
+    fn test_should_index_detected_languages() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        std::fs::write(root.join("deploy"), "#!/usr/bin/env bash\necho deploy\n")?;
+        std::fs::write(root.join("notes"), "plain text\n")?;
+
+        let config = Config::default();
+        assert!(should_index_file(&root.join("Dockerfile"), &config));
+        assert!(should_index_file(&root.join("Main.java"), &config));
+        assert!(should_index_file(&root.join("main.tf"), &config));
+        assert!(should_index_file(&root.join("deploy"), &config));
+        assert!(!should_index_file(&root.join("notes"), &config));
+
+        Ok(())
+    }
+
     #[test]
     fn test_is_oversized_file() -> FlashgrepResult<()> {
         let temp_dir = TempDir::new()?;
@@ -347,6 +285,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_classify_file_tiers() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        std::fs::write(root.join("small.rs"), "fn main() {}\n")?;
+        std::fs::write(
+            root.join("large.sql"),
+            "INSERT INTO t VALUES (1);\n".repeat(20),
+        )?;
+        std::fs::write(root.join("blob.json"), b"{\"a\": \0}")?;
+        std::fs::write(root.join("latin1.md"), b"caf\xe9\n")?;
+        std::fs::write(root.join("utf16.txt"), b"\xFF\xFEh\0i\0\n\0")?;
+
+        let config = Config {
+            max_file_size: 100,
+            skip_file_size: 1000,
+            ..Config::default()
+        };
+        assert_eq!(
+            classify_file(&root.join("small.rs"), &config)?,
+            FileTier::Normal
+        );
+        assert_eq!(
+            classify_file(&root.join("large.sql"), &config)?,
+            FileTier::Large
+        );
+        assert_eq!(
+            classify_file(&root.join("blob.json"), &config)?,
+            FileTier::Skipped(SkipReason::Binary)
+        );
+        assert_eq!(
+            classify_file(&root.join("latin1.md"), &config)?,
+            FileTier::Normal
+        );
+        assert_eq!(
+            classify_file(&root.join("utf16.txt"), &config)?,
+            FileTier::Normal
+        );
+
+        let tight = Config {
+            skip_file_size: 10,
+            ..Config::default()
+        };
+        assert_eq!(
+            classify_file(&root.join("large.sql"), &tight)?,
+            FileTier::Skipped(SkipReason::TooLarge)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_minified() {
+        let config = Config::default();
+        assert!(!is_minified("fn main() {\n    run();\n}\n", &config));
+        assert!(is_minified(
+            &format!("var a={};", "1,".repeat(800)),
+            &config
+        ));
+        let wide = format!("{}\n", "x".repeat(400)).repeat(3);
+        assert!(is_minified(&wide, &config));
+    }
+
     #[test]
     fn test_flashgrep_ignore() -> FlashgrepResult<()> {
         let temp_dir = TempDir::new()?;
@@ -430,6 +430,25 @@ temp*
         Ok(())
     }
 
+    #[test]
+    fn test_nested_gitignore_prunes_scan() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().to_path_buf();
+
+        std::fs::create_dir_all(root.join("web/generated"))?;
+        std::fs::write(root.join("web/app.ts"), "export const app = 1;")?;
+        std::fs::write(root.join("web/generated/api.ts"), "export const api = 1;")?;
+        std::fs::write(root.join("web/.gitignore"), "generated/\n")?;
+
+        let scanner = FileScanner::new(root.clone(), Config::default());
+        let files: Vec<_> = scanner.scan().collect();
+
+        assert!(files.iter().any(|p| p.ends_with("web/app.ts")));
+        assert!(!files.iter().any(|p| p.ends_with("api.ts")));
+
+        Ok(())
+    }
+
     #[test]
     fn test_gitignore_patterns_are_respected() -> FlashgrepResult<()> {
         let temp_dir = TempDir::new()?;
@@ -0,0 +1,184 @@
+//! Tantivy schema of the text index and the version recorded next to it
+
+use crate::{FlashgrepError, FlashgrepResult};
+use std::path::Path;
+use tantivy::schema::*;
+use tantivy::Index;
+
+/// Version of the text index schema written by this build.
+///
+/// 1. Chunk documents with their stored bodies
+/// 2. `file_path_key` for exact deletes and `revision` for git revisions
+/// 3. Bodies are no longer stored; they are read from the metadata store
+pub const TEXT_SCHEMA_VERSION: u32 = 3;
+
+/// File in the text index directory holding its schema version
+const VERSION_FILE: &str = "flashgrep-schema-version";
+
+/// Fields every readable text index has, with the options they are read through
+const REQUIRED_FIELDS: [(&str, Type, bool); 5] = [
+    ("file_path", Type::Str, true),
+    ("content", Type::Str, false),
+    ("start_line", Type::U64, true),
+    ("end_line", Type::U64, true),
+    ("content_hash", Type::Str, true),
+];
+
+/// Build the schema of the current version
+pub fn text_schema() -> Schema {
+    let mut schema_builder = Schema::builder();
+
+    // File path field
+    schema_builder.add_text_field("file_path", TEXT | STORED);
+
+    // Untokenized file path used to delete a file's documents exactly
+    schema_builder.add_text_field("file_path_key", STRING);
+
+    // Content field (tokenized for search). Bodies are read back from the
+    // metadata store by content hash, so they are not stored here.
+    schema_builder.add_text_field("content", TEXT);
+
+    // Start and end line numbers
+    schema_builder.add_u64_field("start_line", STORED | FAST);
+    schema_builder.add_u64_field("end_line", STORED | FAST);
+
+    // Content hash for deduplication
+    schema_builder.add_text_field("content_hash", STRING | STORED);
+
+    // Last modified timestamp
+    schema_builder.add_u64_field("last_modified", FAST);
+
+    // Commit id for documents indexed from a git revision; absent for the working tree
+    schema_builder.add_text_field("revision", STRING | STORED);
+
+    schema_builder.build()
+}
+
+/// Open an existing text index for reading, failing with
+/// [`FlashgrepError::RebuildRequired`] if this build cannot read it.
+/// Older but readable versions are opened as they are; they are migrated
+/// when the index is next opened for writing.
+pub fn open_text_index(index_dir: &Path) -> FlashgrepResult<Index> {
+    let index = Index::open_in_dir(index_dir)?;
+    text_index_version(&index, index_dir)?;
+    Ok(index)
+}
+
+/// Schema version of an opened text index, from its version file or, for
+/// indexes written before the file existed, from its fields
+pub fn text_index_version(index: &Index, index_dir: &Path) -> FlashgrepResult<u32> {
+    let schema = index.schema();
+    for (name, value_type, stored) in REQUIRED_FIELDS {
+        let compatible = schema.get_field(name).ok().is_some_and(|field| {
+            let entry = schema.get_field_entry(field);
+            entry.field_type().value_type() == value_type && (!stored || entry.is_stored())
+        });
+        if !compatible {
+            return Err(FlashgrepError::RebuildRequired(format!(
+                "the text index at {} has no usable '{}' field",
+                index_dir.display(),
+                name
+            )));
+        }
+    }
+
+    let version = match std::fs::read_to_string(index_dir.join(VERSION_FILE)) {
+        Ok(text) => text.trim().parse::<u32>().map_err(|_| {
+            FlashgrepError::RebuildRequired(format!(
+                "the text index at {} has an unreadable schema version '{}'",
+                index_dir.display(),
+                text.trim()
+            ))
+        })?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => infer_version(&schema),
+        Err(err) => return Err(err.into()),
+    };
+    if version > TEXT_SCHEMA_VERSION {
+        return Err(FlashgrepError::RebuildRequired(format!(
+            "the text index at {} has schema version {}, newer than the {} this build supports",
+            index_dir.display(),
+            version,
+            TEXT_SCHEMA_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// Record the current schema version in the text index directory
+pub fn write_text_index_version(index_dir: &Path) -> FlashgrepResult<()> {
+    std::fs::write(
+        index_dir.join(VERSION_FILE),
+        format!("{}\n", TEXT_SCHEMA_VERSION),
+    )?;
+    Ok(())
+}
+
+/// Whether the directory holds a text index whose version has been recorded.
+/// A rebuilt index gets its version file only once all its documents are
+/// committed, so this also tells a finished rebuild from an interrupted one.
+pub fn has_text_index_version(index_dir: &Path) -> bool {
+    index_dir.join("meta.json").exists() && index_dir.join(VERSION_FILE).exists()
+}
+
+fn infer_version(schema: &Schema) -> u32 {
+    let has = |name: &str| schema.get_field(name).is_ok();
+    if !has("file_path_key") || !has("revision") {
+        return 1;
+    }
+    let content_stored = schema
+        .get_field("content")
+        .is_ok_and(|field| schema.get_field_entry(field).is_stored());
+    if content_stored {
+        2
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn versions_are_inferred_and_checked() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+
+        let mut builder = Schema::builder();
+        builder.add_text_field("file_path", TEXT | STORED);
+        builder.add_text_field("content", TEXT | STORED);
+        builder.add_u64_field("start_line", STORED | FAST);
+        builder.add_u64_field("end_line", STORED | FAST);
+        builder.add_text_field("content_hash", STRING | STORED);
+        let original = Index::create_in_dir(temp.path(), builder.build())?;
+        assert_eq!(text_index_version(&original, temp.path())?, 1);
+
+        write_text_index_version(temp.path())?;
+        assert_eq!(
+            text_index_version(&original, temp.path())?,
+            TEXT_SCHEMA_VERSION
+        );
+
+        std::fs::write(temp.path().join(VERSION_FILE), "99\n")?;
+        let err = open_text_index(temp.path()).unwrap_err();
+        assert!(matches!(err, FlashgrepError::RebuildRequired(_)));
+        assert!(err.to_string().contains("newer"));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_fields_require_a_rebuild() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let mut builder = Schema::builder();
+        builder.add_text_field("file_path", TEXT | STORED);
+        builder.add_text_field("content", TEXT);
+        builder.add_text_field("start_line", STRING | STORED);
+        let index = Index::create_in_dir(temp.path(), builder.build())?;
+
+        let err = text_index_version(&index, temp.path()).unwrap_err();
+        assert!(matches!(err, FlashgrepError::RebuildRequired(_)));
+        assert!(err.to_string().contains("'start_line'"));
+        assert!(err.to_string().contains("flashgrep clear"));
+        Ok(())
+    }
+}
@@ -1,13 +1,18 @@
 use crate::FlashgrepResult;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
 use tracing::{debug, info, warn};
 
 /// Current version of the index state format
 pub const INDEX_STATE_VERSION: u32 = 1;
 
+/// Bytes of file content covered by [`FileMetadata::content_hash`]
+pub const CONTENT_HASH_BYTES: usize = 8 * 1024;
+
 /// Represents the persisted state of the file index
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexState {
@@ -34,6 +39,40 @@ pub struct FileMetadata {
     pub content_hash: String,
 }
 
+impl FileMetadata {
+    /// Read the metadata of a file on disk
+    pub fn read(path: &Path) -> FlashgrepResult<Self> {
+        let metadata = std::fs::metadata(path)?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Ok(Self {
+            size: metadata.len(),
+            mtime,
+            content_hash: content_hash(&std::fs::read(path)?),
+        })
+    }
+
+    /// Whether `other` describes the same content, e.g. the same file after a move.
+    ///
+    /// The hash only covers the first [`CONTENT_HASH_BYTES`], so larger files must
+    /// also keep their modification time, as a rename does.
+    pub fn is_same_content(&self, other: &FileMetadata) -> bool {
+        self.size == other.size
+            && self.content_hash == other.content_hash
+            && (self.size <= CONTENT_HASH_BYTES as u64 || self.mtime == other.mtime)
+    }
+}
+
+/// SHA-256 of the first [`CONTENT_HASH_BYTES`] of `content`, hex encoded
+pub fn content_hash(content: &[u8]) -> String {
+    let hash_input = &content[..content.len().min(CONTENT_HASH_BYTES)];
+    hex::encode(Sha256::digest(hash_input))
+}
+
 impl IndexState {
     /// Create a new empty index state
     pub fn new() -> Self {
@@ -231,6 +270,15 @@ impl ThreadSafeIndexState {
         Ok(state.has_file(path))
     }
 
+    /// Get a copy of a file's metadata
+    pub fn get_file(&self, path: &Path) -> FlashgrepResult<Option<FileMetadata>> {
+        let state = self
+            .inner
+            .read()
+            .map_err(|_| crate::FlashgrepError::Index("Failed to acquire read lock".to_string()))?;
+        Ok(state.get_file(path).cloned())
+    }
+
     /// Get all file paths
     pub fn get_all_paths(&self) -> FlashgrepResult<Vec<PathBuf>> {
         let state = self
@@ -387,4 +435,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_same_content_requires_mtime_beyond_hashed_prefix() {
+        let small = FileMetadata {
+            size: 10,
+            mtime: 1,
+            content_hash: content_hash(b"0123456789"),
+        };
+        let copied = FileMetadata {
+            mtime: 2,
+            ..small.clone()
+        };
+        assert!(small.is_same_content(&copied));
+
+        let large = FileMetadata {
+            size: CONTENT_HASH_BYTES as u64 + 1,
+            ..small.clone()
+        };
+        assert!(large.is_same_content(&large.clone()));
+        assert!(!large.is_same_content(&FileMetadata {
+            mtime: 2,
+            ..large.clone()
+        }));
+        assert!(!small.is_same_content(&FileMetadata {
+            content_hash: content_hash(b"9876543210"),
+            ..small.clone()
+        }));
+    }
 }
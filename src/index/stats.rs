@@ -0,0 +1,233 @@
+use crate::config::paths::FlashgrepPaths;
+use crate::db::models::{IndexStats, IndexedRevision, SkippedFile};
+use crate::db::Database;
+use crate::FlashgrepResult;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+/// Upper bounds (inclusive, in lines) of the chunk-size histogram buckets.
+/// Chunks larger than the last bound fall into an open-ended bucket.
+const CHUNK_SIZE_BUCKETS: &[usize] = &[10, 50, 100, 200, 300];
+
+/// Index statistics broken down by language, directory, symbol type and chunk size
+#[derive(Debug, Clone, Serialize)]
+pub struct DetailedStats {
+    #[serde(flatten)]
+    pub totals: IndexStats,
+    pub by_language: BTreeMap<String, BreakdownEntry>,
+    pub by_directory: BTreeMap<String, BreakdownEntry>,
+    pub by_symbol_type: BTreeMap<String, usize>,
+    pub chunk_size_histogram: Vec<HistogramBucket>,
+    pub vectors: Vec<VectorCoverage>,
+    pub tantivy: TantivyStats,
+    /// Number of files left out of the index, by skip reason
+    pub skipped_by_reason: BTreeMap<String, usize>,
+    pub skipped_files: Vec<SkippedFile>,
+    /// Git revisions indexed alongside the working tree
+    pub revisions: Vec<IndexedRevision>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BreakdownEntry {
+    pub files: usize,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistogramBucket {
+    /// Smallest chunk size (lines) in the bucket
+    pub min_lines: usize,
+    /// Largest chunk size (lines) in the bucket, `None` for the open-ended bucket
+    pub max_lines: Option<usize>,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorCoverage {
+    pub model_id: String,
+    pub vectors: usize,
+    /// Fraction of chunks with a vector for this model (0.0 - 1.0)
+    pub coverage: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TantivyStats {
+    pub available: bool,
+    pub segments: usize,
+    pub documents: u64,
+}
+
+impl DetailedStats {
+    /// Collect detailed statistics for the index at `paths`
+    pub fn collect(paths: &FlashgrepPaths) -> FlashgrepResult<Self> {
+        let db = Database::open_read_only(&paths.metadata_db())?;
+        let totals = db.get_stats()?;
+        let repo_root = paths.repo_root();
+
+        let by_language = db
+            .language_breakdown()?
+            .into_iter()
+            .map(|(language, files, chunks)| (language, BreakdownEntry { files, chunks }))
+            .collect();
+
+        let mut by_directory: BTreeMap<String, BreakdownEntry> = BTreeMap::new();
+        for (file_path, chunks) in db.file_chunk_counts()? {
+            let entry = by_directory
+                .entry(top_level_directory(&file_path, repo_root))
+                .or_default();
+            entry.files += 1;
+            entry.chunks += chunks;
+        }
+
+        let by_symbol_type = db.symbol_type_counts()?.into_iter().collect();
+        let chunk_size_histogram = chunk_size_histogram(&db.chunk_line_counts()?);
+
+        let vectors = db
+            .vector_counts_by_model()?
+            .into_iter()
+            .map(|(model_id, vectors)| VectorCoverage {
+                coverage: if totals.total_chunks == 0 {
+                    0.0
+                } else {
+                    (vectors as f64 / totals.total_chunks as f64).min(1.0)
+                },
+                model_id,
+                vectors,
+            })
+            .collect();
+
+        let skipped_files = db.get_skipped_files()?;
+        let mut skipped_by_reason: BTreeMap<String, usize> = BTreeMap::new();
+        for skipped in &skipped_files {
+            *skipped_by_reason.entry(skipped.reason.clone()).or_default() += 1;
+        }
+
+        Ok(Self {
+            totals,
+            by_language,
+            by_directory,
+            by_symbol_type,
+            chunk_size_histogram,
+            vectors,
+            tantivy: tantivy_stats(&paths.text_index_dir()),
+            skipped_by_reason,
+            skipped_files,
+            revisions: db.get_revisions()?,
+        })
+    }
+}
+
+/// First path component below the repository root, or "." for files at the root
+fn top_level_directory(file_path: &Path, repo_root: &Path) -> String {
+    let rel = file_path.strip_prefix(repo_root).unwrap_or(file_path);
+    let mut components = rel
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)));
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_string_lossy().to_string(),
+        _ => ".".to_string(),
+    }
+}
+
+fn chunk_size_histogram(line_counts: &[usize]) -> Vec<HistogramBucket> {
+    let mut buckets = Vec::with_capacity(CHUNK_SIZE_BUCKETS.len() + 1);
+    let mut min_lines = 1;
+    for &max in CHUNK_SIZE_BUCKETS {
+        buckets.push(HistogramBucket {
+            min_lines,
+            max_lines: Some(max),
+            chunks: 0,
+        });
+        min_lines = max + 1;
+    }
+    buckets.push(HistogramBucket {
+        min_lines,
+        max_lines: None,
+        chunks: 0,
+    });
+
+    for &lines in line_counts {
+        let idx = CHUNK_SIZE_BUCKETS
+            .iter()
+            .position(|&max| lines <= max)
+            .unwrap_or(CHUNK_SIZE_BUCKETS.len());
+        buckets[idx].chunks += 1;
+    }
+    buckets
+}
+
+fn tantivy_stats(text_index_dir: &Path) -> TantivyStats {
+    let index = match tantivy::Index::open_in_dir(text_index_dir) {
+        Ok(index) => index,
+        Err(_) => return TantivyStats::default(),
+    };
+    let segments = index.searchable_segment_metas().unwrap_or_default();
+    TantivyStats {
+        available: true,
+        segments: segments.len(),
+        documents: segments.iter().map(|s| s.num_docs() as u64).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::engine::Indexer;
+    use tempfile::TempDir;
+
+    #[test]
+    fn histogram_buckets_are_inclusive_and_open_ended() {
+        let histogram = chunk_size_histogram(&[1, 10, 11, 300, 301, 5000]);
+        let counts = histogram.iter().map(|b| b.chunks).collect::<Vec<_>>();
+        assert_eq!(counts, vec![2, 1, 0, 0, 1, 2]);
+        assert_eq!(histogram.last().unwrap().max_lines, None);
+        assert_eq!(histogram[1].min_lines, 11);
+    }
+
+    #[test]
+    fn detailed_stats_group_by_language_and_directory() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::write(root.join("src/lib.rs"), "pub fn lib() {}\n")?;
+        std::fs::write(root.join("main.py"), "def main(): pass\n")?;
+
+        let mut indexer = Indexer::new(root.clone())?;
+        indexer.index_repository(&root)?;
+        drop(indexer);
+
+        let stats = DetailedStats::collect(&FlashgrepPaths::new(&root))?;
+        assert_eq!(stats.totals.total_files, 2);
+        assert_eq!(stats.by_language["rust"].files, 1);
+        assert_eq!(stats.by_language["python"].files, 1);
+        assert_eq!(stats.by_directory["src"].files, 1);
+        assert_eq!(stats.by_directory["."].files, 1);
+        assert_eq!(stats.by_symbol_type["function"], 2);
+        assert!(stats.tantivy.available);
+        assert_eq!(stats.tantivy.documents, 2);
+
+        let json = serde_json::to_value(&stats)?;
+        assert_eq!(json["total_files"], serde_json::json!(2));
+        Ok(())
+    }
+
+    #[test]
+    fn detailed_stats_report_skipped_files() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::write(root.join("lib.rs"), "pub fn lib() {}\n")?;
+        std::fs::write(root.join("blob.rs"), b"fn x() {}\0\x01\x02")?;
+
+        let mut indexer = Indexer::new(root.clone())?;
+        indexer.index_repository(&root)?;
+        drop(indexer);
+
+        let stats = DetailedStats::collect(&FlashgrepPaths::new(&root))?;
+        assert_eq!(stats.totals.total_files, 1);
+        assert_eq!(stats.skipped_by_reason["binary"], 1);
+        assert_eq!(stats.skipped_files.len(), 1);
+        assert!(stats.skipped_files[0].file_path.ends_with("blob.rs"));
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use crate::config::paths::FlashgrepPaths;
+use crate::config::Config;
+use crate::db::models::{FileMetadata, IndexFailure};
+use crate::db::Database;
+use crate::index::scanner::FileScanner;
+use crate::watcher::registry::{is_process_alive, WatcherRegistry};
+use crate::FlashgrepResult;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Maximum number of paths listed per pending-change category
+const PENDING_SAMPLE_LIMIT: usize = 50;
+
+/// Structured snapshot of index health for a repository
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub repo_root: PathBuf,
+    pub index_exists: bool,
+    pub counts: IndexCounts,
+    pub pending: PendingChanges,
+    pub last_commit: LastCommit,
+    pub watcher: WatcherStatus,
+    pub failed_files: Vec<IndexFailure>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexCounts {
+    pub files: usize,
+    pub chunks: usize,
+    pub symbols: usize,
+    pub index_size_bytes: u64,
+}
+
+/// Differences between the working tree and the indexed metadata
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PendingChanges {
+    pub added: usize,
+    pub modified: usize,
+    pub deleted: usize,
+    pub added_paths: Vec<PathBuf>,
+    pub modified_paths: Vec<PathBuf>,
+    pub deleted_paths: Vec<PathBuf>,
+}
+
+impl PendingChanges {
+    pub fn total(&self) -> usize {
+        self.added + self.modified + self.deleted
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LastCommit {
+    /// Tantivy opstamp of the last commit
+    pub opstamp: Option<u64>,
+    /// Unix timestamp of the last Tantivy commit
+    pub committed_at: Option<i64>,
+    /// Newest file modification time recorded in metadata
+    pub last_file_update: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WatcherStatus {
+    pub registered: bool,
+    pub alive: bool,
+    pub pid: Option<u32>,
+    pub started_at: Option<i64>,
+}
+
+impl IndexStatus {
+    /// Collect the status of the index for `repo_root`
+    pub fn collect(repo_root: &Path, registry: &WatcherRegistry) -> FlashgrepResult<Self> {
+        let paths = FlashgrepPaths::new(repo_root);
+        let watcher = watcher_status(repo_root, registry);
+
+        if !paths.exists() || !paths.metadata_db().exists() {
+            return Ok(Self {
+                repo_root: repo_root.to_path_buf(),
+                index_exists: false,
+                counts: IndexCounts::default(),
+                pending: PendingChanges::default(),
+                last_commit: LastCommit::default(),
+                watcher,
+                failed_files: Vec::new(),
+            });
+        }
+
+        let config = Config::from_file(&paths.config_file()).unwrap_or_default();
+        let db = Database::open_read_only(&paths.metadata_db())?;
+        let stats = db.get_stats()?;
+
+        Ok(Self {
+            repo_root: repo_root.to_path_buf(),
+            index_exists: true,
+            counts: IndexCounts {
+                files: stats.total_files,
+                chunks: stats.total_chunks,
+                symbols: stats.total_symbols,
+                index_size_bytes: stats.index_size_bytes,
+            },
+            pending: pending_changes(repo_root, &config, &db)?,
+            last_commit: LastCommit {
+                last_file_update: stats.last_update,
+                ..last_commit(&paths.text_index_dir())
+            },
+            watcher,
+            failed_files: db.get_index_failures()?,
+        })
+    }
+}
+
+fn watcher_status(repo_root: &Path, registry: &WatcherRegistry) -> WatcherStatus {
+    let entry = registry.get(repo_root).ok().flatten().cloned();
+    match entry {
+        Some(entry) => WatcherStatus {
+            registered: true,
+            alive: is_process_alive(entry.pid),
+            pid: Some(entry.pid),
+            started_at: Some(entry.started_at),
+        },
+        None => WatcherStatus::default(),
+    }
+}
+
+fn pending_changes(
+    repo_root: &Path,
+    config: &Config,
+    db: &Database,
+) -> FlashgrepResult<PendingChanges> {
+    let indexed = db.get_file_mtimes()?;
+    let scanner = FileScanner::new(repo_root.to_path_buf(), config.clone());
+    let mut pending = PendingChanges::default();
+    let mut seen = HashSet::new();
+
+    for path in scanner.scan() {
+        let current = FileMetadata::from_path(&path).map(|m| m.last_modified).ok();
+        match indexed.get(&path) {
+            None => {
+                pending.added += 1;
+                push_sample(&mut pending.added_paths, &path);
+            }
+            Some(stored) if Some(*stored) != current => {
+                pending.modified += 1;
+                push_sample(&mut pending.modified_paths, &path);
+            }
+            Some(_) => {}
+        }
+        seen.insert(path);
+    }
+
+    let mut deleted = indexed
+        .keys()
+        .filter(|p| !seen.contains(*p))
+        .cloned()
+        .collect::<Vec<_>>();
+    deleted.sort();
+    pending.deleted = deleted.len();
+    deleted.truncate(PENDING_SAMPLE_LIMIT);
+    pending.deleted_paths = deleted;
+    pending.added_paths.sort();
+    pending.modified_paths.sort();
+
+    Ok(pending)
+}
+
+fn push_sample(samples: &mut Vec<PathBuf>, path: &Path) {
+    if samples.len() < PENDING_SAMPLE_LIMIT {
+        samples.push(path.to_path_buf());
+    }
+}
+
+fn last_commit(text_index_dir: &Path) -> LastCommit {
+    let opstamp = tantivy::Index::open_in_dir(text_index_dir)
+        .and_then(|index| index.load_metas())
+        .map(|metas| metas.opstamp)
+        .ok();
+    let committed_at = std::fs::metadata(text_index_dir.join("meta.json"))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64);
+    LastCommit {
+        opstamp,
+        committed_at,
+        last_file_update: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::engine::Indexer;
+    use tempfile::TempDir;
+
+    #[test]
+    fn status_reports_counts_pending_and_watcher() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::write(repo_root.join("main.rs"), "fn main() {}\n")?;
+
+        let mut indexer = Indexer::new(repo_root.clone())?;
+        indexer.index_repository(&repo_root)?;
+        drop(indexer);
+
+        std::fs::write(repo_root.join("extra.rs"), "fn extra() {}\n")?;
+
+        let registry_dir = TempDir::new()?;
+        let mut registry =
+            WatcherRegistry::load_from_path(registry_dir.path().join("watchers.json"))?;
+        registry.upsert(&repo_root, std::process::id())?;
+
+        let status = IndexStatus::collect(&repo_root, &registry)?;
+        assert!(status.index_exists);
+        assert_eq!(status.counts.files, 1);
+        assert_eq!(status.pending.added, 1);
+        assert_eq!(status.pending.deleted, 0);
+        assert!(status.last_commit.opstamp.is_some());
+        assert!(status.watcher.registered);
+        assert!(status.watcher.alive);
+        assert_eq!(status.watcher.pid, Some(std::process::id()));
+        Ok(())
+    }
+
+    #[test]
+    fn status_without_index_is_empty() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let registry = WatcherRegistry::load_from_path(temp_dir.path().join("watchers.json"))?;
+        let status = IndexStatus::collect(temp_dir.path(), &registry)?;
+        assert!(!status.index_exists);
+        assert!(!status.watcher.registered);
+        Ok(())
+    }
+}
@@ -7,10 +7,13 @@ pub mod chunking;
 pub mod cli;
 pub mod config;
 pub mod db;
+pub mod encoding;
+pub mod git;
 pub mod index;
 pub mod mcp;
 pub mod neural;
 pub mod path_utils;
+pub mod progress;
 pub mod search;
 pub mod symbols;
 pub mod watcher;
@@ -30,12 +33,24 @@ pub const DEFAULT_MCP_PORT: u16 = 7777;
 /// Default directory name for flashgrep data
 pub const FLASHGREP_DIR: &str = ".flashgrep";
 
-/// Maximum file size to index (2MB)
+/// Files larger than this are streamed instead of read whole (2MB)
 pub const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
 
+/// Files larger than this are skipped and recorded (64MB)
+pub const SKIP_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Line length above which a file is treated as minified
+pub const MINIFIED_LINE_LENGTH: usize = 1000;
+
 /// Maximum chunk size in lines
 pub const MAX_CHUNK_LINES: usize = 300;
 
+/// Chunk size in lines after which the chunker ends a chunk at the next boundary
+pub const TARGET_CHUNK_LINES: usize = 1;
+
+/// Lines shared by consecutive chunks
+pub const CHUNK_OVERLAP_LINES: usize = 0;
+
 /// Initialize logging with tracing
 pub fn init_logging() {
     tracing_subscriber::fmt()
@@ -90,6 +105,18 @@ pub enum FlashgrepError {
 
     #[error("Task error: {0}")]
     Task(String),
+
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Git error: {0}")]
+    Git(String),
+
+    #[error("Index rebuild required: {0}. Run 'flashgrep clear' and then 'flashgrep index'.")]
+    RebuildRequired(String),
+
+    #[error("Index migration required: {0}. Run 'flashgrep index' to migrate it.")]
+    MigrationRequired(String),
 }
 
 impl From<anyhow::Error> for FlashgrepError {
@@ -152,6 +179,10 @@ impl FlashgrepError {
             FlashgrepError::FileWatcher(_) => 6,
             FlashgrepError::McpServer(_) => 7,
             FlashgrepError::Task(_) => 8,
+            FlashgrepError::Cancelled(_) => 9,
+            FlashgrepError::Git(_) => 10,
+            FlashgrepError::RebuildRequired(_) => 11,
+            FlashgrepError::MigrationRequired(_) => 12,
         }
     }
 }
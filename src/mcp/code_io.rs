@@ -1,12 +1,16 @@
 use crate::config::paths::FlashgrepPaths;
+use crate::db::models::{BlameInfo, LineRange};
 use crate::db::Database;
+use crate::encoding;
+use crate::git::{blame_cached, GitRepo};
 use crate::mcp::safety::{
     chunking_guidance, continuation_meta, payload_too_large_error, MAX_MCP_READ_BYTES,
     MAX_MCP_WRITE_REPLACEMENT_BYTES, REASON_BATCH_DUPLICATE_OPERATION_ID,
-    REASON_BATCH_DUPLICATE_TARGET, REASON_BATCH_OVERLAPPING_OPERATIONS, REASON_FILE_NOT_FOUND,
-    REASON_INTERNAL_ERROR, REASON_INVALID_RANGE, REASON_PAYLOAD_TOO_LARGE,
-    REASON_PRECONDITION_FAILED,
+    REASON_BATCH_DUPLICATE_TARGET, REASON_BATCH_OVERLAPPING_OPERATIONS, REASON_CANCELLED,
+    REASON_CANCELLED_ROLLED_BACK, REASON_FILE_NOT_FOUND, REASON_INTERNAL_ERROR,
+    REASON_INVALID_RANGE, REASON_PAYLOAD_TOO_LARGE, REASON_PRECONDITION_FAILED,
 };
+use crate::progress::ProgressReporter;
 use crate::{FlashgrepError, FlashgrepResult};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -30,6 +34,7 @@ pub fn read_code_input_schema() -> Value {
             "max_bytes": {"type": "integer", "minimum": 1, "description": "Byte budget"},
             "max_lines": {"type": "integer", "minimum": 1, "description": "Line budget"},
             "chunk_index": {"type": "integer", "minimum": 0, "description": "Continuation chunk index"},
+            "blame": {"type": "boolean", "default": false, "description": "Attach the last commit hash, author, date and summary touching the returned lines"},
             "metadata_level": {
                 "type": "string",
                 "enum": ["minimal", "standard"],
@@ -191,6 +196,19 @@ pub fn read_code(paths: &FlashgrepPaths, arguments: &Value) -> FlashgrepResult<V
         !bounded.truncated,
     );
 
+    let with_blame = arguments
+        .get("blame")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if with_blame && !bounded.included_lines.is_empty() {
+        response["blame"] = json!(blame_slice(
+            paths,
+            &read_target.file_path,
+            &content,
+            LineRange::new(bounded.first_line, bounded.last_line)
+        ));
+    }
+
     if metadata_level == MetadataLevel::Standard {
         response["mode"] = Value::String(read_target.mode_name.to_string());
         response["total_lines_available"] = Value::Number((read_target.lines.len() as u64).into());
@@ -243,11 +261,12 @@ pub fn write_code(arguments: &Value) -> FlashgrepResult<Value> {
     }
 
     let path = PathBuf::from(file_path);
-    let original_content = std::fs::read_to_string(&path)?;
+    let decoded = encoding::read_decoded(&path)?;
+    let original_content = decoded.content;
     let original_hash = calculate_sha256(&original_content);
-    let had_trailing_newline = original_content.ends_with('\n');
 
-    let original_lines: Vec<String> = original_content.lines().map(ToString::to_string).collect();
+    let layout = LineLayout::parse(&original_content);
+    let original_lines = &layout.lines;
     if original_lines.is_empty() {
         return Err(FlashgrepError::Config(
             "Cannot apply line-range write to empty file".to_string(),
@@ -264,7 +283,7 @@ pub fn write_code(arguments: &Value) -> FlashgrepResult<Value> {
 
     let conflict = check_preconditions(
         arguments.get("precondition"),
-        &original_lines,
+        original_lines,
         &original_hash,
         start_line,
         end_line,
@@ -278,23 +297,9 @@ pub fn write_code(arguments: &Value) -> FlashgrepResult<Value> {
         }));
     }
 
-    let replacement_lines: Vec<String> = if replacement.is_empty() {
-        Vec::new()
-    } else {
-        replacement.split('\n').map(ToString::to_string).collect()
-    };
-
-    let mut new_lines = Vec::new();
-    new_lines.extend_from_slice(&original_lines[..start_line - 1]);
-    new_lines.extend(replacement_lines.iter().cloned());
-    new_lines.extend_from_slice(&original_lines[end_line..]);
+    let (new_content, new_line_count) = layout.splice(start_line, end_line, replacement);
 
-    let mut new_content = new_lines.join("\n");
-    if had_trailing_newline {
-        new_content.push('\n');
-    }
-
-    std::fs::write(&path, &new_content)?;
+    encoding::write_text(&path, &new_content, decoded.encoding)?;
     let new_hash = calculate_sha256(&new_content);
 
     Ok(json!({
@@ -303,7 +308,7 @@ pub fn write_code(arguments: &Value) -> FlashgrepResult<Value> {
         "start_line": start_line,
         "end_line": end_line,
         "replaced_line_count": end_line - start_line + 1,
-        "new_line_count": replacement_lines.len(),
+        "new_line_count": new_line_count,
         "file_hash_before": original_hash,
         "file_hash_after": new_hash
     }))
@@ -337,12 +342,23 @@ struct BatchState {
 
 #[derive(Clone)]
 struct FileSnapshot {
-    original_content: String,
+    original_bytes: Vec<u8>,
     lines: Vec<String>,
     file_hash_before: String,
 }
 
 pub fn batch_write_code(arguments: &Value) -> FlashgrepResult<Value> {
+    batch_write_code_with_progress(arguments, &ProgressReporter::disabled())
+}
+
+/// Apply a batch of edits, reporting per-operation progress and honouring cancellation.
+///
+/// A cancelled atomic batch restores every touched file; a cancelled best-effort
+/// batch keeps the edits already applied and skips the rest.
+pub fn batch_write_code_with_progress(
+    arguments: &Value,
+    progress: &ProgressReporter,
+) -> FlashgrepResult<Value> {
     let mode = match arguments.get("mode").and_then(Value::as_str) {
         None | Some("atomic") => BatchMode::Atomic,
         Some("best_effort") => BatchMode::BestEffort,
@@ -425,7 +441,7 @@ pub fn batch_write_code(arguments: &Value) -> FlashgrepResult<Value> {
 
         if !file_snapshots.contains_key(&op.file_path) {
             let path = PathBuf::from(&op.file_path);
-            let original_content = match std::fs::read_to_string(&path) {
+            let original_bytes = match std::fs::read(&path) {
                 Ok(v) => v,
                 Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                     state.status = "failed".to_string();
@@ -436,10 +452,11 @@ pub fn batch_write_code(arguments: &Value) -> FlashgrepResult<Value> {
                 Err(e) => return Err(FlashgrepError::Io(e)),
             };
 
+            let original_content = encoding::decode_file_bytes(&path, &original_bytes)?.content;
             let lines: Vec<String> = original_content.lines().map(ToString::to_string).collect();
             let snapshot = FileSnapshot {
                 file_hash_before: calculate_sha256(&original_content),
-                original_content,
+                original_bytes,
                 lines,
             };
             file_snapshots.insert(op.file_path.clone(), snapshot);
@@ -499,7 +516,31 @@ pub fn batch_write_code(arguments: &Value) -> FlashgrepResult<Value> {
     }
 
     let mut touched_files = HashSet::new();
+    let total_ops = states.len() as u64;
     for idx in 0..states.len() {
+        if progress.is_cancelled() {
+            if mode == BatchMode::Atomic {
+                rollback_atomic_files(&touched_files, &file_snapshots)?;
+            }
+            for state in &mut states {
+                if state.status == "applied" && mode == BatchMode::Atomic {
+                    state.status = "skipped".to_string();
+                    state.reason_code = Some(REASON_CANCELLED_ROLLED_BACK.to_string());
+                    state.message = Some("Rolled back due to cancellation".to_string());
+                    state.file_hash_after = None;
+                } else if state.status == "pending" {
+                    state.status = "skipped".to_string();
+                    state.reason_code = Some(REASON_CANCELLED.to_string());
+                    state.message = Some("Skipped due to cancellation".to_string());
+                }
+            }
+            let mut payload = batch_result_payload(mode, states);
+            payload["ok"] = json!(false);
+            payload["cancelled"] = json!(true);
+            return Ok(payload);
+        }
+        progress.report(idx as u64, Some(total_ops), Some("batch_write_code"));
+
         if states[idx].status != "pending" {
             continue;
         }
@@ -662,7 +703,7 @@ fn rollback_atomic_files(
 ) -> FlashgrepResult<()> {
     for path in touched_files {
         if let Some(snapshot) = snapshots.get(path) {
-            std::fs::write(path, &snapshot.original_content)?;
+            std::fs::write(path, &snapshot.original_bytes)?;
         }
     }
     Ok(())
@@ -719,7 +760,6 @@ struct WriteSession {
     start_line: usize,
     end_line: usize,
     file_hash_before: String,
-    had_trailing_newline: bool,
     replacement_accumulated: String,
     next_chunk_index: usize,
 }
@@ -757,11 +797,9 @@ fn write_code_chunked(
     let session_path = write_session_path(continuation_id);
     let mut session = if chunk_index == 0 {
         let path = PathBuf::from(file_path);
-        let original_content = std::fs::read_to_string(&path)?;
+        let original_content = encoding::read_to_string(&path)?;
         let original_hash = calculate_sha256(&original_content);
-        let had_trailing_newline = original_content.ends_with('\n');
-        let original_lines: Vec<String> =
-            original_content.lines().map(ToString::to_string).collect();
+        let original_lines = LineLayout::parse(&original_content).lines;
         if original_lines.is_empty() {
             return Err(FlashgrepError::Config(
                 "Cannot apply line-range write to empty file".to_string(),
@@ -797,7 +835,6 @@ fn write_code_chunked(
             start_line,
             end_line,
             file_hash_before: original_hash,
-            had_trailing_newline,
             replacement_accumulated: String::new(),
             next_chunk_index: 0,
         }
@@ -846,30 +883,19 @@ fn write_code_chunked(
     }
 
     let path = PathBuf::from(file_path);
-    let original_content = std::fs::read_to_string(&path)?;
-    let original_lines: Vec<String> = original_content.lines().map(ToString::to_string).collect();
-
-    let replacement_lines: Vec<String> = if session.replacement_accumulated.is_empty() {
-        Vec::new()
-    } else {
-        session
-            .replacement_accumulated
-            .split('\n')
-            .map(ToString::to_string)
-            .collect()
-    };
-
-    let mut new_lines = Vec::new();
-    new_lines.extend_from_slice(&original_lines[..start_line - 1]);
-    new_lines.extend(replacement_lines.iter().cloned());
-    new_lines.extend_from_slice(&original_lines[end_line..]);
-
-    let mut new_content = new_lines.join("\n");
-    if session.had_trailing_newline {
-        new_content.push('\n');
+    let decoded = encoding::read_decoded(&path)?;
+    let layout = LineLayout::parse(&decoded.content);
+    if end_line > layout.lines.len() {
+        return Err(FlashgrepError::Config(format!(
+            "Invalid range: end_line {} exceeds file line count {}",
+            end_line,
+            layout.lines.len()
+        )));
     }
+    let (new_content, new_line_count) =
+        layout.splice(start_line, end_line, &session.replacement_accumulated);
 
-    std::fs::write(&path, &new_content)?;
+    encoding::write_text(&path, &new_content, decoded.encoding)?;
     let new_hash = calculate_sha256(&new_content);
 
     let _ = std::fs::remove_file(&session_path);
@@ -880,7 +906,7 @@ fn write_code_chunked(
         "start_line": start_line,
         "end_line": end_line,
         "replaced_line_count": end_line - start_line + 1,
-        "new_line_count": replacement_lines.len(),
+        "new_line_count": new_line_count,
         "file_hash_before": session.file_hash_before,
         "file_hash_after": new_hash,
         "continuation": continuation_meta(
@@ -891,6 +917,87 @@ fn write_code_chunked(
     }))
 }
 
+/// A file's lines together with the terminator each one ended with, so a line-range
+/// edit can leave every untouched byte (CRLF, LF, missing final newline) as it was
+struct LineLayout {
+    lines: Vec<String>,
+    endings: Vec<&'static str>,
+}
+
+impl LineLayout {
+    /// Split like [`str::lines`], remembering `\r\n`, `\n` or no terminator per line
+    fn parse(content: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut endings = Vec::new();
+        let mut rest = content;
+        while !rest.is_empty() {
+            let (line, ending, next) = match rest.find('\n') {
+                Some(idx) if rest[..idx].ends_with('\r') => {
+                    (&rest[..idx - 1], "\r\n", &rest[idx + 1..])
+                }
+                Some(idx) => (&rest[..idx], "\n", &rest[idx + 1..]),
+                None => (rest, "", ""),
+            };
+            lines.push(line.to_string());
+            endings.push(ending);
+            rest = next;
+        }
+        Self { lines, endings }
+    }
+
+    /// The more common of `\r\n` and `\n`, preferring `\n` on a tie
+    fn dominant_ending(&self) -> &'static str {
+        let crlf = self.endings.iter().filter(|e| **e == "\r\n").count();
+        let lf = self.endings.iter().filter(|e| **e == "\n").count();
+        if crlf > lf {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    /// Replace lines `start_line..=end_line` (1-based) and return the new content and
+    /// the number of inserted lines. Inserted lines use the ending of the last replaced
+    /// line (or the file's dominant ending), and the last inserted line keeps exactly
+    /// the last replaced line's ending, so the trailing-newline state is preserved.
+    fn splice(&self, start_line: usize, end_line: usize, replacement: &str) -> (String, usize) {
+        let last_ending = self.endings[end_line - 1];
+        let line_ending = if last_ending.is_empty() {
+            self.dominant_ending()
+        } else {
+            last_ending
+        };
+        let replacement_lines: Vec<&str> = if replacement.is_empty() {
+            Vec::new()
+        } else {
+            replacement
+                .split('\n')
+                .map(|line| line.strip_suffix('\r').unwrap_or(line))
+                .collect()
+        };
+
+        let original = || self.lines.iter().zip(&self.endings);
+        let mut content = String::new();
+        for (line, ending) in original().take(start_line - 1) {
+            content.push_str(line);
+            content.push_str(ending);
+        }
+        for (idx, line) in replacement_lines.iter().enumerate() {
+            content.push_str(line);
+            content.push_str(if idx + 1 == replacement_lines.len() {
+                last_ending
+            } else {
+                line_ending
+            });
+        }
+        for (line, ending) in original().skip(end_line) {
+            content.push_str(line);
+            content.push_str(ending);
+        }
+        (content, replacement_lines.len())
+    }
+}
+
 fn write_session_path(continuation_id: &str) -> PathBuf {
     let safe_id: String = continuation_id
         .chars()
@@ -1028,7 +1135,7 @@ fn read_file_slice(
         ));
     }
 
-    let content = std::fs::read_to_string(file_path)?;
+    let content = encoding::read_to_string(Path::new(file_path))?;
     let all_lines: Vec<&str> = content.lines().collect();
 
     if all_lines.is_empty() {
@@ -1085,7 +1192,7 @@ fn read_symbol_slice(
     symbol_name: &str,
     context_lines: usize,
 ) -> FlashgrepResult<ReadTarget> {
-    let db = Database::open(&paths.metadata_db())?;
+    let db = Database::open_read_only(&paths.metadata_db())?;
     let symbols = db.find_symbols_by_name(symbol_name)?;
     let symbol = symbols
         .first()
@@ -1192,6 +1299,30 @@ fn get_required_usize(arguments: &Value, key: &str) -> FlashgrepResult<usize> {
     Ok(num as usize)
 }
 
+/// Blame the lines returned by `read_code`; `None` outside git or for untracked files
+fn blame_slice(
+    paths: &FlashgrepPaths,
+    file_path: &str,
+    content: &str,
+    lines: LineRange,
+) -> Option<BlameInfo> {
+    let repo = GitRepo::open(paths.repo_root()).ok()?;
+    let db = paths
+        .metadata_db()
+        .exists()
+        .then(|| Database::open_read_only(&paths.metadata_db()).ok())
+        .flatten();
+    blame_cached(
+        db.as_ref(),
+        &repo,
+        Path::new(file_path),
+        &calculate_sha256(content),
+        lines,
+        None,
+    )
+    .ok()
+}
+
 fn calculate_sha256(content: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
@@ -1306,6 +1437,40 @@ mod tests {
         assert!(second["continuation_start_line"].is_null());
     }
 
+    #[test]
+    fn read_code_attaches_blame_for_committed_lines() {
+        let (temp, file_path) = setup_file("fn blamed() {}\n");
+        let repo_root = temp.path().to_path_buf();
+        let paths = FlashgrepPaths::new(&repo_root);
+        crate::git::tests::git(&repo_root, &["init", "-q", "-b", "main"]);
+        crate::git::tests::git(&repo_root, &["add", "sample.rs"]);
+        crate::git::tests::git(&repo_root, &["commit", "-q", "-m", "add blamed"]);
+
+        paths.create().expect("create paths");
+        let db = Database::open(&paths.metadata_db()).expect("open database");
+
+        let args = json!({"file_path": file_path.to_string_lossy(), "blame": true});
+        let response = read_code(&paths, &args).expect("read with blame");
+        assert_eq!(response["blame"]["summary"], "add blamed");
+        assert_eq!(response["blame"]["author"], "flashgrep");
+
+        // The second read is answered from the cache filled by the first
+        let content_hash = calculate_sha256(response["content"].as_str().expect("content"));
+        let mut cached = db
+            .get_cached_blame(&file_path, &content_hash)
+            .expect("read cache")
+            .expect("cached blame");
+        cached.summary = "served from the cache".to_string();
+        db.cache_blame(&file_path, &content_hash, &cached)
+            .expect("mark cache");
+        let response = read_code(&paths, &args).expect("cached read");
+        assert_eq!(response["blame"]["summary"], "served from the cache");
+
+        let plain = read_code(&paths, &json!({"file_path": file_path.to_string_lossy()}))
+            .expect("read without blame");
+        assert!(plain.get("blame").is_none());
+    }
+
     #[test]
     fn read_code_rejects_ambiguous_mode() {
         let temp = TempDir::new().expect("temp dir");
@@ -1339,6 +1504,50 @@ mod tests {
         assert_eq!(updated, "line1\nupdated\nline3\n");
     }
 
+    #[test]
+    fn read_and_write_code_preserve_file_encoding() {
+        let temp = TempDir::new().expect("temp dir");
+        let paths = FlashgrepPaths::new(temp.path());
+        let utf16 = encoding::TextEncoding {
+            encoding: encoding_rs::UTF_16LE,
+            bom: true,
+        };
+        let latin1 = encoding::TextEncoding {
+            encoding: encoding_rs::WINDOWS_1252,
+            bom: false,
+        };
+
+        for (name, text_encoding) in [("utf16.txt", utf16), ("latin1.txt", latin1)] {
+            let file_path = temp.path().join(name);
+            let original = "// café\r\nlet a = 1;\r\nlet b = 2;\r\n";
+            fs::write(&file_path, text_encoding.encode(original).unwrap()).expect("seed");
+
+            let read = read_code(
+                &paths,
+                &json!({"file_path": file_path.to_string_lossy(), "metadata_level": "minimal"}),
+            )
+            .expect("read");
+            assert_eq!(read["content"], json!("// café\nlet a = 1;\nlet b = 2;"));
+
+            let result = write_code(&json!({
+                "file_path": file_path.to_string_lossy(),
+                "start_line": 2,
+                "end_line": 2,
+                "replacement": "let a = \"crème\";"
+            }))
+            .expect("write");
+            assert_eq!(result["ok"], Value::Bool(true), "{}", name);
+
+            let expected = "// café\r\nlet a = \"crème\";\r\nlet b = 2;\r\n";
+            assert_eq!(
+                fs::read(&file_path).expect("read back"),
+                text_encoding.encode(expected).unwrap(),
+                "{}",
+                name
+            );
+        }
+    }
+
     #[test]
     fn write_code_reports_precondition_conflict() {
         let (_temp, file_path) = setup_file("line1\nline2\nline3\n");
@@ -1498,6 +1707,75 @@ mod tests {
         assert_eq!(updated, "a\nhello world\nc\n");
     }
 
+    #[test]
+    fn line_layout_splice_preserves_endings() {
+        let mixed = LineLayout::parse("a\r\nb\nc");
+        assert_eq!(mixed.lines, vec!["a", "b", "c"]);
+        assert_eq!(mixed.splice(2, 2, "x\ny"), ("a\r\nx\ny\nc".to_string(), 2));
+        assert_eq!(
+            mixed.splice(3, 3, "z\r\nw"),
+            ("a\r\nb\nz\nw".to_string(), 2)
+        );
+        assert_eq!(mixed.splice(1, 1, ""), ("b\nc".to_string(), 0));
+
+        let crlf = LineLayout::parse("a\r\nb\r\n");
+        assert_eq!(
+            crlf.splice(2, 2, "x\ny"),
+            ("a\r\nx\r\ny\r\n".to_string(), 2)
+        );
+        assert_eq!(crlf.splice(1, 2, "only"), ("only\r\n".to_string(), 1));
+    }
+
+    #[test]
+    fn writes_preserve_mixed_line_endings_and_final_newline() {
+        let (_temp, file_path) = setup_file("one\r\ntwo\nthree\r\nfour");
+        let path = file_path.to_string_lossy().to_string();
+
+        let single = write_code(&json!({
+            "file_path": path,
+            "start_line": 2,
+            "end_line": 2,
+            "replacement": "TWO"
+        }))
+        .expect("write_code");
+        assert_eq!(single["ok"], Value::Bool(true));
+        assert_eq!(
+            fs::read(&file_path).expect("read"),
+            b"one\r\nTWO\nthree\r\nfour"
+        );
+
+        let batch = batch_write_code(&json!({
+            "operations": [
+                {"id": "first", "file_path": path, "start_line": 1, "end_line": 1, "replacement": "ONE"},
+                {"id": "last", "file_path": path, "start_line": 4, "end_line": 4, "replacement": "FOUR\nFIVE"}
+            ]
+        }))
+        .expect("batch_write_code");
+        assert_eq!(batch["ok"], Value::Bool(true));
+        assert_eq!(
+            fs::read(&file_path).expect("read"),
+            b"ONE\r\nTWO\nthree\r\nFOUR\r\nFIVE"
+        );
+
+        for (chunk_index, replacement, is_final_chunk) in [(0, "THR", false), (1, "EE", true)] {
+            let chunk = write_code(&json!({
+                "file_path": path,
+                "start_line": 3,
+                "end_line": 3,
+                "replacement": replacement,
+                "continuation_id": "test-chunked-crlf-write",
+                "chunk_index": chunk_index,
+                "is_final_chunk": is_final_chunk
+            }))
+            .expect("chunked write");
+            assert_eq!(chunk["ok"], Value::Bool(true));
+        }
+        assert_eq!(
+            fs::read(&file_path).expect("read"),
+            b"ONE\r\nTWO\nTHREE\r\nFOUR\r\nFIVE"
+        );
+    }
+
     #[test]
     fn batch_write_code_atomic_rejects_overlap() {
         let (_temp, file_path) = setup_file("a\nb\nc\n");
@@ -1591,6 +1869,53 @@ mod tests {
         assert_eq!(content, "a\nb\nc\n");
     }
 
+    #[test]
+    fn batch_write_code_atomic_cancellation_rolls_back_applied_ops() {
+        use crate::progress::{CancellationToken, ProgressSink};
+        use std::sync::Arc;
+
+        let (_temp, file_path) = setup_file("a\nb\nc\n");
+        let token = CancellationToken::new();
+        let sink_token = token.clone();
+        let sink: ProgressSink = Arc::new(move |progress, _, _| {
+            if progress == 1 {
+                sink_token.cancel();
+            }
+        });
+        let reporter = ProgressReporter::new(Some(sink), token);
+
+        let ops = (1..=3)
+            .map(|line| {
+                json!({
+                    "id": format!("op{}", line),
+                    "file_path": file_path.to_string_lossy(),
+                    "start_line": line,
+                    "end_line": line,
+                    "replacement": format!("edited{}", line)
+                })
+            })
+            .collect::<Vec<_>>();
+        let result = batch_write_code_with_progress(
+            &json!({"mode": "atomic", "operations": ops}),
+            &reporter,
+        )
+        .expect("batch result");
+
+        assert_eq!(result["cancelled"], Value::Bool(true));
+        assert_eq!(result["ok"], Value::Bool(false));
+        assert_eq!(result["applied_count"], Value::Number(0u64.into()));
+        assert_eq!(
+            result["results"][0]["reason_code"],
+            Value::String(REASON_CANCELLED_ROLLED_BACK.to_string())
+        );
+        assert_eq!(
+            result["results"][2]["reason_code"],
+            Value::String(REASON_CANCELLED.to_string())
+        );
+        let content = fs::read_to_string(file_path).expect("file read");
+        assert_eq!(content, "a\nb\nc\n");
+    }
+
     #[test]
     fn batch_write_code_dry_run_does_not_mutate_files() {
         let (_temp, file_path) = setup_file("a\nb\nc\n");
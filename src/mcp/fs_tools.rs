@@ -1,3 +1,4 @@
+use crate::encoding;
 use crate::mcp::safety::not_found_error;
 use crate::{FlashgrepError, FlashgrepResult};
 use serde_json::{json, Value};
@@ -92,7 +93,7 @@ pub fn fs_read(arguments: &Value) -> FlashgrepResult<Value> {
         }));
     }
 
-    let content = fs::read_to_string(&target)?;
+    let content = encoding::read_to_string(&target)?;
     Ok(json!({
         "ok": true,
         "operation": "fs_read",
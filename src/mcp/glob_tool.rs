@@ -1,12 +1,23 @@
+use crate::config::languages::LanguageMap;
+use crate::config::paths::{find_repo_root, FlashgrepPaths};
+use crate::config::Config;
+use crate::db::Database;
+use crate::index::scanner::FlashgrepIgnore;
 use crate::path_utils::{normalize_glob_pattern, normalize_path_for_matching};
+use crate::progress::ProgressReporter;
+use crate::search::languages_from_mcp_args;
 use crate::{FlashgrepError, FlashgrepResult};
 use glob::{MatchOptions, Pattern};
 use serde_json::{json, Value};
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 use walkdir::WalkDir;
 
+/// Number of visited entries between glob progress notifications
+const GLOB_PROGRESS_INTERVAL: u64 = 1000;
+
 pub fn glob_input_schema() -> Value {
     json!({
         "type": "object",
@@ -16,9 +27,11 @@ pub fn glob_input_schema() -> Value {
             "include": {"type": "array", "items": {"type": "string"}, "description": "Additional include glob patterns"},
             "exclude": {"type": "array", "items": {"type": "string"}, "description": "Exclude glob patterns"},
             "extensions": {"type": "array", "items": {"type": "string"}, "description": "File extensions (rs or .rs)"},
+            "languages": {"type": "array", "items": {"type": "string"}, "description": "Language ids or aliases; uses the indexed language column when an index exists, otherwise detects languages on the fly"},
             "max_depth": {"type": "integer", "minimum": 0, "description": "Maximum traversal depth from root"},
             "recursive": {"type": "boolean", "description": "Whether traversal recurses into subdirectories"},
             "include_hidden": {"type": "boolean", "description": "Include hidden files/directories"},
+            "respect_gitignore": {"type": "boolean", "description": "Skip paths ignored by .gitignore, .git/info/exclude, global excludes and .flashgrepignore (default true)"},
             "follow_symlinks": {"type": "boolean", "description": "Follow symbolic links"},
             "case_sensitive": {"type": "boolean", "description": "Case-sensitive glob matching"},
             "sort_by": {"type": "string", "enum": ["path", "name", "modified", "size"]},
@@ -30,6 +43,14 @@ pub fn glob_input_schema() -> Value {
 }
 
 pub fn run_glob(arguments: &Value) -> FlashgrepResult<Value> {
+    run_glob_with_progress(arguments, &ProgressReporter::disabled())
+}
+
+/// Run a glob walk, reporting the number of visited entries and honouring cancellation
+pub fn run_glob_with_progress(
+    arguments: &Value,
+    progress: &ProgressReporter,
+) -> FlashgrepResult<Value> {
     let opts = GlobOptions::from_args(arguments)?;
     let mut matches = Vec::new();
 
@@ -38,13 +59,26 @@ pub fn run_glob(arguments: &Value) -> FlashgrepResult<Value> {
         walker = walker.max_depth(max_depth + 1);
     }
 
+    let ignore_root = find_repo_root(&opts.root).unwrap_or_else(|| opts.root.clone());
+    let ignore = if opts.respect_gitignore {
+        FlashgrepIgnore::from_root(&ignore_root)
+    } else {
+        FlashgrepIgnore::default()
+    };
+
     let include_patterns = compile_patterns(&opts.includes)?;
     let exclude_patterns = compile_patterns(&opts.excludes)?;
+    let language_filter = LanguageFilter::new(&ignore_root, &opts.languages)?;
+    let mut visited = 0u64;
 
     for entry in walker.into_iter().filter_entry(|e| {
         entry_allowed(e.path(), &opts.root, opts.include_hidden)
+            && (e.depth() == 0 || !ignore.is_ignored(e.path(), &ignore_root))
             && !should_prune_dir(e.path(), &opts.root, &exclude_patterns, opts.case_sensitive)
     }) {
+        visited += 1;
+        progress.checkpoint("glob", visited, None, GLOB_PROGRESS_INTERVAL)?;
+
         let entry = match entry {
             Ok(e) => e,
             Err(_) => continue,
@@ -69,6 +103,11 @@ pub fn run_glob(arguments: &Value) -> FlashgrepResult<Value> {
         if !extension_allowed(entry.path(), &opts.extensions) {
             continue;
         }
+        if let Some(filter) = &language_filter {
+            if !filter.matches(entry.path(), &ignore_root) {
+                continue;
+            }
+        }
 
         let metadata = entry.metadata().ok();
         let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
@@ -126,9 +165,11 @@ pub fn run_glob(arguments: &Value) -> FlashgrepResult<Value> {
             "includes": opts.includes,
             "excludes": opts.excludes,
             "extensions": opts.extensions,
+            "languages": opts.languages,
             "max_depth": opts.max_depth,
             "recursive": opts.recursive,
             "include_hidden": opts.include_hidden,
+            "respect_gitignore": opts.respect_gitignore,
             "follow_symlinks": opts.follow_symlinks,
             "case_sensitive": opts.case_sensitive,
             "sort_by": opts.sort_by.as_str(),
@@ -145,9 +186,11 @@ struct GlobOptions {
     includes: Vec<String>,
     excludes: Vec<String>,
     extensions: Vec<String>,
+    languages: Vec<String>,
     max_depth: Option<usize>,
     recursive: bool,
     include_hidden: bool,
+    respect_gitignore: bool,
     follow_symlinks: bool,
     case_sensitive: bool,
     sort_by: SortBy,
@@ -178,6 +221,7 @@ impl GlobOptions {
 
         let excludes = vec_from_string_array(arguments.get("exclude"))?;
         let extensions = normalize_extensions(vec_from_string_array(arguments.get("extensions"))?);
+        let languages = languages_from_mcp_args(arguments)?;
 
         let recursive = arguments
             .get("recursive")
@@ -197,6 +241,10 @@ impl GlobOptions {
             .get("include_hidden")
             .and_then(Value::as_bool)
             .unwrap_or(false);
+        let respect_gitignore = arguments
+            .get("respect_gitignore")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
         let follow_symlinks = arguments
             .get("follow_symlinks")
             .and_then(Value::as_bool)
@@ -230,9 +278,11 @@ impl GlobOptions {
             includes,
             excludes,
             extensions,
+            languages,
             max_depth,
             recursive,
             include_hidden,
+            respect_gitignore,
             follow_symlinks,
             case_sensitive,
             sort_by,
@@ -243,6 +293,54 @@ impl GlobOptions {
     }
 }
 
+/// Restricts glob results to files in the requested languages
+enum LanguageFilter {
+    /// Repo-relative paths of indexed files stored with a requested language
+    Indexed(HashSet<String>),
+    /// No index to consult: detect each file's language with the language map
+    Detected { map: LanguageMap, ids: Vec<String> },
+}
+
+impl LanguageFilter {
+    fn new(repo_root: &Path, languages: &[String]) -> FlashgrepResult<Option<Self>> {
+        let paths = FlashgrepPaths::new(repo_root);
+        let config = Config::from_file(&paths.config_file()).unwrap_or_default();
+        let ids = config.languages.resolve_ids(languages);
+        if ids.is_empty() {
+            return Ok(None);
+        }
+        if !paths.metadata_db().exists() {
+            return Ok(Some(Self::Detected {
+                map: config.languages,
+                ids,
+            }));
+        }
+
+        // Stored paths are absolute; the index may have been built from a canonical root
+        let canonical_root = repo_root.canonicalize().ok();
+        let files = Database::open_read_only(&paths.metadata_db())?
+            .files_with_languages(&ids)?
+            .into_iter()
+            .map(|path| match &canonical_root {
+                Some(canonical) if !path.starts_with(repo_root) => {
+                    relative_unix_path(&path, canonical)
+                }
+                _ => relative_unix_path(&path, repo_root),
+            })
+            .collect();
+        Ok(Some(Self::Indexed(files)))
+    }
+
+    fn matches(&self, path: &Path, repo_root: &Path) -> bool {
+        match self {
+            Self::Indexed(files) => files.contains(&relative_unix_path(path, repo_root)),
+            Self::Detected { map, ids } => map
+                .detect_file(path)
+                .is_some_and(|def| ids.contains(&def.id)),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum SortBy {
     Path,
@@ -609,6 +707,66 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn nested_gitignore_respected_unless_disabled() {
+        let (_tmp, root) = setup();
+        fs::create_dir_all(root.join("src/nested/gen")).expect("create gen dir");
+        fs::write(root.join("src/nested/gen/out.rs"), "// generated\n").expect("write gen");
+        fs::write(root.join("src/.gitignore"), "nested/gen/\n").expect("write gitignore");
+
+        let respected = run_glob(&json!({"path": root, "pattern": "**/*.rs"})).expect("glob");
+        let rel_paths = |v: &Value| {
+            v["results"]
+                .as_array()
+                .expect("results")
+                .iter()
+                .map(|r| r["relative_path"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(!rel_paths(&respected).contains(&"src/nested/gen/out.rs".to_string()));
+        assert!(rel_paths(&respected).contains(&"src/nested/mod.rs".to_string()));
+
+        let unfiltered = run_glob(&json!({
+            "path": root,
+            "pattern": "**/*.rs",
+            "respect_gitignore": false
+        }))
+        .expect("glob");
+        assert!(rel_paths(&unfiltered).contains(&"src/nested/gen/out.rs".to_string()));
+    }
+
+    #[test]
+    fn languages_filter_uses_index_or_detection() {
+        let (_tmp, root) = setup();
+        fs::create_dir_all(root.join("bin")).expect("create bin dir");
+        fs::write(root.join("bin/release"), "#!/bin/sh\necho release\n").expect("write script");
+        let rel_paths = |v: &Value| {
+            v["results"]
+                .as_array()
+                .expect("results")
+                .iter()
+                .map(|r| r["relative_path"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Without an index, languages are detected from names, shebangs and modelines
+        let detected = run_glob(&json!({"path": root, "languages": ["sh"]})).expect("glob");
+        assert_eq!(rel_paths(&detected), vec!["bin/release".to_string()]);
+
+        // With an index, the stored language column decides
+        let mut indexer = crate::index::engine::Indexer::new(root.clone()).expect("indexer");
+        indexer.index_repository(&root).expect("index");
+        drop(indexer);
+        fs::write(root.join("bin/unindexed"), "#!/bin/sh\n").expect("write unindexed");
+
+        let indexed = run_glob(&json!({"path": root, "languages": "shell,rust"})).expect("glob");
+        let paths = rel_paths(&indexed);
+        assert!(paths.contains(&"bin/release".to_string()));
+        assert!(paths.contains(&"src/main.rs".to_string()));
+        assert!(!paths.contains(&"bin/unindexed".to_string()));
+        assert_eq!(indexed["options"]["languages"], json!(["shell", "rust"]));
+    }
+
     #[test]
     fn hidden_paths_respected_by_include_hidden_flag() {
         let (_tmp, root) = setup();
@@ -647,4 +805,17 @@ mod tests {
             .iter()
             .any(|p| p.contains(".hidden/secret.rs")));
     }
+
+    #[test]
+    fn cancelled_glob_returns_cancelled_error() {
+        use crate::progress::CancellationToken;
+
+        let (_temp, root) = setup();
+        let token = CancellationToken::new();
+        token.cancel();
+        let reporter = ProgressReporter::new(None, token);
+
+        let result = run_glob_with_progress(&json!({"path": root}), &reporter);
+        assert!(matches!(result, Err(FlashgrepError::Cancelled(_))));
+    }
 }
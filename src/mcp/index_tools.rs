@@ -0,0 +1,246 @@
+use crate::config::paths::FlashgrepPaths;
+use crate::index::doctor::DoctorReport;
+use crate::index::engine::Indexer;
+use crate::index::history::search_history;
+use crate::index::status::IndexStatus;
+use crate::progress::ProgressReporter;
+use crate::watcher::registry::WatcherRegistry;
+use crate::{FlashgrepError, FlashgrepResult};
+use serde_json::{json, Value};
+
+pub fn index_status_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {}
+    })
+}
+
+pub fn reindex_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "paths": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Files, directories or globs (repository-relative) to reindex; omit for the whole repository"
+            },
+            "force": {
+                "type": "boolean",
+                "description": "Reindex unchanged files too; without paths this clears and rebuilds the whole index"
+            }
+        }
+    })
+}
+
+pub fn doctor_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "fix": {
+                "type": "boolean",
+                "description": "Repair the inconsistencies found, file by file, without clearing the index"
+            }
+        }
+    })
+}
+
+pub fn history_search_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "query": {
+                "type": "string",
+                "description": "Text matched against commit messages, authors and changed paths, e.g. \"retry logic\""
+            },
+            "limit": {"type": "integer", "minimum": 1, "default": 20}
+        },
+        "required": ["query"]
+    })
+}
+
+/// Structured index health: counts, pending changes, last commit, watcher and failures
+pub fn index_status(paths: &FlashgrepPaths) -> FlashgrepResult<Value> {
+    let registry = WatcherRegistry::load_default()?;
+    let status = IndexStatus::collect(paths.repo_root(), &registry)?;
+    let pending_total = status.pending.total();
+    let mut payload = serde_json::to_value(&status)?;
+    payload["pending"]["total"] = json!(pending_total);
+    payload["ok"] = json!(true);
+    Ok(payload)
+}
+
+/// Reindex selected paths, or the whole repository, streaming progress through `progress`
+pub fn reindex(
+    paths: &FlashgrepPaths,
+    arguments: &Value,
+    progress: &ProgressReporter,
+) -> FlashgrepResult<Value> {
+    let selectors = match arguments.get("paths") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|v| {
+                v.as_str().map(ToString::to_string).ok_or_else(|| {
+                    FlashgrepError::Config("paths must be an array of strings".to_string())
+                })
+            })
+            .collect::<FlashgrepResult<Vec<_>>>()?,
+        Some(_) => {
+            return Err(FlashgrepError::Config(
+                "paths must be an array of strings".to_string(),
+            ))
+        }
+    };
+    let force = arguments
+        .get("force")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let repo_root = paths.repo_root().to_path_buf();
+    let mut indexer = Indexer::new(repo_root.clone())?;
+
+    if selectors.is_empty() {
+        if force {
+            indexer.clear_index()?;
+        }
+        let stats = indexer.index_repository_with_progress(&repo_root, progress)?;
+        return Ok(json!({
+            "ok": true,
+            "scope": "repository",
+            "force": force,
+            "total_files": stats.total_files,
+            "total_chunks": stats.total_chunks,
+            "total_symbols": stats.total_symbols,
+            "failed_files": indexer.db().get_index_failures()?.len(),
+        }));
+    }
+
+    let summary = indexer.reindex_paths(&repo_root, &selectors, force, progress)?;
+    let mut payload = serde_json::to_value(&summary)?;
+    payload["ok"] = json!(summary.failed == 0);
+    payload["scope"] = json!("paths");
+    payload["force"] = json!(force);
+    payload["paths"] = json!(selectors);
+    Ok(payload)
+}
+
+/// Cross-check the index stores and optionally repair them
+pub fn doctor(paths: &FlashgrepPaths, arguments: &Value) -> FlashgrepResult<Value> {
+    let fix = arguments
+        .get("fix")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let repo_root = paths.repo_root();
+    let report = DoctorReport::check(repo_root)?;
+    if !report.index_exists {
+        return Err(FlashgrepError::IndexNotFound(repo_root.to_path_buf()));
+    }
+
+    let (repairs, remaining) = if fix && !report.is_healthy() {
+        let mut indexer = Indexer::new(repo_root.to_path_buf())?;
+        let repairs = report.repair(&mut indexer)?;
+        drop(indexer);
+        (Some(repairs), DoctorReport::check(repo_root)?)
+    } else {
+        (None, report.clone())
+    };
+
+    Ok(json!({
+        "ok": remaining.is_healthy(),
+        "healthy": remaining.is_healthy(),
+        "fix": fix,
+        "issues": report.issue_count(),
+        "report": report,
+        "repairs": repairs,
+        "remaining_issues": remaining.issue_count(),
+    }))
+}
+
+/// Search git history, updating the history index from the last indexed commit first
+pub fn history_search(paths: &FlashgrepPaths, arguments: &Value) -> FlashgrepResult<Value> {
+    let query = arguments
+        .get("query")
+        .and_then(Value::as_str)
+        .filter(|query| !query.trim().is_empty())
+        .ok_or_else(|| FlashgrepError::Config("Missing query parameter".to_string()))?;
+    let limit = arguments.get("limit").and_then(Value::as_u64).unwrap_or(20) as usize;
+
+    let (update, hits) = search_history(paths, query, limit)?;
+    Ok(json!({
+        "ok": true,
+        "query": query,
+        "head": update.head,
+        "added_commits": update.added_commits,
+        "rebuilt": update.rebuilt,
+        "stale": update.stale,
+        "commits": hits,
+        "total": hits.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reindex_paths_and_full_rebuild() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::write(root.join("src/lib.rs"), "pub fn lib() {}\n")?;
+        std::fs::write(root.join("main.py"), "def main(): pass\n")?;
+        let paths = FlashgrepPaths::new(&root);
+
+        let full = reindex(
+            &paths,
+            &json!({"force": true}),
+            &ProgressReporter::disabled(),
+        )?;
+        assert_eq!(full["scope"], json!("repository"));
+        assert_eq!(full["total_files"], json!(2));
+
+        let partial = reindex(
+            &paths,
+            &json!({"paths": ["src/**/*.rs"], "force": true}),
+            &ProgressReporter::disabled(),
+        )?;
+        assert_eq!(partial["scope"], json!("paths"));
+        assert_eq!(partial["matched"], json!(1));
+        assert_eq!(partial["indexed"], json!(1));
+        Ok(())
+    }
+
+    #[test]
+    fn reindex_rejects_non_string_paths() {
+        let temp = TempDir::new().expect("temp dir");
+        let paths = FlashgrepPaths::new(temp.path());
+        let err = reindex(
+            &paths,
+            &json!({"paths": [1]}),
+            &ProgressReporter::disabled(),
+        );
+        assert!(matches!(err, Err(FlashgrepError::Config(_))));
+    }
+
+    #[test]
+    fn doctor_fix_removes_deleted_files() -> FlashgrepResult<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+        std::fs::write(root.join("main.py"), "def main(): pass\n")?;
+        std::fs::write(root.join("old.py"), "def old(): pass\n")?;
+        let paths = FlashgrepPaths::new(&root);
+        reindex(&paths, &json!({}), &ProgressReporter::disabled())?;
+        std::fs::remove_file(root.join("old.py"))?;
+
+        let check = doctor(&paths, &json!({}))?;
+        assert_eq!(check["healthy"], json!(false));
+        assert_eq!(check["issues"], json!(1));
+        assert!(check["repairs"].is_null());
+
+        let fixed = doctor(&paths, &json!({"fix": true}))?;
+        assert_eq!(fixed["healthy"], json!(true));
+        assert_eq!(fixed["repairs"]["removed_files"], json!(1));
+        Ok(())
+    }
+}
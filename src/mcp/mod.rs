@@ -2,6 +2,7 @@ pub mod bootstrap;
 pub mod code_io;
 pub mod fs_tools;
 pub mod glob_tool;
+pub mod index_tools;
 pub mod safety;
 pub mod skill;
 pub mod stdio;
@@ -10,6 +11,8 @@ pub mod tools;
 use crate::config::paths::FlashgrepPaths;
 use crate::config::Config;
 use crate::db::Database;
+use crate::index::schema::open_text_index;
+use crate::index::stats::DetailedStats;
 use crate::mcp::bootstrap::{
     build_bootstrap_payload, evaluate_ai_discovery_fallback, evaluate_policy_route,
     is_bootstrap_tool, policy_denied_payload, prompt_budget_telemetry,
@@ -20,12 +23,16 @@ use crate::mcp::fs_tools::{
     fs_copy, fs_create, fs_list, fs_move, fs_read, fs_remove, fs_stat, fs_write,
 };
 use crate::mcp::glob_tool::run_glob;
+use crate::mcp::index_tools::{doctor, history_search, index_status, reindex};
 use crate::mcp::safety::{
     check_arguments_size, chunking_guidance, invalid_params_error, map_error_with_not_found,
     payload_too_large_error, MAX_MCP_GET_SLICE_BYTES, MAX_MCP_REQUEST_BYTES,
     MAX_MCP_RESPONSE_BYTES,
 };
-use crate::search::{QueryOptions, QueryRetrievalMode, Searcher};
+use crate::progress::ProgressReporter;
+use crate::search::{
+    languages_from_mcp_args, resolve_language_files, QueryOptions, QueryRetrievalMode, Searcher,
+};
 use crate::FlashgrepResult;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -84,7 +91,7 @@ async fn handle_connection(mut stream: TcpStream, paths: FlashgrepPaths) -> Flas
     let mut line = String::new();
 
     // Open Tantivy index for searching
-    let tantivy_index = match tantivy::Index::open_in_dir(paths.text_index_dir()) {
+    let tantivy_index = match open_text_index(&paths.text_index_dir()) {
         Ok(idx) => Some(idx),
         Err(e) => {
             error!("Failed to open Tantivy index: {}", e);
@@ -256,6 +263,10 @@ async fn handle_request(
                                         "symbol_name": r.symbol_name,
                                         "relevance_score": r.relevance_score,
                                         "preview": r.preview,
+                                        "revision": r.revision,
+                                        "changed_hunks": r.changed_hunks,
+                                        "blame": r.blame,
+                                        "also_in": r.also_in,
                                     })
                                 })
                                 .collect();
@@ -647,8 +658,13 @@ async fn handle_request(
                     "error": "Missing symbol_name parameter",
                 }))
             } else {
-                let db = Database::open(&paths.metadata_db())?;
-                let symbols = db.find_symbols_by_name(symbol_name)?;
+                let db = Database::open_read_only(&paths.metadata_db())?;
+                let config = Config::from_file(&paths.config_file()).unwrap_or_default();
+                let languages = languages_from_mcp_args(&request.params)?;
+                let mut symbols = db.find_symbols_by_name(symbol_name)?;
+                if let Some(files) = resolve_language_files(&db, &config, &languages)? {
+                    symbols.retain(|s| files.contains(&s.file_path));
+                }
 
                 let json_symbols: Vec<_> = symbols
                     .iter()
@@ -670,7 +686,7 @@ async fn handle_request(
             }
         }
         "list_files" => {
-            let db = Database::open(&paths.metadata_db())?;
+            let db = Database::open_read_only(&paths.metadata_db())?;
             let files = db.get_all_files()?;
 
             let file_strings: Vec<String> = files
@@ -683,18 +699,26 @@ async fn handle_request(
                 "total": files.len(),
             }))
         }
+        "index_status" => {
+            Some(index_status(paths).unwrap_or_else(|e| invalid_params_error(&e.to_string())))
+        }
+        "reindex" => Some(
+            reindex(paths, &request.params, &ProgressReporter::disabled())
+                .unwrap_or_else(|e| invalid_params_error(&e.to_string())),
+        ),
+        "doctor" => Some(
+            doctor(paths, &request.params).unwrap_or_else(|e| invalid_params_error(&e.to_string())),
+        ),
+        "history_search" => Some(
+            history_search(paths, &request.params)
+                .unwrap_or_else(|e| invalid_params_error(&e.to_string())),
+        ),
         "stats" => {
-            let db = Database::open(&paths.metadata_db())?;
-            let stats = db.get_stats()?;
-
-            Some(serde_json::json!({
-                "total_files": stats.total_files,
-                "total_chunks": stats.total_chunks,
-                "total_symbols": stats.total_symbols,
-                "index_size_bytes": stats.index_size_bytes,
-                "index_size_mb": stats.index_size_bytes / 1024 / 1024,
-                "last_update": stats.last_update,
-            }))
+            let stats = DetailedStats::collect(paths)?;
+            let mut payload = serde_json::to_value(&stats)?;
+            payload["index_size_mb"] =
+                serde_json::json!(stats.totals.index_size_bytes / 1024 / 1024);
+            Some(payload)
         }
         // New MCP tool methods
         "search" => {
@@ -19,6 +19,8 @@ pub const REASON_INTERNAL_ERROR: &str = "internal_error";
 pub const REASON_BATCH_DUPLICATE_OPERATION_ID: &str = "duplicate_operation_id";
 pub const REASON_BATCH_DUPLICATE_TARGET: &str = "duplicate_target";
 pub const REASON_BATCH_OVERLAPPING_OPERATIONS: &str = "overlapping_operations";
+pub const REASON_CANCELLED: &str = "cancelled";
+pub const REASON_CANCELLED_ROLLED_BACK: &str = "cancelled_rolled_back";
 pub const REASON_FALLBACK_GATE_REQUIRED: &str = "fallback_gate_required";
 pub const REASON_UNSUPPORTED_FALLBACK_REASON_CODE: &str = "unsupported_fallback_reason_code";
 pub const REASON_FALLBACK_GATE_MISMATCH: &str = "fallback_gate_mismatch";
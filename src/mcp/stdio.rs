@@ -6,6 +6,8 @@
 use crate::config::paths::FlashgrepPaths;
 use crate::config::Config;
 use crate::db::Database;
+use crate::index::schema::open_text_index;
+use crate::index::stats::DetailedStats;
 use crate::mcp::bootstrap::{
     build_bootstrap_payload, evaluate_ai_discovery_fallback, evaluate_policy_route,
     is_bootstrap_tool, policy_denied_payload, prompt_budget_telemetry,
@@ -13,26 +15,35 @@ use crate::mcp::bootstrap::{
     CANONICAL_BOOTSTRAP_TRIGGER,
 };
 use crate::mcp::code_io::{
-    batch_write_code, batch_write_code_input_schema, read_code, read_code_input_schema, write_code,
-    write_code_input_schema,
+    batch_write_code_input_schema, batch_write_code_with_progress, read_code,
+    read_code_input_schema, write_code, write_code_input_schema,
 };
 use crate::mcp::fs_tools::{
     fs_copy, fs_create, fs_list, fs_move, fs_read, fs_remove, fs_stat, fs_write,
 };
-use crate::mcp::glob_tool::{glob_input_schema, run_glob};
+use crate::mcp::glob_tool::{glob_input_schema, run_glob_with_progress};
+use crate::mcp::index_tools::{
+    doctor, doctor_input_schema, history_search, history_search_input_schema, index_status,
+    index_status_input_schema, reindex, reindex_input_schema,
+};
 use crate::mcp::safety::{
-    check_arguments_size, chunking_guidance, map_error_with_not_found, not_found_error,
-    payload_too_large_error, MAX_MCP_GET_SLICE_BYTES, MAX_MCP_REQUEST_BYTES,
-    MAX_MCP_RESPONSE_BYTES,
+    check_arguments_size, chunking_guidance, invalid_params_error, map_error_with_not_found,
+    not_found_error, payload_too_large_error, MAX_MCP_GET_SLICE_BYTES, MAX_MCP_REQUEST_BYTES,
+    MAX_MCP_RESPONSE_BYTES, REASON_CANCELLED,
 };
 use crate::mcp::tools::{create_bootstrap_tools, create_tools};
-use crate::search::{QueryOptions, QueryRetrievalMode, Searcher};
-use crate::FlashgrepResult;
+use crate::progress::{CancellationToken, ProgressReporter, ProgressSink};
+use crate::search::{
+    languages_from_mcp_args, resolve_language_files, QueryOptions, QueryRetrievalMode, Searcher,
+};
+use crate::{FlashgrepError, FlashgrepResult};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc, Mutex};
 use tracing::{debug, error, info, warn};
 
 /// MCP Server using stdio transport
@@ -52,17 +63,15 @@ impl McpStdioServer {
     }
 
     /// Start the MCP server on stdio
+    ///
+    /// Requests are read on a dedicated thread so that `notifications/cancelled`
+    /// can flag in-flight work while the main thread is still executing a tool.
     pub fn start(&self) -> FlashgrepResult<()> {
         info!("Starting MCP server on stdio");
         eprintln!("MCP server started on stdio");
 
-        let stdin = io::stdin();
-        let stdout = io::stdout();
-        let mut stdout_lock = stdout.lock();
-        let reader = stdin.lock();
-
         // Open Tantivy index for searching
-        let tantivy_index = match tantivy::Index::open_in_dir(self.paths.text_index_dir()) {
+        let tantivy_index = match open_text_index(&self.paths.text_index_dir()) {
             Ok(idx) => Some(idx),
             Err(e) => {
                 warn!("Failed to open Tantivy index: {}", e);
@@ -70,78 +79,70 @@ impl McpStdioServer {
             }
         };
 
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(e) => {
-                    error!("Error reading line: {}", e);
+        let cancellations = CancellationRegistry::default();
+        let (tx, rx) = mpsc::channel::<IncomingMessage>();
+        let reader_cancellations = cancellations.clone();
+        std::thread::spawn(move || read_incoming(io::stdin().lock(), tx, reader_cancellations));
+
+        for message in rx {
+            let request = match message {
+                IncomingMessage::Request(request) => request,
+                IncomingMessage::Reply(response) => {
+                    write_response_line(&mut io::stdout().lock(), &response)?;
                     continue;
                 }
             };
 
-            let trimmed_line = line.trim();
-            if trimmed_line.is_empty() {
-                continue;
-            }
+            let request_id = request.id.clone();
+            let cancellation = request_id
+                .as_ref()
+                .map(|id| cancellations.token_for(id))
+                .unwrap_or_default();
+            let progress = progress_reporter_for(&request, cancellation.clone());
 
-            if trimmed_line.len() > MAX_MCP_REQUEST_BYTES {
-                let error_response = JsonRpcResponse {
-                    jsonrpc: "2.0".to_string(),
-                    id: None,
-                    result: Some(payload_too_large_error(
-                        "request",
-                        trimmed_line.len(),
-                        MAX_MCP_REQUEST_BYTES,
-                        &chunking_guidance(MAX_MCP_REQUEST_BYTES),
-                    )),
-                    error: None,
-                };
-                write_response_line(&mut stdout_lock, &error_response)?;
-                continue;
-            }
-
-            debug!("Received: {}", trimmed_line);
-
-            match serde_json::from_str::<JsonRpcRequest>(&line) {
-                Ok(request) => {
-                    let response = match self.handle_request(request, tantivy_index.as_ref()) {
-                        Ok(r) => r,
-                        Err(e) => JsonRpcResponse {
-                            jsonrpc: "2.0".to_string(),
-                            id: None,
-                            result: Some(json!({
-                                "error": "invalid_params",
-                                "message": format!("request_failed: {}", e),
-                            })),
-                            error: None,
-                        },
-                    };
-                    write_response_line(&mut stdout_lock, &response)?;
-                }
-                Err(e) => {
-                    error!("Failed to parse JSON-RPC request: {}", e);
-                    let error_response = JsonRpcResponse {
+            let response =
+                match self.handle_request_with_progress(request, tantivy_index.as_ref(), &progress)
+                {
+                    Ok(r) => r,
+                    Err(e) => JsonRpcResponse {
                         jsonrpc: "2.0".to_string(),
                         id: None,
-                        result: None,
-                        error: Some(JsonRpcError {
-                            code: -32700,
-                            message: "Parse error".to_string(),
-                            data: None,
-                        }),
-                    };
-                    write_response_line(&mut stdout_lock, &error_response)?;
-                }
+                        result: Some(json!({
+                            "error": "invalid_params",
+                            "message": format!("request_failed: {}", e),
+                        })),
+                        error: None,
+                    },
+                };
+
+            if let Some(id) = &request_id {
+                cancellations.remove(id);
+            }
+            // Cancelled requests get no response, per the MCP cancellation contract.
+            if cancellation.is_cancelled() {
+                info!("Request {:?} cancelled by client", request_id);
+                continue;
             }
+            write_response_line(&mut io::stdout().lock(), &response)?;
         }
 
         Ok(())
     }
 
+    #[cfg(test)]
     fn handle_request(
         &self,
         request: JsonRpcRequest,
         tantivy_index: Option<&tantivy::Index>,
+    ) -> FlashgrepResult<JsonRpcResponse> {
+        self.handle_request_with_progress(request, tantivy_index, &ProgressReporter::disabled())
+    }
+
+    fn handle_request_with_progress(
+        &self,
+        request: JsonRpcRequest,
+        tantivy_index: Option<&tantivy::Index>,
+        progress: &ProgressReporter,
     ) -> FlashgrepResult<JsonRpcResponse> {
         let result = match request.method.as_str() {
             "initialize" => {
@@ -185,14 +186,18 @@ impl McpStdioServer {
                             "properties": {
                                 "text": {"type": "string", "description": "Search text"},
                                 "limit": {"type": "integer", "description": "Maximum results", "default": 10},
-                                "mode": {"type": "string", "enum": ["smart", "literal", "regex"], "default": "smart"},
+                                "mode": {"type": "string", "enum": ["smart", "literal", "regex", "structured"], "default": "smart", "description": "structured accepts path:, lang:, sym:, case:, \"phrases\" and -exclusions"},
                                 "retrieval_mode": {"type": "string", "enum": ["lexical", "neural"], "default": "lexical"},
                                 "case_sensitive": {"type": "boolean", "default": true},
                                 "regex_flags": {"type": "string", "description": "Regex flags (e.g. i for case-insensitive)"},
                                 "include": {"type": "array", "items": {"type": "string"}},
                                 "exclude": {"type": "array", "items": {"type": "string"}},
+                                "languages": {"type": "array", "items": {"type": "string"}, "description": "Language ids or aliases resolved through the indexed language column (e.g. rust, go, shell)"},
                                 "context": {"type": "integer", "minimum": 0, "default": 0},
-                                "offset": {"type": "integer", "minimum": 0, "default": 0}
+                                "offset": {"type": "integer", "minimum": 0, "default": 0},
+                                "rev": {"type": "string", "description": "Search a git branch, tag or commit indexed with `flashgrep index --rev` instead of the working tree"},
+                                "changed_since": {"type": "string", "description": "Git ref; only search files changed since it forked off HEAD (HEAD: uncommitted changes). Results list the changed hunks they overlap"},
+                                "blame": {"type": "boolean", "default": false, "description": "Attach the last commit hash, author, date and summary touching each result's lines"}
                             },
                             "required": ["text"]
                         }
@@ -237,7 +242,10 @@ impl McpStdioServer {
                         "description": "Find symbol definitions",
                         "inputSchema": {
                             "type": "object",
-                            "properties": {"symbol_name": {"type": "string"}},
+                            "properties": {
+                                "symbol_name": {"type": "string"},
+                                "languages": {"type": "array", "items": {"type": "string"}, "description": "Language ids or aliases resolved through the indexed language column (e.g. rust, go, shell)"}
+                            },
                             "required": ["symbol_name"]
                         }
                     }),
@@ -248,15 +256,32 @@ impl McpStdioServer {
                     }),
                     json!({
                         "name": "stats",
-                        "description": "Get index statistics",
+                        "description": "Get index statistics with per-language, per-directory, symbol type, chunk size and vector coverage breakdowns",
                         "inputSchema": {"type": "object", "properties": {}}
                     }),
+                    json!({
+                        "name": "index_status",
+                        "description": "Structured index health: counts, pending changes, last commit, watcher and failed files",
+                        "inputSchema": index_status_input_schema()
+                    }),
+                    json!({
+                        "name": "reindex",
+                        "description": "Reindex paths/globs or the whole repository (force rebuilds) with progress",
+                        "inputSchema": reindex_input_schema()
+                    }),
+                    json!({
+                        "name": "doctor",
+                        "description": "Cross-check SQLite, the text index, index state and the filesystem; repair with fix=true",
+                        "inputSchema": doctor_input_schema()
+                    }),
+                    json!({
+                        "name": "history_search",
+                        "description": "Search git commit messages, authors and changed paths; links commits to currently indexed files",
+                        "inputSchema": history_search_input_schema()
+                    }),
                 ];
 
-                for def in create_tools()
-                    .into_iter()
-                    .chain(create_bootstrap_tools().into_iter())
-                {
+                for def in create_tools().into_iter().chain(create_bootstrap_tools()) {
                     tools.push(json!({
                         "name": def.name,
                         "description": def.description,
@@ -280,16 +305,22 @@ impl McpStdioServer {
                     .unwrap_or(serde_json::json!({}));
 
                 match tool_name {
-                    "query" => self.handle_query_tool(&arguments, tantivy_index)?,
+                    "query" => self.handle_query_tool(&arguments, tantivy_index, progress)?,
                     "ask" => self.handle_ask_tool(&arguments, tantivy_index)?,
                     "get_slice" => self.handle_get_slice_tool(&arguments)?,
                     "read_code" => self.handle_read_code_tool(&arguments)?,
                     "write_code" => self.handle_write_code_tool(&arguments)?,
-                    "batch_write_code" => self.handle_batch_write_code_tool(&arguments)?,
-                    "glob" => self.handle_glob_tool(&arguments)?,
+                    "batch_write_code" => {
+                        self.handle_batch_write_code_tool(&arguments, progress)?
+                    }
+                    "glob" => self.handle_glob_tool(&arguments, progress)?,
                     "get_symbol" => self.handle_get_symbol_tool(&arguments)?,
                     "list_files" => self.handle_list_files_tool()?,
                     "stats" => self.handle_stats_tool()?,
+                    "index_status" => self.handle_index_status_tool()?,
+                    "reindex" => self.handle_reindex_tool(&arguments, progress)?,
+                    "doctor" => self.handle_doctor_tool(&arguments)?,
+                    "history_search" => self.handle_history_search_tool(&arguments)?,
                     "search" => {
                         self.handle_policy_gated_fallback_tool("search", &arguments, |s| {
                             self.handle_search_tool(s)
@@ -361,6 +392,7 @@ impl McpStdioServer {
         &self,
         arguments: &Value,
         tantivy_index: Option<&tantivy::Index>,
+        progress: &ProgressReporter,
     ) -> FlashgrepResult<Option<Value>> {
         let decision = evaluate_policy_route("query", arguments);
         if decision.route_state == PolicyRouteState::Denied {
@@ -400,19 +432,63 @@ impl McpStdioServer {
         }
 
         if let Some(index) = tantivy_index {
-            let searcher = Searcher::new(index, &self.paths.metadata_db())?;
+            let searcher =
+                Searcher::new(index, &self.paths.metadata_db())?.with_progress(progress.clone());
             match searcher.query_with_options(&options) {
                 Ok(response) => {
                     let text_results: Vec<String> = response
                         .results
                         .iter()
                         .map(|r| {
+                            let changed = if r.changed_hunks.is_empty() {
+                                String::new()
+                            } else {
+                                let hunks: Vec<String> = r
+                                    .changed_hunks
+                                    .iter()
+                                    .map(|h| format!("{}-{}", h.start_line, h.end_line))
+                                    .collect();
+                                format!(" [changed: {}]", hunks.join(","))
+                            };
+                            let blame = r
+                                .blame
+                                .as_ref()
+                                .map(|b| {
+                                    format!(
+                                        "\nblame: {} {} {} {}",
+                                        &b.commit_id[..b.commit_id.len().min(12)],
+                                        b.author,
+                                        b.date,
+                                        b.summary
+                                    )
+                                })
+                                .unwrap_or_default();
+                            let also_in = if r.also_in.is_empty() {
+                                String::new()
+                            } else {
+                                let locations: Vec<String> = r
+                                    .also_in
+                                    .iter()
+                                    .map(|l| {
+                                        format!(
+                                            "{}:{}-{}",
+                                            l.file_path.display(),
+                                            l.start_line,
+                                            l.end_line
+                                        )
+                                    })
+                                    .collect();
+                                format!("\nalso appears in: {}", locations.join(", "))
+                            };
                             format!(
-                                "{}:{}-{} (score: {:.2})\n{}",
+                                "{}:{}-{} (score: {:.2}){}{}{}\n{}",
                                 r.file_path.display(),
                                 r.start_line,
                                 r.end_line,
                                 r.relevance_score,
+                                changed,
+                                blame,
+                                also_in,
                                 r.preview
                             )
                         })
@@ -427,6 +503,9 @@ impl McpStdioServer {
                         "mode": format!("{:?}", options.mode).to_lowercase(),
                         "retrieval_mode": format!("{:?}", options.retrieval_mode).to_lowercase(),
                         "case_sensitive": options.case_sensitive,
+                        "languages": options.languages,
+                        "rev": options.revision,
+                        "changed_since": options.changed_since,
                         "route_state": route_decision.as_str(),
                         "reason_code": route_decision.reason_code,
                         "fallback_gate_id": route_decision.fallback_gate_id,
@@ -451,6 +530,9 @@ impl McpStdioServer {
                         "content": [{"type": "text", "text": serde_json::to_string(&payload)?}]
                     })))
                 }
+                Err(e @ FlashgrepError::Cancelled(_)) => {
+                    Self::as_tool_envelope(cancelled_payload(&e))
+                }
                 Err(e) => Ok(Some(serde_json::json!({
                     "content": [{"type": "text", "text": format!("Search error: {}", e)}],
                     "isError": true
@@ -718,8 +800,21 @@ impl McpStdioServer {
             })));
         }
 
-        let db = Database::open(&self.paths.metadata_db())?;
-        match db.find_symbols_by_name(symbol_name) {
+        let languages = match languages_from_mcp_args(arguments) {
+            Ok(languages) => languages,
+            Err(e) => return Ok(Some(invalid_params_error(&e.to_string()))),
+        };
+        let db = Database::open_read_only(&self.paths.metadata_db())?;
+        let config = Config::from_file(&self.paths.config_file()).unwrap_or_default();
+        let found = db
+            .find_symbols_by_name(symbol_name)
+            .and_then(|mut symbols| {
+                if let Some(files) = resolve_language_files(&db, &config, &languages)? {
+                    symbols.retain(|s| files.contains(&s.file_path));
+                }
+                Ok(symbols)
+            });
+        match found {
             Ok(symbols) => {
                 let text: Vec<String> = symbols
                     .iter()
@@ -746,9 +841,16 @@ impl McpStdioServer {
         }
     }
 
-    fn handle_glob_tool(&self, arguments: &Value) -> FlashgrepResult<Option<Value>> {
-        let payload = match run_glob(arguments) {
+    fn handle_glob_tool(
+        &self,
+        arguments: &Value,
+        progress: &ProgressReporter,
+    ) -> FlashgrepResult<Option<Value>> {
+        let payload = match run_glob_with_progress(arguments, progress) {
             Ok(payload) => payload,
+            Err(e @ FlashgrepError::Cancelled(_)) => {
+                return Self::as_tool_envelope(cancelled_payload(&e));
+            }
             Err(e) => {
                 if let Some(path) = arguments.get("path").and_then(Value::as_str) {
                     if !std::path::Path::new(path).exists() {
@@ -799,7 +901,11 @@ impl McpStdioServer {
         Self::as_tool_envelope(payload)
     }
 
-    fn handle_batch_write_code_tool(&self, arguments: &Value) -> FlashgrepResult<Option<Value>> {
+    fn handle_batch_write_code_tool(
+        &self,
+        arguments: &Value,
+        progress: &ProgressReporter,
+    ) -> FlashgrepResult<Option<Value>> {
         if let Err(e) = check_arguments_size(arguments, MAX_MCP_REQUEST_BYTES) {
             return Ok(Some(json!({
                 "content": [{"type": "text", "text": serde_json::to_string(&json!({"error": "invalid_params", "message": e.to_string()}))?}],
@@ -807,7 +913,7 @@ impl McpStdioServer {
             })));
         }
 
-        let payload = match batch_write_code(arguments) {
+        let payload = match batch_write_code_with_progress(arguments, progress) {
             Ok(v) => v,
             Err(e) => {
                 return Self::as_tool_envelope(map_error_with_not_found(
@@ -1129,7 +1235,7 @@ impl McpStdioServer {
     }
 
     fn handle_list_files_tool(&self) -> FlashgrepResult<Option<Value>> {
-        let db = Database::open(&self.paths.metadata_db())?;
+        let db = Database::open_read_only(&self.paths.metadata_db())?;
         match db.get_all_files() {
             Ok(files) => {
                 let file_list: Vec<String> = files
@@ -1147,25 +1253,43 @@ impl McpStdioServer {
         }
     }
 
+    fn handle_index_status_tool(&self) -> FlashgrepResult<Option<Value>> {
+        match index_status(&self.paths) {
+            Ok(payload) => Self::as_tool_envelope(payload),
+            Err(e) => Self::as_tool_envelope(invalid_params_error(&e.to_string())),
+        }
+    }
+
+    fn handle_reindex_tool(
+        &self,
+        arguments: &Value,
+        progress: &ProgressReporter,
+    ) -> FlashgrepResult<Option<Value>> {
+        match reindex(&self.paths, arguments, progress) {
+            Ok(payload) => Self::as_tool_envelope(payload),
+            Err(e @ FlashgrepError::Cancelled(_)) => Self::as_tool_envelope(cancelled_payload(&e)),
+            Err(e) => Self::as_tool_envelope(invalid_params_error(&e.to_string())),
+        }
+    }
+
+    fn handle_doctor_tool(&self, arguments: &Value) -> FlashgrepResult<Option<Value>> {
+        match doctor(&self.paths, arguments) {
+            Ok(payload) => Self::as_tool_envelope(payload),
+            Err(e) => Self::as_tool_envelope(invalid_params_error(&e.to_string())),
+        }
+    }
+
+    fn handle_history_search_tool(&self, arguments: &Value) -> FlashgrepResult<Option<Value>> {
+        match history_search(&self.paths, arguments) {
+            Ok(payload) => Self::as_tool_envelope(payload),
+            Err(e) => Self::as_tool_envelope(invalid_params_error(&e.to_string())),
+        }
+    }
+
     fn handle_stats_tool(&self) -> FlashgrepResult<Option<Value>> {
-        let db = Database::open(&self.paths.metadata_db())?;
-        match db.get_stats() {
-            Ok(stats) => {
-                let text = format!(
-                    "Files: {}\nChunks: {}\nSymbols: {}\nIndex size: {} MB\n",
-                    stats.total_files,
-                    stats.total_chunks,
-                    stats.total_symbols,
-                    stats.index_size_bytes / 1024 / 1024
-                );
-                Ok(Some(serde_json::json!({
-                    "content": [{"type": "text", "text": text}]
-                })))
-            }
-            Err(e) => Ok(Some(serde_json::json!({
-                "content": [{"type": "text", "text": format!("Error: {}", e)}],
-                "isError": true
-            }))),
+        match DetailedStats::collect(&self.paths) {
+            Ok(stats) => Self::as_tool_envelope(serde_json::to_value(&stats)?),
+            Err(e) => Self::as_tool_envelope(invalid_params_error(&e.to_string())),
         }
     }
 }
@@ -1178,7 +1302,7 @@ fn write_response_line<W: Write>(
     if response_json.len() > MAX_MCP_RESPONSE_BYTES {
         let fallback = JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
-            id: response.id,
+            id: response.id.clone(),
             result: Some(payload_too_large_error(
                 "response",
                 response_json.len(),
@@ -1195,6 +1319,179 @@ fn write_response_line<W: Write>(
     Ok(())
 }
 
+/// Message handed from the stdin reader thread to the request loop
+enum IncomingMessage {
+    Request(JsonRpcRequest),
+    Reply(JsonRpcResponse),
+}
+
+/// Cancellation tokens for requests that are queued or in flight, keyed by request id
+#[derive(Clone, Default)]
+struct CancellationRegistry {
+    tokens: Arc<Mutex<HashMap<RequestId, CancellationToken>>>,
+}
+
+impl CancellationRegistry {
+    fn token_for(&self, id: &RequestId) -> CancellationToken {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.entry(id.clone()).or_default().clone()
+    }
+
+    fn cancel(&self, id: &RequestId) {
+        // Registering on cancel also covers a notification racing ahead of its request.
+        self.token_for(id).cancel();
+    }
+
+    fn remove(&self, id: &RequestId) {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.remove(id);
+    }
+}
+
+fn read_incoming<R: BufRead>(
+    reader: R,
+    tx: mpsc::Sender<IncomingMessage>,
+    cancellations: CancellationRegistry,
+) {
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                error!("Error reading line: {}", e);
+                continue;
+            }
+        };
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() {
+            continue;
+        }
+
+        let message = if trimmed_line.len() > MAX_MCP_REQUEST_BYTES {
+            IncomingMessage::Reply(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id: None,
+                result: Some(payload_too_large_error(
+                    "request",
+                    trimmed_line.len(),
+                    MAX_MCP_REQUEST_BYTES,
+                    &chunking_guidance(MAX_MCP_REQUEST_BYTES),
+                )),
+                error: None,
+            })
+        } else {
+            debug!("Received: {}", trimmed_line);
+            match serde_json::from_str::<JsonRpcRequest>(trimmed_line) {
+                Ok(request) if request.method == "notifications/cancelled" => {
+                    let request_id = request
+                        .params
+                        .get("requestId")
+                        .cloned()
+                        .and_then(|id| serde_json::from_value::<RequestId>(id).ok());
+                    match request_id {
+                        Some(id) => {
+                            info!(
+                                "Cancellation requested for {}: {:?}",
+                                id,
+                                request.params.get("reason")
+                            );
+                            cancellations.cancel(&id);
+                        }
+                        None => warn!("notifications/cancelled without a valid requestId"),
+                    }
+                    continue;
+                }
+                Ok(request) => {
+                    if let Some(id) = &request.id {
+                        cancellations.token_for(id);
+                    }
+                    IncomingMessage::Request(request)
+                }
+                Err(e) => {
+                    error!("Failed to parse JSON-RPC request: {}", e);
+                    IncomingMessage::Reply(JsonRpcResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: None,
+                        result: None,
+                        error: Some(JsonRpcError {
+                            code: -32700,
+                            message: "Parse error".to_string(),
+                            data: None,
+                        }),
+                    })
+                }
+            }
+        };
+
+        if tx.send(message).is_err() {
+            break;
+        }
+    }
+}
+
+/// Build the progress reporter for a request, emitting `notifications/progress`
+/// on stdout when the client supplied `params._meta.progressToken`.
+fn progress_reporter_for(
+    request: &JsonRpcRequest,
+    cancellation: CancellationToken,
+) -> ProgressReporter {
+    let sink: Option<ProgressSink> = request
+        .params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+        .map(|token| -> ProgressSink {
+            Arc::new(move |progress, total, message| {
+                let notification = progress_notification(&token, progress, total, message);
+                if let Err(e) = write_notification_line(&mut io::stdout().lock(), &notification) {
+                    warn!("Failed to write progress notification: {}", e);
+                }
+            })
+        });
+    ProgressReporter::new(sink, cancellation)
+}
+
+fn progress_notification(
+    token: &Value,
+    progress: u64,
+    total: Option<u64>,
+    message: Option<&str>,
+) -> JsonRpcNotification {
+    let mut params = json!({
+        "progressToken": token,
+        "progress": progress,
+    });
+    if let Some(total) = total {
+        params["total"] = json!(total);
+    }
+    if let Some(message) = message {
+        params["message"] = json!(message);
+    }
+    JsonRpcNotification {
+        jsonrpc: "2.0".to_string(),
+        method: "notifications/progress".to_string(),
+        params,
+    }
+}
+
+fn cancelled_payload(err: &FlashgrepError) -> Value {
+    json!({
+        "error": "cancelled",
+        "reason_code": REASON_CANCELLED,
+        "message": err.to_string(),
+    })
+}
+
+fn write_notification_line<W: Write>(
+    writer: &mut W,
+    notification: &JsonRpcNotification,
+) -> FlashgrepResult<()> {
+    writeln!(writer, "{}", serde_json::to_string(notification)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
 #[derive(Debug, Deserialize)]
 struct JsonRpcRequest {
     #[allow(dead_code)]
@@ -1202,19 +1499,49 @@ struct JsonRpcRequest {
     method: String,
     #[serde(default)]
     params: Value,
-    id: Option<u64>,
+    id: Option<RequestId>,
+}
+
+/// JSON-RPC request id, which clients may send as a number or a string
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RequestId {
+    Number(serde_json::Number),
+    String(String),
+}
+
+impl From<u64> for RequestId {
+    fn from(id: u64) -> Self {
+        Self::Number(id.into())
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{}", number),
+            Self::String(text) => write!(f, "{:?}", text),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct JsonRpcResponse {
     jsonrpc: String,
-    id: Option<u64>,
+    id: Option<RequestId>,
     #[serde(skip_serializing_if = "Option::is_none")]
     result: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<JsonRpcError>,
 }
 
+#[derive(Debug, Serialize)]
+struct JsonRpcNotification {
+    jsonrpc: String,
+    method: String,
+    params: Value,
+}
+
 #[derive(Debug, Serialize)]
 struct JsonRpcError {
     code: i32,
@@ -1364,7 +1691,7 @@ mod tests {
             jsonrpc: "2.0".to_string(),
             method: "initialize".to_string(),
             params: json!({"clientInfo": {"name":"test","version":"1.0"}}),
-            id: Some(1),
+            id: Some(RequestId::from(1)),
         };
 
         let response = server
@@ -1390,11 +1717,14 @@ mod tests {
 
         let server = McpStdioServer::new(root.clone()).expect("server");
         let envelope = server
-            .handle_glob_tool(&json!({
-                "path": root,
-                "pattern": "**/*.rs",
-                "limit": 5
-            }))
+            .handle_glob_tool(
+                &json!({
+                    "path": root,
+                    "pattern": "**/*.rs",
+                    "limit": 5
+                }),
+                &ProgressReporter::disabled(),
+            )
             .expect("glob result")
             .expect("glob envelope");
         let payload_text = envelope["content"][0]["text"]
@@ -1559,7 +1889,7 @@ mod tests {
                     "files": [root.join("src/main.rs").to_string_lossy()]
                 }
             }),
-            id: Some(1),
+            id: Some(RequestId::from(1)),
         };
 
         let response = server.handle_request(req, None).expect("response");
@@ -1585,7 +1915,7 @@ mod tests {
             jsonrpc: "2.0".to_string(),
             method: "tools/list".to_string(),
             params: json!({}),
-            id: Some(1),
+            id: Some(RequestId::from(1)),
         };
         let response = server.handle_request(req, None).expect("response");
         let result = response.result.expect("result payload");
@@ -1626,7 +1956,7 @@ mod tests {
                     "limit": 5
                 }
             }),
-            id: Some(2),
+            id: Some(RequestId::from(2)),
         };
 
         let response = server
@@ -1677,4 +2007,84 @@ mod tests {
         );
         assert_eq!(forced["status"], Value::String("injected".to_string()));
     }
+
+    #[test]
+    fn progress_notification_carries_token_and_totals() {
+        let notification =
+            progress_notification(&json!("tok-1"), 3, Some(10), Some("batch_write_code"));
+        let value = serde_json::to_value(&notification).expect("serialize");
+        assert_eq!(value["method"], json!("notifications/progress"));
+        assert_eq!(value["params"]["progressToken"], json!("tok-1"));
+        assert_eq!(value["params"]["progress"], json!(3));
+        assert_eq!(value["params"]["total"], json!(10));
+        assert!(value.get("id").is_none());
+    }
+
+    #[test]
+    fn cancelled_notification_flags_token_without_forwarding() {
+        let input = concat!(
+            "{\"jsonrpc\":\"2.0\",\"id\":7,\"method\":\"tools/call\",\"params\":{\"name\":\"glob\"}}\n",
+            "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/cancelled\",\"params\":{\"requestId\":7}}\n"
+        );
+        let registry = CancellationRegistry::default();
+        let (tx, rx) = mpsc::channel();
+        read_incoming(io::Cursor::new(input), tx, registry.clone());
+
+        let forwarded = rx.iter().collect::<Vec<_>>();
+        assert_eq!(forwarded.len(), 1);
+        assert!(
+            matches!(&forwarded[0], IncomingMessage::Request(r) if r.id == Some(RequestId::from(7)))
+        );
+        assert!(registry.token_for(&RequestId::from(7)).is_cancelled());
+    }
+
+    #[test]
+    fn cancelled_notification_accepts_string_request_ids() {
+        let input = concat!(
+            "{\"jsonrpc\":\"2.0\",\"id\":\"req-7\",\"method\":\"tools/call\",\"params\":{\"name\":\"glob\"}}\n",
+            "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/cancelled\",\"params\":{\"requestId\":\"req-7\"}}\n"
+        );
+        let registry = CancellationRegistry::default();
+        let (tx, rx) = mpsc::channel();
+        read_incoming(io::Cursor::new(input), tx, registry.clone());
+
+        let forwarded = rx.iter().collect::<Vec<_>>();
+        assert_eq!(forwarded.len(), 1);
+        let id = RequestId::String("req-7".to_string());
+        assert!(matches!(&forwarded[0], IncomingMessage::Request(r) if r.id.as_ref() == Some(&id)));
+        assert!(registry.token_for(&id).is_cancelled());
+        assert!(!registry.token_for(&RequestId::from(7)).is_cancelled());
+
+        // Responses echo the id in the form the client sent it
+        let response = JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            result: Some(json!({})),
+            error: None,
+        };
+        let value = serde_json::to_value(&response).expect("serialize response");
+        assert_eq!(value["id"], json!("req-7"));
+    }
+
+    #[test]
+    fn cancelled_glob_call_returns_cancelled_envelope() {
+        let temp = TempDir::new().expect("temp dir");
+        let root = temp.path().to_path_buf();
+        let server = McpStdioServer::new(root.clone()).expect("server");
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let req = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "tools/call".to_string(),
+            params: json!({"name": "glob", "arguments": {"path": root}}),
+            id: Some(RequestId::from(1)),
+        };
+        let response = server
+            .handle_request_with_progress(req, None, &ProgressReporter::new(None, token))
+            .expect("response");
+        let payload = payload_text(response.result);
+        assert_eq!(payload["error"], json!("cancelled"));
+        assert_eq!(payload["reason_code"], json!(REASON_CANCELLED));
+    }
 }
@@ -209,6 +209,7 @@ fn create_glob_tool() -> ToolDefinition {
                 "include": {"type": "array", "items": {"type": "string"}},
                 "exclude": {"type": "array", "items": {"type": "string"}},
                 "extensions": {"type": "array", "items": {"type": "string"}},
+                "languages": {"type": "array", "items": {"type": "string"}},
                 "max_depth": {"type": "integer", "minimum": 0},
                 "recursive": {"type": "boolean"},
                 "include_hidden": {"type": "boolean"},
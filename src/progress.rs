@@ -0,0 +1,145 @@
+//! Progress reporting and cooperative cancellation for long-running operations
+//!
+//! Work loops (query scans, batch writes, glob walks, indexing) take a
+//! [`ProgressReporter`] and call [`ProgressReporter::checkpoint`] between units of
+//! work. The reporter forwards progress to an optional sink (the MCP transport
+//! turns these into `notifications/progress`) and surfaces cancellation as
+//! [`FlashgrepError::Cancelled`].
+
+use crate::{FlashgrepError, FlashgrepResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Callback receiving `(progress, total, message)` updates
+pub type ProgressSink = Arc<dyn Fn(u64, Option<u64>, Option<&str>) + Send + Sync>;
+
+/// Shared flag used to request cancellation of in-flight work
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new, non-cancelled token
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Reports progress for one operation and exposes its cancellation state
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    sink: Option<ProgressSink>,
+    cancellation: CancellationToken,
+}
+
+impl ProgressReporter {
+    /// Reporter that discards progress and is never cancelled
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Create a reporter with an optional sink and a cancellation token
+    pub fn new(sink: Option<ProgressSink>, cancellation: CancellationToken) -> Self {
+        Self { sink, cancellation }
+    }
+
+    /// Emit a progress update if a sink is attached
+    pub fn report(&self, progress: u64, total: Option<u64>, message: Option<&str>) {
+        if let Some(sink) = &self.sink {
+            sink(progress, total, message);
+        }
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Return an error if the operation has been cancelled
+    pub fn check_cancelled(&self, operation: &str) -> FlashgrepResult<()> {
+        if self.is_cancelled() {
+            return Err(FlashgrepError::Cancelled(operation.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check cancellation and report progress every `every` units (and at completion)
+    pub fn checkpoint(
+        &self,
+        operation: &str,
+        progress: u64,
+        total: Option<u64>,
+        every: u64,
+    ) -> FlashgrepResult<()> {
+        self.check_cancelled(operation)?;
+        self.report_every(operation, progress, total, every);
+        Ok(())
+    }
+
+    /// Report progress every `every` units (and at completion), for loops that
+    /// handle cancellation themselves
+    pub fn report_every(&self, operation: &str, progress: u64, total: Option<u64>, every: u64) {
+        let at_end = total.map(|t| progress >= t).unwrap_or(false);
+        if self.sink.is_some() && (at_end || (every > 0 && progress.is_multiple_of(every))) {
+            self.report(progress, total, Some(operation));
+        }
+    }
+}
+
+impl std::fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("has_sink", &self.sink.is_some())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn checkpoint_reports_on_interval_and_completion() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = seen.clone();
+        let sink: ProgressSink = Arc::new(move |progress, total, _| {
+            sink_seen.lock().unwrap().push((progress, total));
+        });
+        let reporter = ProgressReporter::new(Some(sink), CancellationToken::new());
+
+        for i in 1..=5 {
+            reporter.checkpoint("scan", i, Some(5), 2).unwrap();
+        }
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(2, Some(5)), (4, Some(5)), (5, Some(5))]
+        );
+    }
+
+    #[test]
+    fn cancelled_token_surfaces_cancelled_error() {
+        let token = CancellationToken::new();
+        let reporter = ProgressReporter::new(None, token.clone());
+        assert!(reporter.check_cancelled("glob").is_ok());
+
+        token.cancel();
+        match reporter.checkpoint("glob", 1, None, 1) {
+            Err(FlashgrepError::Cancelled(op)) => assert_eq!(op, "glob"),
+            other => panic!("expected cancellation, got {:?}", other),
+        }
+    }
+}
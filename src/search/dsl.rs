@@ -0,0 +1,642 @@
+//! Structured query language used by [`QueryMode::Structured`](super::QueryMode)
+//!
+//! A query is a whitespace-separated list of clauses that must all hold:
+//!
+//! - `word`, `"exact phrase"`: the chunk contains the text
+//! - `path:src/mcp` (`file:`, `f:`): the repository-relative path contains the
+//!   value, or matches it as a glob when it has `*`, `?` or `[`
+//! - `lang:rust` (`language:`, `l:`): the file has one of the given languages
+//! - `sym:handle_request` (`symbol:`): the chunk defines the symbol
+//! - `case:yes|no|auto`: case sensitivity of text and path matching; `auto`
+//!   is sensitive when a text clause has an uppercase letter
+//!
+//! Any clause but `case:` is negated with a leading `-`. Field values can be
+//! quoted to include spaces, and `\"` and `\\` escape inside quotes.
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::path_utils::normalize_path_for_matching;
+use crate::{FlashgrepError, FlashgrepResult};
+use glob::{MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A parsed structured query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuredQuery {
+    pub clauses: Vec<Clause>,
+    /// Case sensitivity from `case:`, or `None` to use the caller's default
+    pub case_sensitive: Option<bool>,
+}
+
+/// One condition of a structured query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause {
+    pub kind: ClauseKind,
+    /// Whether the clause was prefixed with `-`
+    pub negated: bool,
+    /// 1-based column of the clause in the query, for error messages
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClauseKind {
+    Text(String),
+    Path(String),
+    Language(String),
+    Symbol(String),
+}
+
+/// A query that cannot be parsed or compiled, pointing at the offending column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParseError {
+    pub query: String,
+    /// 1-based column the error refers to
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at column {}\n  {}\n  {}^",
+            self.message,
+            self.column,
+            self.query,
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
+
+impl From<QueryParseError> for FlashgrepError {
+    fn from(err: QueryParseError) -> Self {
+        FlashgrepError::Config(format!("Invalid structured query: {}", err))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Path,
+    Language,
+    Symbol,
+    Case,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "path" | "file" | "f" => Some(Field::Path),
+            "lang" | "language" | "l" => Some(Field::Language),
+            "sym" | "symbol" => Some(Field::Symbol),
+            "case" => Some(Field::Case),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a structured query
+pub fn parse(query: &str) -> Result<StructuredQuery, QueryParseError> {
+    Parser {
+        query,
+        chars: query.chars().collect(),
+        pos: 0,
+    }
+    .parse()
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> Result<StructuredQuery, QueryParseError> {
+        let mut clauses = Vec::new();
+        let mut case = None;
+
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            let Some(first) = self.peek() else {
+                break;
+            };
+            let column = self.pos + 1;
+
+            let negated = first == '-';
+            if negated {
+                self.pos += 1;
+                if self.peek().is_none_or(char::is_whitespace) {
+                    return Err(self.error(column, "'-' must be followed by a term to exclude"));
+                }
+            }
+
+            if self.peek() == Some('"') {
+                let text = self.quoted()?;
+                clauses.push(Clause {
+                    kind: ClauseKind::Text(text),
+                    negated,
+                    column,
+                });
+                continue;
+            }
+
+            let word_start = self.pos;
+            let field = self.field_prefix();
+            let Some((field, name)) = field else {
+                self.pos = word_start;
+                let text = self.bare();
+                clauses.push(Clause {
+                    kind: ClauseKind::Text(text),
+                    negated,
+                    column,
+                });
+                continue;
+            };
+
+            let value_column = self.pos + 1;
+            let value = match self.peek() {
+                Some('"') => self.quoted()?,
+                Some(c) if !c.is_whitespace() => self.bare(),
+                _ => {
+                    return Err(
+                        self.error(value_column, &format!("missing value after '{}:'", name))
+                    )
+                }
+            };
+
+            let kind = match field {
+                Field::Path => ClauseKind::Path(value),
+                Field::Language => ClauseKind::Language(value),
+                Field::Symbol => ClauseKind::Symbol(value),
+                Field::Case => {
+                    if negated {
+                        return Err(self.error(column, "'case:' cannot be negated"));
+                    }
+                    if case.is_some() {
+                        return Err(self.error(column, "'case:' is given more than once"));
+                    }
+                    case = Some(match value.to_ascii_lowercase().as_str() {
+                        "yes" => CaseSetting::Yes,
+                        "no" => CaseSetting::No,
+                        "auto" => CaseSetting::Auto,
+                        _ => {
+                            return Err(self.error(
+                                value_column,
+                                &format!(
+                                    "expected yes, no or auto after 'case:', found '{}'",
+                                    value
+                                ),
+                            ))
+                        }
+                    });
+                    continue;
+                }
+            };
+            clauses.push(Clause {
+                kind,
+                negated,
+                column,
+            });
+        }
+
+        if clauses.is_empty() {
+            return Err(self.error(self.chars.len() + 1, "query has no terms or filters"));
+        }
+
+        let case_sensitive = case.map(|setting| match setting {
+            CaseSetting::Yes => true,
+            CaseSetting::No => false,
+            CaseSetting::Auto => clauses.iter().any(|clause| {
+                matches!(&clause.kind, ClauseKind::Text(text) if text.chars().any(char::is_uppercase))
+            }),
+        });
+        Ok(StructuredQuery {
+            clauses,
+            case_sensitive,
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Consume a known `name:` prefix, leaving the position after the colon
+    fn field_prefix(&mut self) -> Option<(Field, String)> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos == start || self.peek() != Some(':') {
+            return None;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        let field = Field::from_name(&name.to_ascii_lowercase())?;
+        self.pos += 1;
+        Some((field, name))
+    }
+
+    /// Text up to the next whitespace, taken as is
+    fn bare(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// A double-quoted string starting at the current position
+    fn quoted(&mut self) -> Result<String, QueryParseError> {
+        let open = self.pos + 1;
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error(open, "unterminated quote")),
+                Some('"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some('\\') if matches!(self.chars.get(self.pos + 1), Some('"' | '\\')) => {
+                    text.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if text.is_empty() {
+            return Err(self.error(open, "empty quotes"));
+        }
+        if self.peek().is_some_and(|c| !c.is_whitespace()) {
+            return Err(self.error(self.pos + 1, "expected whitespace after closing quote"));
+        }
+        Ok(text)
+    }
+
+    fn error(&self, column: usize, message: &str) -> QueryParseError {
+        QueryParseError {
+            query: self.query.to_string(),
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum CaseSetting {
+    Yes,
+    No,
+    Auto,
+}
+
+impl StructuredQuery {
+    /// Text the chunk must contain, which the text index can look up
+    pub fn required_texts(&self) -> impl Iterator<Item = &str> {
+        self.clauses.iter().filter_map(|clause| match &clause.kind {
+            ClauseKind::Text(text) if !clause.negated => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// The clauses of a structured query that are checked against each candidate
+/// chunk after the text index lookup
+pub struct StructuredFilter {
+    texts: Vec<(String, bool)>,
+    paths: Vec<(PathMatcher, bool)>,
+    languages: Vec<(LanguageMatcher, bool)>,
+    symbols: Vec<(HashSet<(PathBuf, usize)>, bool)>,
+    case_sensitive: bool,
+}
+
+enum PathMatcher {
+    Glob(Pattern),
+    Contains(String),
+}
+
+struct LanguageMatcher {
+    ids: Vec<String>,
+    /// Indexed files with the language; revisions have none and use the path
+    files: Option<HashSet<PathBuf>>,
+}
+
+impl StructuredFilter {
+    /// Resolve a query's filters. Symbol and language lookups read the
+    /// metadata store, which only describes the working tree, so revision
+    /// searches detect languages from paths and reject `sym:`.
+    pub fn new(
+        query: &StructuredQuery,
+        query_text: &str,
+        case_sensitive: bool,
+        db: &Database,
+        config: &Config,
+        revision: bool,
+    ) -> FlashgrepResult<Self> {
+        let error = |clause: &Clause, message: String| QueryParseError {
+            query: query_text.to_string(),
+            column: clause.column,
+            message,
+        };
+        let mut filter = Self {
+            texts: Vec::new(),
+            paths: Vec::new(),
+            languages: Vec::new(),
+            symbols: Vec::new(),
+            case_sensitive,
+        };
+
+        for clause in &query.clauses {
+            match &clause.kind {
+                ClauseKind::Text(text) => {
+                    let text = if case_sensitive {
+                        text.clone()
+                    } else {
+                        text.to_lowercase()
+                    };
+                    filter.texts.push((text, clause.negated));
+                }
+                ClauseKind::Path(value) => {
+                    let value = value.replace('\\', "/");
+                    let matcher = if value.contains(['*', '?', '[']) {
+                        PathMatcher::Glob(Pattern::new(&value).map_err(|e| {
+                            error(clause, format!("invalid glob '{}': {}", value, e))
+                        })?)
+                    } else if case_sensitive {
+                        PathMatcher::Contains(value)
+                    } else {
+                        PathMatcher::Contains(value.to_lowercase())
+                    };
+                    filter.paths.push((matcher, clause.negated));
+                }
+                ClauseKind::Language(name) => {
+                    let Some(def) = config.languages.get(name) else {
+                        return Err(error(clause, format!("unknown language '{}'", name)).into());
+                    };
+                    let ids = vec![def.id.clone()];
+                    let files = if revision {
+                        None
+                    } else {
+                        Some(db.files_with_languages(&ids)?.into_iter().collect())
+                    };
+                    filter
+                        .languages
+                        .push((LanguageMatcher { ids, files }, clause.negated));
+                }
+                ClauseKind::Symbol(name) => {
+                    if revision {
+                        return Err(error(
+                            clause,
+                            "'sym:' only searches the working tree, not revisions".to_string(),
+                        )
+                        .into());
+                    }
+                    let definitions = db
+                        .find_symbols_by_name(name)?
+                        .into_iter()
+                        .map(|symbol| (symbol.file_path, symbol.line_number))
+                        .collect();
+                    filter.symbols.push((definitions, clause.negated));
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Indexed working tree files that can pass the `sym:`, `path:` and
+    /// `lang:` clauses, or `None` when the query has none of them
+    pub fn candidate_files(
+        &self,
+        db: &Database,
+        repo_root: &Path,
+        config: &Config,
+    ) -> FlashgrepResult<Option<HashSet<PathBuf>>> {
+        let mut candidates: Option<HashSet<PathBuf>> = None;
+        for (definitions, negated) in &self.symbols {
+            if *negated {
+                continue;
+            }
+            let files: HashSet<PathBuf> =
+                definitions.iter().map(|(path, _)| path.clone()).collect();
+            candidates = Some(match candidates {
+                Some(known) => known.intersection(&files).cloned().collect(),
+                None => files,
+            });
+        }
+        if candidates.is_none() && (!self.paths.is_empty() || !self.languages.is_empty()) {
+            candidates = Some(db.get_all_files()?.into_iter().collect());
+        }
+        Ok(candidates.map(|files| {
+            files
+                .into_iter()
+                .filter(|file| {
+                    self.matches_path(file.strip_prefix(repo_root).unwrap_or(file))
+                        && self.matches_language(file, config)
+                })
+                .collect()
+        }))
+    }
+
+    /// Whether the chunk's repository-relative path passes the `path:` clauses
+    pub fn matches_path(&self, relative_path: &Path) -> bool {
+        if self.paths.is_empty() {
+            return true;
+        }
+        let normalized = normalize_path_for_matching(relative_path);
+        let folded = if self.case_sensitive {
+            normalized.clone()
+        } else {
+            normalized.to_lowercase()
+        };
+        let options = MatchOptions {
+            case_sensitive: self.case_sensitive,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        self.paths.iter().all(|(matcher, negated)| {
+            let found = match matcher {
+                PathMatcher::Glob(pattern) => pattern.matches_with(&normalized, options),
+                PathMatcher::Contains(value) => folded.contains(value.as_str()),
+            };
+            found != *negated
+        })
+    }
+
+    /// Whether the file passes the `lang:` clauses. Several positive clauses
+    /// accept any of their languages.
+    pub fn matches_language(&self, file_path: &Path, config: &Config) -> bool {
+        let mut positive = None;
+        for (matcher, negated) in &self.languages {
+            let found = match &matcher.files {
+                Some(files) => files.contains(file_path),
+                None => config
+                    .languages
+                    .detect_path(file_path)
+                    .is_some_and(|def| matcher.ids.contains(&def.id)),
+            };
+            if *negated {
+                if found {
+                    return false;
+                }
+            } else {
+                positive = Some(positive.unwrap_or(false) || found);
+            }
+        }
+        positive.unwrap_or(true)
+    }
+
+    /// Whether the chunk body and line range pass the text and `sym:` clauses
+    pub fn matches_chunk(
+        &self,
+        file_path: &Path,
+        start_line: usize,
+        end_line: usize,
+        content: &str,
+    ) -> bool {
+        let folded;
+        let content = if self.case_sensitive {
+            content
+        } else {
+            folded = content.to_lowercase();
+            &folded
+        };
+        let texts_ok = self
+            .texts
+            .iter()
+            .all(|(text, negated)| content.contains(text.as_str()) != *negated);
+        texts_ok
+            && self.symbols.iter().all(|(definitions, negated)| {
+                let defined = definitions.iter().any(|(path, line)| {
+                    path == file_path && (start_line..=end_line).contains(line)
+                });
+                defined != *negated
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clause(kind: ClauseKind, negated: bool, column: usize) -> Clause {
+        Clause {
+            kind,
+            negated,
+            column,
+        }
+    }
+
+    #[test]
+    fn parses_fields_phrases_and_negation() {
+        let query =
+            parse(r#"path:src/mcp lang:rust sym:handle_request "exact phrase" -test case:yes"#)
+                .expect("query");
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(ClauseKind::Path("src/mcp".into()), false, 1),
+                clause(ClauseKind::Language("rust".into()), false, 14),
+                clause(ClauseKind::Symbol("handle_request".into()), false, 24),
+                clause(ClauseKind::Text("exact phrase".into()), false, 43),
+                clause(ClauseKind::Text("test".into()), true, 58),
+            ]
+        );
+        assert_eq!(query.case_sensitive, Some(true));
+        assert_eq!(
+            query.required_texts().collect::<Vec<_>>(),
+            vec!["exact phrase"]
+        );
+    }
+
+    #[test]
+    fn unknown_prefixes_quotes_and_escapes_are_text() {
+        let query = parse(r#"std::fs::read -path:"my dir" "say \"hi\"" case:auto"#).expect("query");
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(ClauseKind::Text("std::fs::read".into()), false, 1),
+                clause(ClauseKind::Path("my dir".into()), true, 15),
+                clause(ClauseKind::Text(r#"say "hi""#.into()), false, 30),
+            ]
+        );
+        // No uppercase text, so auto is insensitive
+        assert_eq!(query.case_sensitive, Some(false));
+        assert_eq!(parse("Foo case:auto").unwrap().case_sensitive, Some(true));
+        assert_eq!(parse("foo").unwrap().case_sensitive, None);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_column() {
+        let cases = [
+            (r#"path:src "abc"#, 10, "unterminated quote"),
+            ("lang: rust", 6, "missing value after 'lang:'"),
+            ("foo - bar", 5, "'-' must be followed"),
+            ("foo case:maybe", 10, "expected yes, no or auto"),
+            ("-case:yes foo", 1, "cannot be negated"),
+            (r#"say "hi"there"#, 9, "expected whitespace"),
+            (r#"a """#, 3, "empty quotes"),
+            ("case:no", 8, "no terms or filters"),
+        ];
+        for (query, column, message) in cases {
+            let err = parse(query).expect_err(query);
+            assert_eq!(err.column, column, "{}", query);
+            assert!(err.message.contains(message), "{}: {}", query, err.message);
+        }
+
+        let rendered = parse("lang: rust").unwrap_err().to_string();
+        assert!(rendered.ends_with("lang: rust\n       ^"), "{}", rendered);
+    }
+
+    #[test]
+    fn filter_applies_text_path_and_case() -> FlashgrepResult<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db = Database::open(&temp.path().join("test.db"))?;
+        let config = Config::default();
+        let text = "Handler path:src/mcp -path:*_test.rs -todo case:no";
+        let query = parse(text)?;
+        let filter = StructuredFilter::new(
+            &query,
+            text,
+            query.case_sensitive.unwrap_or(true),
+            &db,
+            &config,
+            false,
+        )?;
+
+        assert!(filter.matches_path(Path::new("src/MCP/stdio.rs")));
+        assert!(!filter.matches_path(Path::new("src/mcp/stdio_test.rs")));
+        assert!(!filter.matches_path(Path::new("src/cli/mod.rs")));
+        let file = Path::new("/repo/src/mcp/stdio.rs");
+        assert!(filter.matches_chunk(file, 1, 3, "fn handler() {}"));
+        assert!(!filter.matches_chunk(file, 1, 3, "fn handler() {} // TODO"));
+        assert!(!filter.matches_chunk(file, 1, 3, "fn other() {}"));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_language_and_revision_symbols_are_rejected() -> FlashgrepResult<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db = Database::open(&temp.path().join("test.db"))?;
+        let config = Config::default();
+
+        let text = "foo lang:klingon";
+        let err = StructuredFilter::new(&parse(text)?, text, true, &db, &config, false)
+            .err()
+            .expect("unknown language");
+        assert!(err
+            .to_string()
+            .contains("unknown language 'klingon' at column 5"));
+
+        let text = "sym:main";
+        let err = StructuredFilter::new(&parse(text)?, text, true, &db, &config, true)
+            .err()
+            .expect("revision symbols");
+        assert!(err.to_string().contains("working tree"));
+        Ok(())
+    }
+}
@@ -1,23 +1,39 @@
+pub mod dsl;
+
 use crate::config::Config;
-use crate::db::models::{SearchResult, Symbol};
+use crate::db::models::{ChunkLocation, LineRange, SearchResult, Symbol};
 use crate::db::Database;
+use crate::encoding;
+use crate::git::{blame_cached, GitRepo};
 use crate::neural::{cosine_similarity, provider_assist_rerank, pseudo_embedding};
 use crate::path_utils::{normalize_glob_pattern, normalize_path_for_matching};
+use crate::progress::ProgressReporter;
+use crate::search::dsl::StructuredFilter;
 use crate::FlashgrepError;
 use crate::FlashgrepResult;
 use glob::{MatchOptions, Pattern};
 use regex::{Regex, RegexBuilder};
 use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use tantivy::query::QueryParser;
-use tantivy::{Index, IndexReader, ReloadPolicy};
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query, QueryParser, TermQuery, TermSetQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Index, IndexReader, ReloadPolicy, Term};
 use tracing::{debug, warn};
 
+/// Number of scanned candidates between query progress notifications
+const QUERY_PROGRESS_INTERVAL: u64 = 256;
+
+/// Most fetched documents whose chunk bodies are read in one lookup
+const QUERY_BODY_BATCH: usize = 512;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum QueryMode {
     Smart,
     Literal,
     Regex,
+    /// Field filters and phrases, parsed by [`dsl::parse`]
+    Structured,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -35,9 +51,18 @@ pub struct QueryOptions {
     pub case_sensitive: bool,
     pub include: Vec<String>,
     pub exclude: Vec<String>,
+    /// Language ids or aliases; results are limited to files stored with these languages
+    pub languages: Vec<String>,
     pub context: usize,
     pub offset: usize,
     pub retrieval_mode: QueryRetrievalMode,
+    /// Indexed git revision (branch, tag or commit id) to search instead of the working tree
+    pub revision: Option<String>,
+    /// Git ref; results are limited to files changed since it forked off `HEAD`,
+    /// or to uncommitted changes when it is `HEAD`
+    pub changed_since: Option<String>,
+    /// Attach the last commit touching each result's lines
+    pub with_blame: bool,
 }
 
 impl QueryOptions {
@@ -50,9 +75,13 @@ impl QueryOptions {
             case_sensitive: true,
             include: Vec::new(),
             exclude: Vec::new(),
+            languages: Vec::new(),
             context: 0,
             offset: 0,
             retrieval_mode: QueryRetrievalMode::Lexical,
+            revision: None,
+            changed_since: None,
+            with_blame: false,
         }
     }
 
@@ -69,9 +98,10 @@ impl QueryOptions {
             "smart" => QueryMode::Smart,
             "literal" => QueryMode::Literal,
             "regex" => QueryMode::Regex,
+            "structured" => QueryMode::Structured,
             other => {
                 return Err(FlashgrepError::Config(format!(
-                    "Invalid query mode '{}'. Expected one of: smart, literal, regex",
+                    "Invalid query mode '{}'. Expected one of: smart, literal, regex, structured",
                     other
                 )))
             }
@@ -80,6 +110,9 @@ impl QueryOptions {
         if !fixed_patterns.is_empty() {
             mode = QueryMode::Literal;
         }
+        if mode == QueryMode::Structured {
+            dsl::parse(&text)?;
+        }
 
         let flags = args
             .get("regex_flags")
@@ -93,8 +126,12 @@ impl QueryOptions {
 
         let include = vec_from_str_array(args.get("include"))?;
         let exclude = vec_from_str_array(args.get("exclude"))?;
+        let languages = languages_from_mcp_args(args)?;
         let context = args.get("context").and_then(Value::as_u64).unwrap_or(0) as usize;
         let offset = args.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let revision = non_empty_str(args.get("rev"));
+        let changed_since = non_empty_str(args.get("changed_since"));
+        let with_blame = args.get("blame").and_then(Value::as_bool).unwrap_or(false);
 
         let retrieval_mode = match args
             .get("retrieval_mode")
@@ -127,13 +164,52 @@ impl QueryOptions {
             case_sensitive,
             include,
             exclude,
+            languages,
             context,
             offset,
             retrieval_mode,
+            revision,
+            changed_since,
+            with_blame,
         })
     }
 }
 
+fn non_empty_str(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Parse the `languages` MCP argument (array of names, or a comma-separated string)
+pub fn languages_from_mcp_args(args: &Value) -> FlashgrepResult<Vec<String>> {
+    match args.get("languages") {
+        Some(Value::String(list)) => Ok(list
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(ToString::to_string)
+            .collect()),
+        other => vec_from_str_array(other),
+    }
+}
+
+/// Indexed files whose stored language matches one of `languages` (ids or aliases).
+/// Returns `None` when no language filter was requested.
+pub fn resolve_language_files(
+    db: &Database,
+    config: &Config,
+    languages: &[String],
+) -> FlashgrepResult<Option<HashSet<PathBuf>>> {
+    let ids = config.languages.resolve_ids(languages);
+    if ids.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(db.files_with_languages(&ids)?.into_iter().collect()))
+}
+
 #[derive(Debug, Clone)]
 pub struct QueryResponse {
     pub results: Vec<SearchResult>,
@@ -200,6 +276,8 @@ pub struct Searcher {
     query_parser: QueryParser,
     db: Database,
     config: Config,
+    repo_root: PathBuf,
+    progress: ProgressReporter,
 }
 
 impl Searcher {
@@ -212,14 +290,18 @@ impl Searcher {
         let schema = index.schema();
 
         // Create query parser for the content field
-        let content_field = schema.get_field("content").unwrap();
+        let content_field = schema.get_field("content")?;
         let query_parser = QueryParser::for_index(index, vec![content_field]);
 
-        let db = Database::open(db_path)?;
-        let config_path = db_path
+        let db = Database::open_read_only(db_path)?;
+        let flashgrep_dir = db_path
             .parent()
-            .map(|p| p.join("config.json"))
             .ok_or_else(|| FlashgrepError::Config("invalid metadata db path".to_string()))?;
+        let config_path = flashgrep_dir.join("config.json");
+        let repo_root = flashgrep_dir
+            .parent()
+            .unwrap_or(flashgrep_dir)
+            .to_path_buf();
         let config = if config_path.exists() {
             Config::from_file(&config_path)?
         } else {
@@ -231,9 +313,17 @@ impl Searcher {
             query_parser,
             db,
             config,
+            repo_root,
+            progress: ProgressReporter::disabled(),
         })
     }
 
+    /// Attach a progress reporter used to report scan progress and observe cancellation
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
     /// Search the index with a text query
     pub fn query(&self, text: &str, limit: usize) -> FlashgrepResult<Vec<SearchResult>> {
         let options = QueryOptions::new(text.to_string(), limit);
@@ -256,6 +346,11 @@ impl Searcher {
             });
         }
 
+        // Neural artifacts only exist for the working tree
+        if options.revision.is_some() {
+            return self.query_lexical(options);
+        }
+
         match options.retrieval_mode {
             QueryRetrievalMode::Lexical => self.query_lexical(options),
             QueryRetrievalMode::Neural => self.query_neural_assisted(options),
@@ -295,7 +390,7 @@ impl Searcher {
                     .into_iter()
                     .map(|chunk| {
                         let similarity = cosine_similarity(&query_embedding, &chunk.embedding);
-                        SearchResult {
+                        let result = SearchResult {
                             file_path: chunk.file_path,
                             start_line: chunk.start_line,
                             end_line: chunk.end_line,
@@ -303,17 +398,25 @@ impl Searcher {
                             relevance_score: similarity,
                             preview: chunk.content.lines().take(3).collect::<Vec<_>>().join("\n"),
                             content: None,
-                        }
+                            revision: None,
+                            changed_hunks: Vec::new(),
+                            blame: None,
+                            also_in: Vec::new(),
+                        };
+                        (result, chunk.content_hash)
                     })
                     .collect::<Vec<_>>();
-                seeded.sort_by(|a, b| {
+                seeded.sort_by(|(a, _), (b, _)| {
                     b.relevance_score
                         .total_cmp(&a.relevance_score)
                         .then_with(|| a.file_path.cmp(&b.file_path))
                         .then_with(|| a.start_line.cmp(&b.start_line))
                 });
-                base.results = seeded
+                // Identical chunks score the same; keep the first and list the rest
+                let mut seen = HashSet::new();
+                let (mut results, content_hashes): (Vec<_>, Vec<_>) = seeded
                     .into_iter()
+                    .filter(|(_, hash)| seen.insert(hash.clone()))
                     .take(
                         self.config
                             .neural
@@ -321,7 +424,9 @@ impl Searcher {
                             .max_candidates
                             .max(options.limit),
                     )
-                    .collect();
+                    .unzip();
+                self.attach_duplicate_locations(&mut results, &content_hashes)?;
+                base.results = results;
             }
         }
 
@@ -416,7 +521,11 @@ impl Searcher {
         options: &QueryOptions,
     ) -> FlashgrepResult<QueryResponse> {
         let primary = self.query_lexical(options)?;
-        if !primary.results.is_empty() || options.text.trim().is_empty() {
+        // Retrying single terms would drop a structured query's filters
+        if !primary.results.is_empty()
+            || options.text.trim().is_empty()
+            || options.mode == QueryMode::Structured
+        {
             return Ok(primary);
         }
 
@@ -437,17 +546,56 @@ impl Searcher {
     fn query_lexical(&self, options: &QueryOptions) -> FlashgrepResult<QueryResponse> {
         let searcher = self.reader.searcher();
         let schema = searcher.schema();
-        let file_path_field = schema.get_field("file_path").unwrap();
-        let content_field = schema.get_field("content").unwrap();
-        let start_line_field = schema.get_field("start_line").unwrap();
-        let end_line_field = schema.get_field("end_line").unwrap();
+        let file_path_field = schema.get_field("file_path")?;
+        let content_field = schema.get_field("content")?;
+        let start_line_field = schema.get_field("start_line")?;
+        let end_line_field = schema.get_field("end_line")?;
+        let content_hash_field = schema.get_field("content_hash")?;
+        let revision_field = schema.get_field("revision").ok();
 
         let include_patterns = compile_patterns(&options.include)?;
         let exclude_patterns = compile_patterns(&options.exclude)?;
+        let revision = self.resolve_revision(options.revision.as_deref())?;
+        // Revision files have no metadata rows, so their language comes from the path
+        let (language_files, revision_language_ids) = match revision {
+            Some(_) => (None, self.config.languages.resolve_ids(&options.languages)),
+            None => (self.language_files(&options.languages)?, Vec::new()),
+        };
+        let changed_files = self.changed_files(options.changed_since.as_deref())?;
+        let blame_repo = if options.with_blame {
+            Some(GitRepo::open(&self.repo_root)?)
+        } else {
+            None
+        };
         let regex = compile_query_regex(options)?;
+        let structured = match options.mode {
+            QueryMode::Structured => {
+                let parsed = dsl::parse(&options.text)?;
+                let case_sensitive = parsed.case_sensitive.unwrap_or(options.case_sensitive);
+                let filter = StructuredFilter::new(
+                    &parsed,
+                    &options.text,
+                    case_sensitive,
+                    &self.db,
+                    &self.config,
+                    revision.is_some(),
+                )?;
+                Some((parsed, filter))
+            }
+            _ => None,
+        };
 
         let query_text = match options.mode {
             QueryMode::Smart => options.text.clone(),
+            // Phrases without a word in them cannot be looked up and are left
+            // to the post-filter; with none left every chunk is a candidate
+            QueryMode::Structured => structured
+                .iter()
+                .flat_map(|(parsed, _)| parsed.required_texts())
+                .filter(|text| text.chars().any(char::is_alphanumeric))
+                .map(|text| format!("+\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect::<Vec<_>>()
+                .join(" "),
             QueryMode::Literal => {
                 let mut fixed = options.fixed_patterns.clone();
                 if fixed.is_empty() && !options.text.is_empty() {
@@ -470,94 +618,281 @@ impl Searcher {
                 .unwrap_or(&options.text)
                 .to_string(),
         };
-        let query = self.query_parser.parse_query(&query_text)?;
+        // A structured query without words to look up is answered by its
+        // filters alone, which only run after the fetch
+        let filter_only = structured.is_some() && query_text.is_empty();
+        let candidate_files = match (&structured, schema.get_field("file_path_key")) {
+            (Some((_, filter)), Ok(field)) if filter_only && revision.is_none() => filter
+                .candidate_files(&self.db, &self.repo_root, &self.config)?
+                .map(|files| (field, files)),
+            _ => None,
+        };
+        let mut query: Box<dyn Query> = if let Some((field, files)) = candidate_files {
+            Box::new(TermSetQuery::new(files.into_iter().map(|file| {
+                Term::from_field_text(field, &file.to_string_lossy())
+            })))
+        } else if query_text.is_empty() {
+            Box::new(AllQuery)
+        } else {
+            self.query_parser.parse_query(&query_text)?
+        };
+        // Keep documents of other revisions out of the fetch window rather than
+        // dropping them after it, where they would crowd out the wanted hits
+        if let Some(field) = revision_field {
+            let revision_clauses: Vec<(Occur, Box<dyn Query>)> = match &revision {
+                Some(commit_id) => vec![(
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(field, commit_id),
+                        IndexRecordOption::Basic,
+                    )),
+                )],
+                None => indexed_revisions(&searcher, field)?
+                    .into_iter()
+                    .map(|term| {
+                        let excluded: Box<dyn Query> =
+                            Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                        (Occur::MustNot, excluded)
+                    })
+                    .collect(),
+            };
+            if !revision_clauses.is_empty() {
+                let mut clauses = vec![(Occur::Must, query)];
+                clauses.extend(revision_clauses);
+                query = Box::new(BooleanQuery::new(clauses));
+            }
+        }
 
         let target_count = options.offset.saturating_add(options.limit);
-        let fetch_limit = target_count
-            .saturating_mul(30)
-            .max(target_count)
-            .min(10_000);
+        // Without a ranking, a window could hold none of the filters' matches,
+        // so every candidate is read until the page is full
+        let fetch_limit = if filter_only {
+            (searcher.num_docs() as usize).max(1)
+        } else {
+            target_count
+                .saturating_mul(30)
+                .max(target_count)
+                .min(10_000)
+        };
         let top_docs = searcher.search(
             &query,
             &tantivy::collector::TopDocs::with_limit(fetch_limit),
         )?;
 
-        let mut results = Vec::new();
+        let mut results: Vec<SearchResult> = Vec::new();
         let mut scanned_files = 0usize;
         let mut matched = 0usize;
+        // Identical chunks collapse into the first result with that content: hash
+        // to its index in `results`, or `None` when it was skipped by the offset
+        let mut seen_content: HashMap<String, Option<usize>> = HashMap::new();
+        let mut result_hashes = Vec::new();
+
+        let total_docs = top_docs.len() as u64;
+        let mut scanned = 0u64;
+        // Batches start at the page size and grow, so a page filled by the first
+        // hits reads few bodies while long scans read them in few lookups
+        let mut batch_size = target_count.clamp(1, QUERY_BODY_BATCH);
+        let mut remaining = top_docs.as_slice();
+        'batches: while !remaining.is_empty() {
+            let (batch, rest) = remaining.split_at(batch_size.min(remaining.len()));
+            remaining = rest;
+            batch_size = batch_size.saturating_mul(2).min(QUERY_BODY_BATCH);
+
+            let mut candidates = Vec::with_capacity(batch.len());
+            for &(score, doc_address) in batch {
+                scanned += 1;
+                self.progress.checkpoint(
+                    "query",
+                    scanned,
+                    Some(total_docs),
+                    QUERY_PROGRESS_INTERVAL,
+                )?;
+                let doc = searcher.doc(doc_address)?;
+
+                let doc_revision = revision_field
+                    .and_then(|field| doc.get_first(field))
+                    .and_then(|v| v.as_text());
+                if doc_revision != revision.as_deref() {
+                    continue;
+                }
 
-        for (score, doc_address) in top_docs {
-            let doc = searcher.doc(doc_address)?;
-
-            let file_path = doc
-                .get_first(file_path_field)
-                .and_then(|v| v.as_text())
-                .map(PathBuf::from)
-                .unwrap_or_default();
-
-            if !path_matches(
-                &file_path,
-                &include_patterns,
-                &exclude_patterns,
-                options.case_sensitive,
-            ) {
-                continue;
+                let file_path = doc
+                    .get_first(file_path_field)
+                    .and_then(|v| v.as_text())
+                    .map(PathBuf::from)
+                    .unwrap_or_default();
+
+                if !path_matches(
+                    &file_path,
+                    &include_patterns,
+                    &exclude_patterns,
+                    options.case_sensitive,
+                ) {
+                    continue;
+                }
+                if language_files
+                    .as_ref()
+                    .is_some_and(|files| !files.contains(&file_path))
+                {
+                    continue;
+                }
+                if let Some((_, filter)) = &structured {
+                    let relative = file_path
+                        .strip_prefix(&self.repo_root)
+                        .unwrap_or(&file_path);
+                    if !filter.matches_path(relative)
+                        || !filter.matches_language(&file_path, &self.config)
+                    {
+                        continue;
+                    }
+                }
+                if !revision_language_ids.is_empty()
+                    && !self
+                        .config
+                        .languages
+                        .detect_path(&file_path)
+                        .is_some_and(|def| revision_language_ids.contains(&def.id))
+                {
+                    continue;
+                }
+                let file_hunks = match &changed_files {
+                    Some(files) => match files.get(&file_path) {
+                        Some(hunks) => Some(hunks),
+                        None => continue,
+                    },
+                    None => None,
+                };
+
+                candidates.push((score, doc, file_path, file_hunks));
             }
+            let hashes: Vec<String> = candidates
+                .iter()
+                .filter(|(_, doc, _, _)| doc.get_first(content_field).is_none())
+                .filter_map(|(_, doc, _, _)| doc.get_first(content_hash_field))
+                .filter_map(|value| value.as_text().map(str::to_string))
+                .collect();
+            let bodies = self.db.get_chunk_contents(&hashes)?;
+
+            for (score, doc, file_path, file_hunks) in candidates {
+                let content_hash = doc
+                    .get_first(content_hash_field)
+                    .and_then(|v| v.as_text())
+                    .unwrap_or("")
+                    .to_string();
+                // Text indexes built before bodies moved to the metadata store still hold them
+                let content = match doc.get_first(content_field).and_then(|v| v.as_text()) {
+                    Some(stored) => stored.to_string(),
+                    None => bodies.get(&content_hash).cloned().unwrap_or_default(),
+                };
+
+                let start_line = doc
+                    .get_first(start_line_field)
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0) as usize;
+
+                let end_line = doc
+                    .get_first(end_line_field)
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0) as usize;
+
+                let matched_query = match &structured {
+                    Some((_, filter)) => {
+                        filter.matches_chunk(&file_path, start_line, end_line, &content)
+                    }
+                    None => matches_query(
+                        &content,
+                        &options.text,
+                        &options.fixed_patterns,
+                        options.case_sensitive,
+                        regex.as_ref(),
+                    ),
+                };
+                if !matched_query {
+                    continue;
+                }
 
-            let content = doc
-                .get_first(content_field)
-                .and_then(|v| v.as_text())
-                .unwrap_or("")
-                .to_string();
-
-            if !matches_query(
-                &content,
-                &options.text,
-                &options.fixed_patterns,
-                options.case_sensitive,
-                regex.as_ref(),
-            ) {
-                continue;
-            }
+                if let Some(first) = seen_content.get(&content_hash) {
+                    // Working-tree duplicates are listed from the metadata store below
+                    if let (Some(index), Some(_)) = (first, &revision) {
+                        results[*index].also_in.push(ChunkLocation {
+                            file_path,
+                            start_line,
+                            end_line,
+                        });
+                    }
+                    continue;
+                }
 
-            let start_line = doc
-                .get_first(start_line_field)
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as usize;
-
-            let end_line = doc
-                .get_first(end_line_field)
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as usize;
-
-            let preview = if options.context > 0 {
-                render_context_preview(&file_path, start_line, end_line, options.context)
-                    .unwrap_or_else(|| content.lines().take(3).collect::<Vec<_>>().join("\n"))
-            } else {
-                content.lines().take(3).collect::<Vec<_>>().join("\n")
-            };
+                let changed_hunks = match file_hunks {
+                    Some(Some(hunks)) => hunks
+                        .iter()
+                        .filter(|hunk| hunk.overlaps(start_line, end_line))
+                        .copied()
+                        .collect(),
+                    // Untracked files are new in their entirety
+                    Some(None) => vec![LineRange::new(start_line, end_line)],
+                    None => Vec::new(),
+                };
+
+                let preview = if options.context > 0 && revision.is_some() {
+                    // The revision is not on disk, so context is limited to the stored chunk
+                    content.clone()
+                } else if options.context > 0 {
+                    self.context_preview(&file_path, start_line, end_line, options.context)?
+                        .unwrap_or_else(|| content.lines().take(3).collect::<Vec<_>>().join("\n"))
+                } else {
+                    content.lines().take(3).collect::<Vec<_>>().join("\n")
+                };
+
+                scanned_files += 1;
+                if matched < options.offset {
+                    matched += 1;
+                    if !content_hash.is_empty() {
+                        seen_content.insert(content_hash, None);
+                    }
+                    continue;
+                }
 
-            scanned_files += 1;
-            if matched < options.offset {
-                matched += 1;
-                continue;
-            }
+                if !content_hash.is_empty() {
+                    seen_content.insert(content_hash.clone(), Some(results.len()));
+                }
+                results.push(SearchResult {
+                    file_path,
+                    start_line,
+                    end_line,
+                    symbol_name: None,
+                    relevance_score: score,
+                    preview,
+                    content: None,
+                    revision: revision.clone(),
+                    changed_hunks,
+                    blame: None,
+                    also_in: Vec::new(),
+                });
 
-            results.push(SearchResult {
-                file_path,
-                start_line,
-                end_line,
-                symbol_name: None,
-                relevance_score: score,
-                preview,
-                content: None,
-            });
+                if let (Some(repo), Some(result)) = (&blame_repo, results.last_mut()) {
+                    result.blame = blame_cached(
+                        Some(&self.db),
+                        repo,
+                        &result.file_path,
+                        &content_hash,
+                        LineRange::new(start_line, end_line),
+                        revision.as_deref(),
+                    )
+                    .map_err(|err| debug!("No blame for {}: {}", result.file_path.display(), err))
+                    .ok();
+                }
+                result_hashes.push(content_hash);
 
-            if results.len() >= options.limit {
-                break;
-            }
+                if results.len() >= options.limit {
+                    break 'batches;
+                }
 
-            matched += 1;
+                matched += 1;
+            }
+        }
+        if revision.is_none() {
+            self.attach_duplicate_locations(&mut results, &result_hashes)?;
         }
 
         let truncated = results.len() >= options.limit;
@@ -574,6 +909,103 @@ impl Searcher {
         })
     }
 
+    /// Lines `start_line..=end_line` of an indexed file with `context` lines around
+    /// them, assembled from the stored chunks. Files whose stored chunks do not map
+    /// onto whole lines, such as split minified files, are read from disk instead.
+    fn context_preview(
+        &self,
+        file_path: &Path,
+        start_line: usize,
+        end_line: usize,
+        context: usize,
+    ) -> FlashgrepResult<Option<String>> {
+        let first = start_line.saturating_sub(context).max(1);
+        let last = end_line.saturating_add(context);
+        let chunks = self.db.get_chunks_in_range(file_path, first, last)?;
+
+        let mut lines = Vec::new();
+        let mut next_line = first;
+        for chunk in &chunks {
+            let chunk_lines: Vec<&str> = chunk.content.split('\n').collect();
+            if chunk_lines.len() != chunk.end_line + 1 - chunk.start_line
+                || chunk.start_line > next_line
+            {
+                return Ok(render_context_preview(
+                    file_path, start_line, end_line, context,
+                ));
+            }
+            for (offset, line) in chunk_lines.iter().enumerate() {
+                let number = chunk.start_line + offset;
+                if number == next_line && number <= last {
+                    lines.push(*line);
+                    next_line += 1;
+                }
+            }
+        }
+        if lines.is_empty() {
+            return Ok(render_context_preview(
+                file_path, start_line, end_line, context,
+            ));
+        }
+        Ok(Some(lines.join("\n")))
+    }
+
+    /// List the other places each result's chunk body is stored in `also_in`
+    fn attach_duplicate_locations(
+        &self,
+        results: &mut [SearchResult],
+        content_hashes: &[String],
+    ) -> FlashgrepResult<()> {
+        for (result, content_hash) in results.iter_mut().zip(content_hashes) {
+            if content_hash.is_empty() {
+                continue;
+            }
+            result.also_in = self
+                .db
+                .get_chunk_locations(content_hash)?
+                .into_iter()
+                .filter(|location| {
+                    location.file_path != result.file_path
+                        || location.start_line != result.start_line
+                        || location.end_line != result.end_line
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Changed files keyed by absolute path with their changed line ranges,
+    /// or `None` when no diff scope was requested
+    fn changed_files(
+        &self,
+        base: Option<&str>,
+    ) -> FlashgrepResult<Option<HashMap<PathBuf, Option<Vec<LineRange>>>>> {
+        let Some(base) = base else {
+            return Ok(None);
+        };
+        let files = GitRepo::open(&self.repo_root)?.changed_files(base)?;
+        Ok(Some(
+            files
+                .into_iter()
+                .map(|file| (self.repo_root.join(file.path), file.hunks))
+                .collect(),
+        ))
+    }
+
+    /// Resolve an indexed revision name or commit id to its commit id
+    fn resolve_revision(&self, rev: Option<&str>) -> FlashgrepResult<Option<String>> {
+        let Some(rev) = rev else {
+            return Ok(None);
+        };
+        match self.db.find_revision(rev)? {
+            Some(revision) => Ok(Some(revision.commit_id)),
+            None => Err(FlashgrepError::Config(format!(
+                "Revision '{}' is not indexed. Run 'flashgrep index --rev {}' first.",
+                rev, rev
+            ))),
+        }
+    }
+
     /// Get a specific slice of a file by line range
     pub fn get_slice(
         &self,
@@ -585,7 +1017,7 @@ impl Searcher {
             return Ok(None);
         }
 
-        let content = std::fs::read_to_string(file_path)?;
+        let content = encoding::read_to_string(file_path)?;
         let lines: Vec<&str> = content.lines().collect();
 
         let start = start_line.saturating_sub(1);
@@ -608,6 +1040,14 @@ impl Searcher {
     pub fn list_files(&self) -> FlashgrepResult<Vec<PathBuf>> {
         self.db.get_all_files()
     }
+
+    /// Indexed files in the given languages, or `None` when `languages` is empty
+    pub fn language_files(
+        &self,
+        languages: &[String],
+    ) -> FlashgrepResult<Option<HashSet<PathBuf>>> {
+        resolve_language_files(&self.db, &self.config, languages)
+    }
 }
 
 fn extract_focus_terms(input: &str) -> Vec<String> {
@@ -744,6 +1184,23 @@ fn pinpoint_best_line(content: &str, query: &str) -> Option<(usize, String)> {
     best.map(|(idx, _, line)| (idx, line))
 }
 
+/// Terms of every commit with documents in the text index, read from the
+/// `revision` field's term dictionaries
+fn indexed_revisions(searcher: &tantivy::Searcher, field: Field) -> FlashgrepResult<Vec<Term>> {
+    let mut commit_ids = BTreeSet::new();
+    for segment in searcher.segment_readers() {
+        let inverted_index = segment.inverted_index(field)?;
+        let mut terms = inverted_index.terms().stream()?;
+        while terms.advance() {
+            commit_ids.insert(String::from_utf8_lossy(terms.key()).to_string());
+        }
+    }
+    Ok(commit_ids
+        .into_iter()
+        .map(|commit_id| Term::from_field_text(field, &commit_id))
+        .collect())
+}
+
 fn compile_query_regex(options: &QueryOptions) -> FlashgrepResult<Option<Regex>> {
     match options.mode {
         QueryMode::Regex => {
@@ -794,7 +1251,7 @@ fn render_context_preview(
     end_line: usize,
     context: usize,
 ) -> Option<String> {
-    let content = std::fs::read_to_string(file_path).ok()?;
+    let content = encoding::read_to_string(file_path).ok()?;
     let lines: Vec<&str> = content.lines().collect();
     if lines.is_empty() {
         return None;
@@ -848,6 +1305,23 @@ mod tests {
         assert_eq!(opts.fixed_patterns.len(), 2);
     }
 
+    #[test]
+    fn query_options_validate_structured_queries() {
+        let opts = QueryOptions::from_mcp_args(&json!({
+            "text": "path:src/mcp -test",
+            "mode": "structured"
+        }))
+        .expect("options");
+        assert_eq!(opts.mode, QueryMode::Structured);
+
+        let err = QueryOptions::from_mcp_args(&json!({
+            "text": "sym:\"handle",
+            "mode": "structured"
+        }))
+        .expect_err("expected parse error");
+        assert!(err.to_string().contains("unterminated quote at column 5"));
+    }
+
     #[test]
     fn query_options_reject_regex_flags_without_regex_mode() {
         let err = QueryOptions::from_mcp_args(&json!({
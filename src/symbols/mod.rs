@@ -1,6 +1,28 @@
 use crate::db::models::{Symbol, SymbolType};
 use regex::Regex;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Which symbol extractor runs on a language's chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolExtractor {
+    /// Keyword patterns shared by most languages (`fn`, `def`, `class`, `import`, ...)
+    #[default]
+    Generic,
+    /// Generic patterns plus typed method declarations (C, C++, Java, Kotlin, C#)
+    CFamily,
+    /// Shell functions, `source` imports and exported variables
+    Shell,
+    /// Make targets and `include` directives
+    Makefile,
+    /// Build stages and base images
+    Dockerfile,
+    /// Terraform/HCL resources, data sources, modules, variables and outputs
+    Terraform,
+    /// Do not extract symbols
+    None,
+}
 
 /// Detects symbols in code using regex patterns
 pub struct SymbolDetector {
@@ -11,8 +33,21 @@ pub struct SymbolDetector {
     route_pattern: Regex,
     sql_pattern: Regex,
     visibility_pattern: Regex,
+    method_pattern: Regex,
+    shell_function_pattern: Regex,
+    shell_source_pattern: Regex,
+    shell_export_pattern: Regex,
+    make_target_pattern: Regex,
+    make_include_pattern: Regex,
+    docker_from_pattern: Regex,
+    terraform_block_pattern: Regex,
 }
 
+/// Words that look like a return type in `word name(...)` but start control flow
+const METHOD_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "else", "new", "throw", "sizeof",
+];
+
 impl SymbolDetector {
     /// Create a new symbol detector with compiled regex patterns
     pub fn new() -> Self {
@@ -51,6 +86,36 @@ impl SymbolDetector {
             visibility_pattern: Regex::new(
                 r"(?i)(?:^|\s)(?:public|private|protected|internal|pub)"
             ).unwrap(),
+
+            // Typed declarations: `void run(`, `std::string Foo::name(`, Kotlin `fun name(`
+            method_pattern: Regex::new(
+                r"^\s*(?:(?:[A-Za-z_][\w:<>,\[\]]*[\s\*&]+)+([A-Za-z_][\w:~]*)|fun\s+(?:<[^>]*>\s*)?(?:[\w.]+\.)?([A-Za-z_]\w*))\s*\([^;]*$"
+            ).unwrap(),
+
+            // Shell functions: `name() {`, `function name`
+            shell_function_pattern: Regex::new(
+                r"^\s*(?:function\s+([A-Za-z_][\w:.-]*)|([A-Za-z_][\w:.-]*)\s*\(\s*\))"
+            ).unwrap(),
+
+            // Shell imports: `source file`, `. file`
+            shell_source_pattern: Regex::new(r"^\s*(?:source|\.)\s+(\S+)").unwrap(),
+
+            // Shell exports: `export NAME=value`
+            shell_export_pattern: Regex::new(r"^\s*export\s+([A-Za-z_]\w*)").unwrap(),
+
+            // Make targets: `name: deps` but not `name := value`
+            make_target_pattern: Regex::new(r"^([A-Za-z0-9_][\w./%-]*)\s*::?(?:[^=]|$)").unwrap(),
+
+            // Make includes: `include file`, `-include file`
+            make_include_pattern: Regex::new(r"^-?include\s+(\S+)").unwrap(),
+
+            // Docker stages: `FROM image [AS name]`
+            docker_from_pattern: Regex::new(r"(?i)^\s*FROM\s+(?:--\S+\s+)*(\S+)(?:\s+AS\s+(\S+))?").unwrap(),
+
+            // Terraform blocks: `resource "type" "name"`, `module "name"`
+            terraform_block_pattern: Regex::new(
+                r#"^\s*(resource|data|module|variable|output|provider)\s+"([^"]+)"(?:\s+"([^"]+)")?"#
+            ).unwrap(),
         }
     }
 
@@ -174,6 +239,166 @@ impl SymbolDetector {
         symbols
     }
 
+    /// Detect symbols in a chunk with the extractor configured for its language
+    pub fn detect_with_extractor(
+        &self,
+        extractor: SymbolExtractor,
+        chunk: &str,
+        file_path: PathBuf,
+        start_line: usize,
+    ) -> Vec<Symbol> {
+        match extractor {
+            SymbolExtractor::Generic => self.detect_in_chunk(chunk, file_path, start_line),
+            SymbolExtractor::CFamily => {
+                let mut symbols = self.detect_in_chunk(chunk, file_path.clone(), start_line);
+                symbols.extend(self.detect_methods(chunk, &file_path, start_line, &symbols));
+                symbols
+            }
+            SymbolExtractor::Shell => self.detect_shell(chunk, &file_path, start_line),
+            SymbolExtractor::Makefile => self.detect_makefile(chunk, &file_path, start_line),
+            SymbolExtractor::Dockerfile => self.detect_dockerfile(chunk, &file_path, start_line),
+            SymbolExtractor::Terraform => self.detect_terraform(chunk, &file_path, start_line),
+            SymbolExtractor::None => Vec::new(),
+        }
+    }
+
+    /// Typed method/function declarations not already found by the generic patterns
+    fn detect_methods(
+        &self,
+        chunk: &str,
+        file_path: &Path,
+        start_line: usize,
+        existing: &[Symbol],
+    ) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for (i, line) in chunk.lines().enumerate() {
+            let line_number = start_line + i;
+            let Some(cap) = self.method_pattern.captures(line) else {
+                continue;
+            };
+            let Some(name) = cap.get(1).or_else(|| cap.get(2)) else {
+                continue;
+            };
+            let name = name.as_str().rsplit("::").next().unwrap_or(name.as_str());
+            if METHOD_KEYWORDS.contains(&name)
+                || existing.iter().any(|s| {
+                    s.line_number == line_number
+                        && s.symbol_name == name
+                        && s.symbol_type == SymbolType::Function
+                })
+            {
+                continue;
+            }
+            symbols.push(new_symbol(
+                name,
+                file_path,
+                line_number,
+                SymbolType::Function,
+            ));
+        }
+        symbols
+    }
+
+    fn detect_shell(&self, chunk: &str, file_path: &Path, start_line: usize) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for (i, line) in chunk.lines().enumerate() {
+            let line_number = start_line + i;
+            if let Some(cap) = self.shell_function_pattern.captures(line) {
+                if let Some(name) = cap.get(1).or_else(|| cap.get(2)) {
+                    symbols.push(new_symbol(
+                        name.as_str(),
+                        file_path,
+                        line_number,
+                        SymbolType::Function,
+                    ));
+                }
+            } else if let Some(cap) = self.shell_source_pattern.captures(line) {
+                symbols.push(new_symbol(
+                    &cap[1],
+                    file_path,
+                    line_number,
+                    SymbolType::Import,
+                ));
+            } else if let Some(cap) = self.shell_export_pattern.captures(line) {
+                symbols.push(new_symbol(
+                    &cap[1],
+                    file_path,
+                    line_number,
+                    SymbolType::Export,
+                ));
+            }
+        }
+        symbols
+    }
+
+    fn detect_makefile(&self, chunk: &str, file_path: &Path, start_line: usize) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for (i, line) in chunk.lines().enumerate() {
+            let line_number = start_line + i;
+            if let Some(cap) = self.make_include_pattern.captures(line) {
+                symbols.push(new_symbol(
+                    &cap[1],
+                    file_path,
+                    line_number,
+                    SymbolType::Import,
+                ));
+            } else if let Some(cap) = self.make_target_pattern.captures(line) {
+                symbols.push(new_symbol(
+                    &cap[1],
+                    file_path,
+                    line_number,
+                    SymbolType::Other("target".to_string()),
+                ));
+            }
+        }
+        symbols
+    }
+
+    fn detect_dockerfile(&self, chunk: &str, file_path: &Path, start_line: usize) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for (i, line) in chunk.lines().enumerate() {
+            let line_number = start_line + i;
+            if let Some(cap) = self.docker_from_pattern.captures(line) {
+                symbols.push(new_symbol(
+                    &cap[1],
+                    file_path,
+                    line_number,
+                    SymbolType::Import,
+                ));
+                if let Some(stage) = cap.get(2) {
+                    symbols.push(new_symbol(
+                        stage.as_str(),
+                        file_path,
+                        line_number,
+                        SymbolType::Other("stage".to_string()),
+                    ));
+                }
+            }
+        }
+        symbols
+    }
+
+    fn detect_terraform(&self, chunk: &str, file_path: &Path, start_line: usize) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for (i, line) in chunk.lines().enumerate() {
+            let line_number = start_line + i;
+            if let Some(cap) = self.terraform_block_pattern.captures(line) {
+                // `resource "aws_s3_bucket" "logs"` is addressed as `aws_s3_bucket.logs`
+                let name = match cap.get(3) {
+                    Some(label) => format!("{}.{}", &cap[2], label.as_str()),
+                    None => cap[2].to_string(),
+                };
+                symbols.push(new_symbol(
+                    &name,
+                    file_path,
+                    line_number,
+                    SymbolType::Other(cap[1].to_string()),
+                ));
+            }
+        }
+        symbols
+    }
+
     /// Extract name from an import statement
     fn extract_import_name(&self, line: &str) -> String {
         // Try to extract the imported module/package name
@@ -248,6 +473,16 @@ impl SymbolDetector {
     }
 }
 
+fn new_symbol(name: &str, file_path: &Path, line_number: usize, symbol_type: SymbolType) -> Symbol {
+    Symbol {
+        id: None,
+        symbol_name: name.to_string(),
+        file_path: file_path.to_path_buf(),
+        line_number,
+        symbol_type,
+    }
+}
+
 impl Default for SymbolDetector {
     fn default() -> Self {
         Self::new()
@@ -321,4 +556,80 @@ mod tests {
             "Should detect route definitions"
         );
     }
+
+    #[test]
+    fn test_detect_with_language_extractors() {
+        let detector = SymbolDetector::new();
+        let has = |symbols: &[Symbol], name: &str, symbol_type: SymbolType| {
+            symbols
+                .iter()
+                .any(|s| s.symbol_name == name && s.symbol_type == symbol_type)
+        };
+
+        let java = "public class Greeter {\n    private String greet(String name) {\n        if (name != null) {\n";
+        let symbols = detector.detect_with_extractor(
+            SymbolExtractor::CFamily,
+            java,
+            PathBuf::from("Greeter.java"),
+            1,
+        );
+        assert!(has(&symbols, "greet", SymbolType::Function));
+        assert!(!has(&symbols, "if", SymbolType::Function));
+
+        let kotlin = "fun String.shout(): String = uppercase()\nfun main(args: Array<String>) {";
+        let symbols = detector.detect_with_extractor(
+            SymbolExtractor::CFamily,
+            kotlin,
+            PathBuf::from("Main.kt"),
+            1,
+        );
+        assert!(has(&symbols, "shout", SymbolType::Function));
+
+        let shell = "source ./lib.sh\nexport PATH\ndeploy() {\n  echo hi\n}\nfunction cleanup {";
+        let symbols =
+            detector.detect_with_extractor(SymbolExtractor::Shell, shell, PathBuf::from("run"), 1);
+        assert!(has(&symbols, "deploy", SymbolType::Function));
+        assert!(has(&symbols, "cleanup", SymbolType::Function));
+        assert!(has(&symbols, "./lib.sh", SymbolType::Import));
+        assert!(has(&symbols, "PATH", SymbolType::Export));
+
+        let make = "include common.mk\nCC := gcc\nbuild: deps\n\t$(CC) main.c";
+        let symbols = detector.detect_with_extractor(
+            SymbolExtractor::Makefile,
+            make,
+            PathBuf::from("Makefile"),
+            1,
+        );
+        assert!(has(&symbols, "build", SymbolType::Other("target".into())));
+        assert!(has(&symbols, "common.mk", SymbolType::Import));
+        assert!(!symbols.iter().any(|s| s.symbol_name == "CC"));
+
+        let docker = "FROM rust:1.80 AS builder\nRUN cargo build";
+        let symbols = detector.detect_with_extractor(
+            SymbolExtractor::Dockerfile,
+            docker,
+            PathBuf::from("Dockerfile"),
+            1,
+        );
+        assert!(has(&symbols, "builder", SymbolType::Other("stage".into())));
+        assert!(has(&symbols, "rust:1.80", SymbolType::Import));
+
+        let terraform = "resource \"aws_s3_bucket\" \"logs\" {\nvariable \"region\" {";
+        let symbols = detector.detect_with_extractor(
+            SymbolExtractor::Terraform,
+            terraform,
+            PathBuf::from("main.tf"),
+            1,
+        );
+        assert!(has(
+            &symbols,
+            "aws_s3_bucket.logs",
+            SymbolType::Other("resource".into())
+        ));
+        assert!(has(
+            &symbols,
+            "region",
+            SymbolType::Other("variable".into())
+        ));
+    }
 }
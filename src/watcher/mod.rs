@@ -1,23 +1,129 @@
 pub mod registry;
 
 use crate::config::paths::FlashgrepPaths;
-use crate::config::Config;
+use crate::config::{Config, WatcherBackend};
 use crate::index::engine::Indexer;
-use crate::index::initial_scanner::{run_initial_scan, ScanResult};
-use crate::index::scanner::{
-    is_binary_file, is_oversized_file, should_ignore_directory, should_index_file, FlashgrepIgnore,
-};
-use crate::index::state::ThreadSafeIndexState;
+use crate::index::initial_scanner::{run_initial_scan, InitialScanner, ScanResult};
+use crate::index::scanner::{should_ignore_directory, should_index_file, FlashgrepIgnore};
+use crate::index::state::{FileMetadata, ThreadSafeIndexState};
+use crate::progress::{CancellationToken, ProgressReporter, ProgressSink};
 use crate::FlashgrepResult;
-use notify::{Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashMap;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{
+    Config as NotifyConfig, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::collections::{BTreeSet, HashMap};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{channel, Receiver};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+pub use crate::index::initial_scanner::SyntheticEvent;
+
+/// Offline changes replayed between progress reports
+const REPLAY_PROGRESS_INTERVAL: u64 = 100;
+
+/// How long to wait for a file system event before checking pending changes
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Poll intervals between the `auto` backend's checks for missed native events
+const AUTO_PROBE_POLL_INTERVALS: u32 = 15;
+
+/// Consecutive checks that must find changes without native events before the
+/// `auto` backend switches to polling
+const AUTO_PROBE_MISSES: u32 = 2;
+
+/// File system types whose changes are not reported by native notifications
+/// (network shares, FUSE mounts, VM and container file sharing, WSL drives)
+const POLLING_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "fuse.sshfs",
+    "9p",
+    "drvfs",
+    "virtiofs",
+    "vboxsf",
+    "fuse.grpcfuse",
+    "fakeowner",
+    "afs",
+];
+
+/// File changes waiting for the event stream to go quiet
+#[derive(Debug, Default)]
+struct PendingChanges {
+    paths: BTreeSet<PathBuf>,
+    /// `(from, to)` moves reported by the platform, applied before `paths`
+    renames: Vec<(PathBuf, PathBuf)>,
+    /// `.git/HEAD` moved, so this is a checkout, rebase or reset
+    head_moved: bool,
+    ignore_files_changed: bool,
+    last_event: Option<Instant>,
+}
+
+impl PendingChanges {
+    /// Whether there are changes and no event arrived for `debounce`
+    fn is_quiet(&self, debounce: Duration) -> bool {
+        self.last_event
+            .is_some_and(|last| last.elapsed() >= debounce)
+    }
+}
+
+/// Schedule of the `auto` backend's rescans for changes native events missed
+#[derive(Debug)]
+struct AutoProbe {
+    interval: Duration,
+    last: Instant,
+    /// A native event for a watched path arrived since the last rescan
+    events_seen: bool,
+    /// Consecutive rescans that found changes no event reported
+    misses: u32,
+}
+
+impl AutoProbe {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: Instant::now(),
+            events_seen: false,
+            misses: 0,
+        }
+    }
+
+    fn saw_event(&mut self) {
+        self.events_seen = true;
+    }
+
+    /// Whether a rescan is due. Intervals in which native events arrived show
+    /// that notifications work, so they are not rescanned.
+    fn is_due(&mut self) -> bool {
+        if self.last.elapsed() < self.interval {
+            return false;
+        }
+        if self.events_seen {
+            self.last = Instant::now();
+            self.events_seen = false;
+            self.misses = 0;
+            return false;
+        }
+        true
+    }
+
+    /// Record a rescan that found `missed` changed files; returns whether enough
+    /// consecutive rescans missed changes to switch to polling
+    fn record(&mut self, missed: usize) -> bool {
+        self.last = Instant::now();
+        self.events_seen = false;
+        self.misses = if missed > 0 { self.misses + 1 } else { 0 };
+        self.misses >= AUTO_PROBE_MISSES
+    }
+}
+
 /// File watcher for incremental indexing
 pub struct FileWatcher {
     repo_root: PathBuf,
@@ -30,14 +136,6 @@ pub struct FileWatcher {
     index_state_path: PathBuf,
 }
 
-/// Represents a change detected during initial scan
-#[derive(Debug, Clone)]
-pub enum SyntheticEvent {
-    FileCreated(PathBuf),
-    FileModified(PathBuf),
-    FileDeleted(PathBuf),
-}
-
 impl FileWatcher {
     /// Create a new file watcher
     pub fn new(repo_root: PathBuf) -> FlashgrepResult<Self> {
@@ -115,8 +213,11 @@ Thumbs.db
         Ok(())
     }
 
-    /// Perform initial scan and emit synthetic events for detected changes
-    pub async fn perform_initial_scan(&mut self) -> FlashgrepResult<ScanResult> {
+    /// Detect changes made while the watcher was down and replay them into the index
+    pub async fn perform_initial_scan(
+        &mut self,
+        progress: &ProgressReporter,
+    ) -> FlashgrepResult<ScanResult> {
         info!("Starting initial index scan...");
 
         let result = run_initial_scan(
@@ -127,9 +228,6 @@ Thumbs.db
         )
         .await?;
 
-        // Emit synthetic events for detected changes
-        // Note: In a full implementation, these would be sent through the event channel
-        // For now, we just log them
         if result.files_added > 0 {
             info!("Detected {} files added while offline", result.files_added);
         }
@@ -145,54 +243,154 @@ Thumbs.db
                 result.files_deleted
             );
         }
+        if result.files_renamed > 0 {
+            info!(
+                "Detected {} files moved while offline",
+                result.files_renamed
+            );
+        }
 
-        // Save the updated index state
+        for error in &result.errors {
+            warn!("Initial scan error: {}", error);
+        }
+
+        if !result.events.is_empty() {
+            info!(
+                "Replaying {} offline changes into the index",
+                result.events.len()
+            );
+            self.apply_synthetic_events(&result.events, progress)?;
+            info!("Index is up to date with offline changes");
+        }
+
+        // Save only once the index has caught up, so an interrupted replay is
+        // detected again on the next start
         self.index_state.save(&self.index_state_path)?;
 
         Ok(result)
     }
 
+    /// Feed changes found by a scan through the same path as live file system events
+    fn apply_synthetic_events(
+        &mut self,
+        events: &[SyntheticEvent],
+        progress: &ProgressReporter,
+    ) -> FlashgrepResult<()> {
+        // Moves keep their chunks and vectors; ones that cannot be re-keyed are
+        // applied as a deletion plus a new file
+        let mut changes = Vec::with_capacity(events.len());
+        for event in events {
+            let SyntheticEvent::FileRenamed(from, to) = event else {
+                changes.push(event.clone());
+                continue;
+            };
+            match self.apply_rename(from, to, true) {
+                Ok(moved) if moved > 0 => {}
+                result => {
+                    if let Err(e) = result {
+                        warn!("Failed to move {} in the index: {}", from.display(), e);
+                    }
+                    changes.push(SyntheticEvent::FileDeleted(from.clone()));
+                    changes.push(SyntheticEvent::FileCreated(to.clone()));
+                }
+            }
+        }
+        let events = changes.as_slice();
+
+        if events.len() >= self.config.watcher_burst_threshold {
+            // Every event is a real change, so skip the mtime check
+            let paths: Vec<PathBuf> = events.iter().map(|e| e.path().to_path_buf()).collect();
+            return self.handle_bulk_change(&paths, true, progress);
+        }
+
+        let total = events.len() as u64;
+        for (done, event) in events.iter().enumerate() {
+            progress.checkpoint(
+                "apply changes",
+                done as u64,
+                Some(total),
+                REPLAY_PROGRESS_INTERVAL,
+            )?;
+            debug!("Applying {:?}", event);
+            // The scan saw new content, which a same-second mtime would hide
+            let force = matches!(event, SyntheticEvent::FileModified(_));
+            if let Err(e) = self.handle_change(event.path(), force) {
+                warn!(
+                    "Failed to apply change for {}: {}",
+                    event.path().display(),
+                    e
+                );
+            }
+        }
+        self.indexer.commit()?;
+        progress.checkpoint(
+            "apply changes",
+            total,
+            Some(total),
+            REPLAY_PROGRESS_INTERVAL,
+        )
+    }
+
     /// Start watching the repository with optional initial scan
     pub async fn watch_with_initial_scan(&mut self) -> FlashgrepResult<()> {
-        info!("Starting file watcher for: {}", self.repo_root.display());
+        self.watch_changes(self.config.enable_initial_index).await
+    }
 
-        // Start the file system watcher immediately (non-blocking)
-        let (tx, rx) = channel();
+    /// Start watching the repository (legacy method without initial scan)
+    pub async fn watch(&mut self) -> FlashgrepResult<()> {
+        self.watch_changes(false).await
+    }
 
-        let mut watcher = RecommendedWatcher::new(
-            move |res: Result<Event, notify::Error>| {
-                if let Ok(event) = res {
-                    let _ = tx.send(event);
+    async fn watch_changes(&mut self, initial_scan: bool) -> FlashgrepResult<()> {
+        info!("Starting file watcher for: {}", self.repo_root.display());
+
+        // Start the native watcher before the initial scan so no change is missed
+        let native = match self.resolve_backend() {
+            WatcherBackend::Poll => None,
+            backend => match self.start_native_watcher() {
+                Ok(native) => Some(native),
+                Err(e) if backend == WatcherBackend::Auto => {
+                    warn!("Native file watching unavailable ({}); polling instead", e);
+                    None
                 }
+                Err(e) => return Err(e),
             },
-            NotifyConfig::default(),
-        )?;
-
-        watcher.watch(&self.repo_root, RecursiveMode::Recursive)?;
-
-        info!("File watcher started, monitoring for changes...");
+        };
 
-        // Perform initial scan if enabled
-        if self.config.enable_initial_index {
+        if initial_scan {
             info!("Starting initial scan in background...");
-            let scan_result = self.perform_initial_scan().await?;
-
-            // Process synthetic events (files detected during scan)
-            self.process_synthetic_changes(&scan_result)?;
+            self.perform_initial_scan(&logging_progress("Replaying offline changes"))
+                .await?;
         } else {
             info!("Initial indexing is disabled");
         }
 
-        // Continue with normal event processing
-        self.process_events(rx)?;
-
-        Ok(())
+        match native {
+            Some((_watcher, rx)) => self.process_events(rx).await,
+            None => self.poll_for_changes().await,
+        }
     }
 
-    /// Start watching the repository (legacy method without initial scan)
-    pub fn watch(&mut self) -> FlashgrepResult<()> {
-        info!("Starting file watcher for: {}", self.repo_root.display());
+    /// Backend to start with: `auto` polls on file systems known not to deliver
+    /// native events and otherwise starts native, probing for missed changes
+    fn resolve_backend(&self) -> WatcherBackend {
+        let backend = self.config.watcher_backend;
+        if backend == WatcherBackend::Auto {
+            if let Some(fs_type) = filesystem_type(&self.repo_root) {
+                if lacks_native_events(&fs_type) {
+                    info!(
+                        "{} is on a {} file system; polling for changes",
+                        self.repo_root.display(),
+                        fs_type
+                    );
+                    return WatcherBackend::Poll;
+                }
+            }
+        }
+        backend
+    }
 
+    fn start_native_watcher(&self) -> FlashgrepResult<(RecommendedWatcher, Receiver<Event>)> {
         let (tx, rx) = channel();
 
         let mut watcher = RecommendedWatcher::new(
@@ -207,77 +405,323 @@ Thumbs.db
         watcher.watch(&self.repo_root, RecursiveMode::Recursive)?;
 
         info!("File watcher started, monitoring for changes...");
+        Ok((watcher, rx))
+    }
 
-        self.process_events(rx)?;
-
-        Ok(())
+    /// Detect changes by rescanning the repository every poll interval
+    async fn poll_for_changes(&mut self) -> FlashgrepResult<()> {
+        info!(
+            "Polling for changes every {}ms",
+            self.config.watcher_poll_interval_ms
+        );
+        loop {
+            tokio::time::sleep(self.poll_interval()).await;
+            self.poll_once().await?;
+        }
     }
 
-    /// Process synthetic changes detected during initial scan
-    fn process_synthetic_changes(&mut self, scan_result: &ScanResult) -> FlashgrepResult<()> {
-        // In a full implementation, this would process the detected changes
-        // and emit events through the same pipeline as real-time events
-        // For now, we just ensure the files are properly indexed
+    /// Rescan against the index state and apply the changes found, returning their number
+    async fn poll_once(&mut self) -> FlashgrepResult<usize> {
+        let result = InitialScanner::new(
+            self.repo_root.clone(),
+            self.config.clone(),
+            self.ignore_patterns.clone(),
+            self.index_state.clone(),
+        )
+        .quiet()
+        .scan()
+        .await?;
 
-        if scan_result.errors.is_empty() {
-            return Ok(());
+        for error in &result.errors {
+            debug!("Poll scan error: {}", error);
         }
-
-        for error in &scan_result.errors {
-            warn!("Initial scan error: {}", error);
+        if result.events.is_empty() {
+            return Ok(0);
         }
 
-        Ok(())
+        info!("Polling found {} changed files", result.events.len());
+        self.apply_synthetic_events(&result.events, &ProgressReporter::disabled())?;
+        self.index_state.save(&self.index_state_path)?;
+        Ok(result.events.len())
     }
 
-    /// Process file system events with debouncing
-    fn process_events(&mut self, rx: Receiver<Event>) -> FlashgrepResult<()> {
-        let mut pending_changes: HashMap<PathBuf, Instant> = HashMap::new();
-        let mut last_update = Instant::now();
+    fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.config.watcher_poll_interval_ms.max(1))
+    }
+
+    /// Process file system events, coalescing bursts.
+    ///
+    /// Changes are applied once events have been quiet for the debounce period.
+    /// A move of `.git/HEAD` (checkout, rebase, reset) or a burst of at least
+    /// `watcher_burst_threshold` paths is reindexed as one parallel batch.
+    ///
+    /// With the `auto` backend, an idle watcher that received no native events
+    /// for a while rescans; changes found by consecutive rescans without any
+    /// native event in between switch it to polling.
+    async fn process_events(&mut self, rx: Receiver<Event>) -> FlashgrepResult<()> {
+        let mut pending = PendingChanges::default();
+        let probing = self.config.watcher_backend == WatcherBackend::Auto;
+        let mut probe = AutoProbe::new(self.poll_interval() * AUTO_PROBE_POLL_INTERVALS);
 
         loop {
-            // Check for new events
-            if let Ok(event) = rx.try_recv() {
-                debug!("File event: {:?}", event);
-
-                for path in event.paths {
-                    if Self::is_ignore_file(&path) {
-                        self.reload_ignore_patterns_and_reconcile()?;
-                        continue;
+            match rx.recv_timeout(EVENT_POLL_INTERVAL) {
+                Ok(event) => {
+                    if self.queue_event(event, &mut pending) {
+                        probe.saw_event();
                     }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            // Drain everything already queued so a burst is seen as a whole
+            while let Ok(event) = rx.try_recv() {
+                if self.queue_event(event, &mut pending) {
+                    probe.saw_event();
+                }
+            }
 
-                    // Skip if path should be ignored
-                    if self.should_ignore_path(&path) {
-                        debug!("Ignoring path: {}", path.display());
-                        continue;
-                    }
+            if pending.is_quiet(self.debounce_duration) {
+                self.flush_changes(std::mem::take(&mut pending))?;
+            }
+
+            if probing && pending.last_event.is_none() && probe.is_due() {
+                let missed = self.poll_once().await?;
+                if probe.record(missed) {
+                    warn!(
+                        "{} changed files produced no file system events; switching to polling",
+                        missed
+                    );
+                    return self.poll_for_changes().await;
+                }
+            }
+        }
+    }
+
+    /// Add the paths of a file system event to the pending changes; returns
+    /// whether any of them is watched
+    fn queue_event(&self, event: Event, pending: &mut PendingChanges) -> bool {
+        let mut watched = false;
+        debug!("File event: {:?}", event);
+
+        if let (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) =
+            (&event.kind, event.paths.as_slice())
+        {
+            pending.renames.push((from.clone(), to.clone()));
+        }
 
-                    pending_changes.insert(path, Instant::now());
+        for path in event.paths {
+            if path == self.repo_root.join(".git").join("HEAD") {
+                pending.head_moved = true;
+            } else if Self::is_ignore_file(&path) {
+                pending.ignore_files_changed = true;
+            } else if self.should_ignore_path(&path) {
+                debug!("Ignoring path: {}", path.display());
+                continue;
+            } else {
+                pending.paths.insert(path);
+            }
+            pending.last_event = Some(Instant::now());
+            watched = true;
+        }
+        watched
+    }
+
+    /// Apply pending changes, as one batch if they look like a bulk change
+    fn flush_changes(&mut self, pending: PendingChanges) -> FlashgrepResult<()> {
+        if pending.ignore_files_changed {
+            self.reload_ignore_patterns_and_reconcile()?;
+        }
+        let mut paths = pending.paths;
+        let mut moved = self.apply_renames(&pending.renames, false, &mut paths);
+        let detected = self.detect_moved_files(&paths)?;
+        moved += self.apply_renames(&detected, true, &mut paths);
+        if paths.is_empty() {
+            return if moved > 0 {
+                self.indexer.commit()
+            } else {
+                Ok(())
+            };
+        }
+
+        let bulk = pending.head_moved || paths.len() >= self.config.watcher_burst_threshold;
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        if bulk {
+            info!(
+                "Coalescing {} changed paths{} into one batch",
+                paths.len(),
+                if pending.head_moved {
+                    " after a HEAD move"
+                } else {
+                    ""
                 }
+            );
+            return self.handle_bulk_change(&paths, false, &logging_progress("Indexing changes"));
+        }
+
+        for path in &paths {
+            if let Err(e) = self.handle_change(path, false) {
+                warn!("Failed to handle change for {}: {}", path.display(), e);
             }
+        }
+        self.indexer.commit()
+    }
 
-            // Process pending changes if debounce period has passed
-            let now = Instant::now();
-            if now.duration_since(last_update) >= self.debounce_duration {
-                let ready_changes: Vec<_> = pending_changes
-                    .iter()
-                    .filter(|(_, instant)| now.duration_since(**instant) >= self.debounce_duration)
-                    .map(|(path, _)| path.clone())
-                    .collect();
-
-                for path in ready_changes {
-                    pending_changes.remove(&path);
-                    if let Err(e) = self.handle_change(&path) {
-                        warn!("Failed to handle change for {}: {}", path.display(), e);
+    /// Re-key moved files, dropping their old paths from `paths`. The new paths
+    /// stay queued so a file edited as well as moved is still reindexed. Returns
+    /// the number of files moved.
+    fn apply_renames(
+        &mut self,
+        renames: &[(PathBuf, PathBuf)],
+        same_content: bool,
+        paths: &mut BTreeSet<PathBuf>,
+    ) -> usize {
+        let mut total = 0;
+        for (from, to) in renames {
+            match self.apply_rename(from, to, same_content) {
+                Ok(moved) => {
+                    if moved > 0 {
+                        paths.remove(from);
                     }
+                    total += moved;
                 }
+                Err(e) => warn!("Failed to move {} in the index: {}", from.display(), e),
+            }
+        }
+        total
+    }
+
+    /// Pair deleted paths with new files whose content matches what was indexed
+    /// at the deleted path, e.g. a move seen as a deletion plus a creation
+    fn detect_moved_files(
+        &self,
+        paths: &BTreeSet<PathBuf>,
+    ) -> FlashgrepResult<Vec<(PathBuf, PathBuf)>> {
+        let mut deleted: HashMap<String, Vec<(PathBuf, FileMetadata)>> = HashMap::new();
+        let mut created = Vec::new();
+        for path in paths {
+            let rel_path = path.strip_prefix(&self.repo_root).unwrap_or(path);
+            if !path.exists() {
+                if let Some(metadata) = self.index_state.get_file(rel_path)? {
+                    deleted
+                        .entry(metadata.content_hash.clone())
+                        .or_default()
+                        .push((path.clone(), metadata));
+                }
+            } else if path.is_file() && !self.index_state.has_file(rel_path)? {
+                created.push(path);
+            }
+        }
+        if deleted.is_empty() {
+            return Ok(Vec::new());
+        }
 
-                last_update = now;
+        let mut moves = Vec::new();
+        for path in created {
+            let Ok(metadata) = FileMetadata::read(path) else {
+                continue;
+            };
+            let Some(candidates) = deleted.get_mut(&metadata.content_hash) else {
+                continue;
+            };
+            if let Some(position) = candidates
+                .iter()
+                .position(|(_, previous)| previous.is_same_content(&metadata))
+            {
+                let (from, _) = candidates.swap_remove(position);
+                moves.push((from, path.clone()));
             }
+        }
+        Ok(moves)
+    }
+
+    /// Re-key an indexed file, or every indexed file under a moved directory, so
+    /// its chunks, vectors and graph nodes are kept. `same_content` is set when the
+    /// move was matched by content; otherwise content is compared with the index
+    /// state. Returns the number of files moved.
+    fn apply_rename(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        same_content: bool,
+    ) -> FlashgrepResult<usize> {
+        let moves: Vec<(PathBuf, PathBuf)> = if to.is_dir() {
+            self.indexer
+                .db()
+                .get_all_files()?
+                .into_iter()
+                .filter_map(|old| {
+                    let new = to.join(old.strip_prefix(from).ok()?);
+                    Some((old, new))
+                })
+                .collect()
+        } else {
+            vec![(from.to_path_buf(), to.to_path_buf())]
+        };
 
-            // Small sleep to prevent busy waiting
-            std::thread::sleep(Duration::from_millis(10));
+        let mut moved = 0;
+        for (old, new) in moves {
+            if !new.is_file() {
+                continue;
+            }
+            let old_rel = old.strip_prefix(&self.repo_root).unwrap_or(&old);
+            if self.should_ignore_path(&new) {
+                // Moved somewhere that is not indexed
+                self.indexer.purge_file(&old)?;
+                self.index_state.remove_file(old_rel)?;
+                continue;
+            }
+            let previous = self.index_state.get_file(old_rel)?;
+            if !self.indexer.rename_file(&old, &new)? {
+                continue;
+            }
+            self.index_state.remove_file(old_rel)?;
+
+            let current = FileMetadata::read(&new)?;
+            if same_content || previous.is_some_and(|p| p.is_same_content(&current)) {
+                // Rewritten with the same content (e.g. by git): record the new mtime
+                // so the file is not reindexed
+                self.indexer.db().set_file_mtime(&new, current.mtime)?;
+            }
+            let new_rel = new.strip_prefix(&self.repo_root).unwrap_or(&new);
+            self.index_state
+                .update_file(new_rel.to_path_buf(), current)?;
+            moved += 1;
+        }
+
+        if moved > 0 {
+            info!(
+                "Moved {} indexed files from {} to {}",
+                moved,
+                from.display(),
+                to.display()
+            );
+        }
+        Ok(moved)
+    }
+
+    /// Reindex many changed paths through the parallel pipeline with one commit
+    fn handle_bulk_change(
+        &mut self,
+        paths: &[PathBuf],
+        force: bool,
+        progress: &ProgressReporter,
+    ) -> FlashgrepResult<()> {
+        let summary = self.indexer.index_changed_paths(paths, force, progress)?;
+        if summary.failed > 0 {
+            warn!("{} changed files failed to index", summary.failed);
+        }
+
+        for path in paths {
+            if path.is_file() {
+                if let Err(e) = self.update_index_state_for_file(path) {
+                    debug!("Failed to update state for {}: {}", path.display(), e);
+                }
+            } else {
+                let rel_path = path.strip_prefix(&self.repo_root).unwrap_or(path);
+                self.index_state.remove_file(rel_path)?;
+            }
         }
+        self.index_state.save(&self.index_state_path)
     }
 
     /// Check if a path should be ignored by the file watcher
@@ -309,17 +753,10 @@ Thumbs.db
             return true;
         }
 
-        // Skip binary files early (for file watcher efficiency)
-        if path.is_file() {
-            // Quick extension check first
-            if !should_index_file(path, &self.config) {
-                return true;
-            }
-
-            // Skip oversized files
-            if let Ok(true) = is_oversized_file(path, self.config.max_file_size) {
-                return true;
-            }
+        // Skip unsupported file types early (for file watcher efficiency); size and
+        // content are classified by the indexer, which records skipped files
+        if path.is_file() && !should_index_file(path, &self.config) {
+            return true;
         }
 
         false
@@ -344,8 +781,8 @@ Thumbs.db
         Ok(())
     }
 
-    /// Handle a single file change
-    fn handle_change(&mut self, path: &Path) -> FlashgrepResult<()> {
+    /// Handle a single file change; `force` reindexes even if the mtime is unchanged
+    fn handle_change(&mut self, path: &Path, force: bool) -> FlashgrepResult<()> {
         if self.ignore_patterns.is_ignored(path, &self.repo_root) {
             debug!(
                 "Path became ignored, pruning if indexed: {}",
@@ -366,15 +803,14 @@ Thumbs.db
             let rel_path = path.strip_prefix(&self.repo_root).unwrap_or(path);
             self.index_state.remove_file(rel_path)?;
         } else if path.is_file() {
-            // Skip binary files during indexing
-            if let Ok(true) = is_binary_file(path) {
-                debug!("Skipping binary file: {}", path.display());
-                return Ok(());
-            }
-
             // File was created or modified
             info!("File changed: {}", path.display());
-            match self.indexer.index_file(path) {
+            let result = if force {
+                self.indexer.force_reindex_file(path)
+            } else {
+                self.indexer.index_file(path)
+            };
+            match result {
                 Ok(indexed) => {
                     if indexed {
                         debug!("Successfully indexed: {}", path.display());
@@ -393,38 +829,9 @@ Thumbs.db
 
     /// Update index state for a single file
     fn update_index_state_for_file(&mut self, path: &Path) -> FlashgrepResult<()> {
-        use crate::index::state::FileMetadata;
-        use sha2::{Digest, Sha256};
-        use std::time::SystemTime;
-
         let rel_path = path.strip_prefix(&self.repo_root).unwrap_or(path);
-
-        let metadata = std::fs::metadata(path)?;
-        let size = metadata.len();
-        let mtime = metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs() as i64)
-            .unwrap_or(0);
-
-        // Compute content hash (first 8KB only)
-        let content = std::fs::read(path)?;
-        let hash_input = if content.len() > 8192 {
-            &content[..8192]
-        } else {
-            &content
-        };
-        let content_hash = hex::encode(Sha256::digest(hash_input));
-
-        let file_metadata = FileMetadata {
-            size,
-            mtime,
-            content_hash,
-        };
-
         self.index_state
-            .update_file(rel_path.to_path_buf(), file_metadata)?;
+            .update_file(rel_path.to_path_buf(), FileMetadata::read(path)?)?;
 
         // Periodically save index state (every 100 changes)
         // In production, this should be debounced
@@ -436,6 +843,66 @@ Thumbs.db
     }
 }
 
+/// Type of the file system holding `path`, where the platform exposes it
+fn filesystem_type(path: &Path) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    let path = path.canonicalize().ok()?;
+    mount_filesystem_type(&mounts, &path)
+}
+
+/// File system type of the deepest mount in `/proc/mounts` format containing `path`
+fn mount_filesystem_type(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point)
+                .then(|| (mount_point.components().count(), fs_type.to_string()))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// Decode the octal escapes (`\040` for a space) used in mount table fields
+fn unescape_mount_field(field: &str) -> PathBuf {
+    let mut decoded = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        decoded.push_str(&rest[..index]);
+        let escape = rest.get(index + 1..index + 4);
+        match escape.and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                decoded.push(char::from(byte));
+                rest = &rest[index + 4..];
+            }
+            None => {
+                decoded.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    PathBuf::from(decoded)
+}
+
+fn lacks_native_events(fs_type: &str) -> bool {
+    POLLING_FILESYSTEMS.contains(&fs_type)
+}
+
+/// Progress reporter that logs updates, for the watcher which has no client to notify
+fn logging_progress(label: &'static str) -> ProgressReporter {
+    let sink: ProgressSink = Arc::new(move |done, total, _| {
+        info!("{}: {}/{}", label, done, total.unwrap_or(done));
+    });
+    ProgressReporter::new(Some(sink), CancellationToken::new())
+}
+
 impl Drop for FileWatcher {
     fn drop(&mut self) {
         // Save index state on shutdown
@@ -525,6 +992,255 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_initial_scan_replays_offline_changes() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::write(repo_root.join("kept.rs"), "fn kept() {}\n")?;
+        std::fs::write(repo_root.join("edited.rs"), "fn before_pull() {}\n")?;
+        std::fs::write(repo_root.join("removed.rs"), "fn removed() {}\n")?;
+
+        let mut watcher = FileWatcher::new(repo_root.clone())?;
+        watcher.indexer.index_repository(&repo_root)?;
+        watcher
+            .perform_initial_scan(&ProgressReporter::disabled())
+            .await?;
+        drop(watcher);
+
+        // Changes made while no watcher is running, e.g. by `git pull`
+        std::fs::write(repo_root.join("edited.rs"), "fn after_pull() {}\n")?;
+        std::fs::write(repo_root.join("added.rs"), "fn added() {}\n")?;
+        std::fs::remove_file(repo_root.join("removed.rs"))?;
+
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_reported = reported.clone();
+        let sink: ProgressSink = Arc::new(move |done, total, _| {
+            sink_reported.lock().unwrap().push((done, total));
+        });
+        let mut watcher = FileWatcher::new(repo_root.clone())?;
+        let result = watcher
+            .perform_initial_scan(&ProgressReporter::new(Some(sink), CancellationToken::new()))
+            .await?;
+        assert_eq!(
+            (
+                result.files_added,
+                result.files_modified,
+                result.files_deleted
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(reported.lock().unwrap().last(), Some(&(3, Some(3))));
+
+        let files = watcher.indexer.db().get_all_files()?;
+        assert!(files.contains(&repo_root.join("added.rs")));
+        assert!(files.contains(&repo_root.join("kept.rs")));
+        assert!(!files.contains(&repo_root.join("removed.rs")));
+        let db = watcher.indexer.db();
+        assert_eq!(db.find_symbols_by_name("after_pull")?.len(), 1);
+        assert!(db.find_symbols_by_name("before_pull")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_burst_of_changes_is_indexed_as_one_batch() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        for name in ["a", "b", "c"] {
+            std::fs::write(
+                repo_root.join(format!("{}.rs", name)),
+                format!("fn {}_main() {{}}\n", name),
+            )?;
+        }
+        let mut watcher = FileWatcher::new(repo_root.clone())?;
+        watcher.indexer.index_repository(&repo_root)?;
+        watcher.config.watcher_burst_threshold = 3;
+
+        // What a branch switch leaves behind: edits, a deletion and a new file
+        let later = std::time::SystemTime::now() + Duration::from_secs(5);
+        for (name, content) in [
+            ("a.rs", "fn a_feature() {}\n"),
+            ("b.rs", "fn b_feature() {}\n"),
+        ] {
+            std::fs::write(repo_root.join(name), content)?;
+            std::fs::File::options()
+                .write(true)
+                .open(repo_root.join(name))?
+                .set_modified(later)?;
+        }
+        std::fs::remove_file(repo_root.join("c.rs"))?;
+        std::fs::write(repo_root.join("d.rs"), "fn d_feature() {}\n")?;
+
+        let mut pending = PendingChanges::default();
+        for name in ["a.rs", "b.rs", "c.rs", "d.rs"] {
+            let event = Event::new(notify::EventKind::Any).add_path(repo_root.join(name));
+            watcher.queue_event(event, &mut pending);
+        }
+        assert!(!pending.is_quiet(Duration::from_secs(60)));
+        assert!(pending.is_quiet(Duration::ZERO));
+        watcher.flush_changes(pending)?;
+
+        let db = watcher.indexer.db();
+        let files = db.get_all_files()?;
+        assert!(!files.contains(&repo_root.join("c.rs")));
+        assert!(files.contains(&repo_root.join("d.rs")));
+        assert!(db.find_symbols_by_name("a_main")?.is_empty());
+        assert_eq!(db.find_symbols_by_name("b_feature")?.len(), 1);
+
+        // The batch is committed to the text index
+        let paths = FlashgrepPaths::new(&repo_root);
+        let index = tantivy::Index::open_in_dir(paths.text_index_dir())?;
+        let searcher = crate::search::Searcher::new(&index, &paths.metadata_db())?;
+        assert_eq!(searcher.query("d_feature", 10)?.len(), 1);
+        assert!(searcher.query("c_main", 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_files_keep_their_chunks() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::create_dir_all(repo_root.join("old_dir"))?;
+        std::fs::write(repo_root.join("moved.rs"), "fn moved_by_editor() {}\n")?;
+        std::fs::write(repo_root.join("old_dir/inner.rs"), "fn inner_module() {}\n")?;
+
+        let mut watcher = FileWatcher::new(repo_root.clone())?;
+        watcher.indexer.index_repository(&repo_root)?;
+        for name in ["moved.rs", "old_dir/inner.rs"] {
+            watcher.update_index_state_for_file(&repo_root.join(name))?;
+        }
+        let chunk_id = |watcher: &FileWatcher, path: &Path| -> FlashgrepResult<Option<i64>> {
+            Ok(watcher.indexer.db().get_file_chunks(path)?[0].id)
+        };
+        let moved_id = chunk_id(&watcher, &repo_root.join("moved.rs"))?;
+        let inner_id = chunk_id(&watcher, &repo_root.join("old_dir/inner.rs"))?;
+
+        // A delete plus a create with the same content, and a reported directory move
+        std::fs::create_dir_all(repo_root.join("src"))?;
+        std::fs::rename(repo_root.join("moved.rs"), repo_root.join("src/moved.rs"))?;
+        std::fs::rename(repo_root.join("old_dir"), repo_root.join("new_dir"))?;
+        let mut pending = PendingChanges::default();
+        for name in ["moved.rs", "src/moved.rs"] {
+            let event = Event::new(EventKind::Any).add_path(repo_root.join(name));
+            watcher.queue_event(event, &mut pending);
+        }
+        let event = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(repo_root.join("old_dir"))
+            .add_path(repo_root.join("new_dir"));
+        watcher.queue_event(event, &mut pending);
+        assert_eq!(pending.renames.len(), 1);
+        watcher.flush_changes(pending)?;
+
+        let mut files = watcher.indexer.db().get_all_files()?;
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                repo_root.join("new_dir/inner.rs"),
+                repo_root.join("src/moved.rs")
+            ]
+        );
+        assert_eq!(
+            chunk_id(&watcher, &repo_root.join("src/moved.rs"))?,
+            moved_id
+        );
+        assert_eq!(
+            chunk_id(&watcher, &repo_root.join("new_dir/inner.rs"))?,
+            inner_id
+        );
+        assert!(watcher
+            .index_state
+            .has_file(Path::new("new_dir/inner.rs"))?);
+        assert!(!watcher.index_state.has_file(Path::new("moved.rs"))?);
+
+        let paths = FlashgrepPaths::new(&repo_root);
+        let index = tantivy::Index::open_in_dir(paths.text_index_dir())?;
+        let searcher = crate::search::Searcher::new(&index, &paths.metadata_db())?;
+        let results = searcher.query("moved_by_editor", 10)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file_path, repo_root.join("src/moved.rs"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_head_move_marks_changes_as_bulk() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        let watcher = FileWatcher::new(repo_root.clone())?;
+
+        let mut pending = PendingChanges::default();
+        let event = Event::new(notify::EventKind::Any)
+            .add_path(repo_root.join(".git/HEAD"))
+            .add_path(repo_root.join(".git/objects/ab/cdef"))
+            .add_path(repo_root.join(".gitignore"));
+        watcher.queue_event(event, &mut pending);
+
+        assert!(pending.head_moved);
+        assert!(pending.ignore_files_changed);
+        assert!(pending.paths.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_polling_detects_and_applies_changes() -> FlashgrepResult<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_root = temp_dir.path().to_path_buf();
+        std::fs::write(repo_root.join("mounted.rs"), "fn before_poll() {}\n")?;
+
+        let mut watcher = FileWatcher::new(repo_root.clone())?;
+        watcher.config.watcher_backend = WatcherBackend::Poll;
+        assert_eq!(watcher.resolve_backend(), WatcherBackend::Poll);
+        watcher
+            .perform_initial_scan(&ProgressReporter::disabled())
+            .await?;
+        assert_eq!(watcher.poll_once().await?, 0);
+
+        std::fs::write(repo_root.join("mounted.rs"), "fn after_poll() {}\n")?;
+        std::fs::write(repo_root.join("new.rs"), "fn new_on_share() {}\n")?;
+        assert_eq!(watcher.poll_once().await?, 2);
+        assert_eq!(watcher.poll_once().await?, 0);
+
+        let db = watcher.indexer.db();
+        assert_eq!(db.find_symbols_by_name("after_poll")?.len(), 1);
+        assert_eq!(db.find_symbols_by_name("new_on_share")?.len(), 1);
+        assert!(db.find_symbols_by_name("before_poll")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_probe_needs_consecutive_misses_without_events() {
+        let mut probe = AutoProbe::new(Duration::ZERO);
+        assert!(probe.is_due());
+        assert!(!probe.record(1));
+        // Native events in the interval skip the rescan and clear the misses
+        probe.saw_event();
+        assert!(!probe.is_due());
+        assert!(probe.is_due());
+        assert!(!probe.record(1));
+        assert!(!probe.record(0));
+        assert!(!probe.record(3));
+        assert!(probe.record(1));
+
+        let mut idle = AutoProbe::new(Duration::from_secs(3600));
+        assert!(!idle.is_due());
+    }
+
+    #[test]
+    fn test_mount_filesystem_type_uses_deepest_mount() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      server:/export /mnt/share nfs4 rw 0 0\n\
+                      C:\\134 /mnt/c drvfs rw 0 0\n\
+                      host /mnt/my\\040files fuse.sshfs rw 0 0\n";
+        let fs_type = |path: &str| mount_filesystem_type(mounts, Path::new(path));
+
+        assert_eq!(fs_type("/home/dev/repo").as_deref(), Some("ext4"));
+        assert_eq!(fs_type("/mnt/share/repo").as_deref(), Some("nfs4"));
+        assert_eq!(fs_type("/mnt/c/Users/dev").as_deref(), Some("drvfs"));
+        assert_eq!(fs_type("/mnt/my files/repo").as_deref(), Some("fuse.sshfs"));
+        assert_eq!(fs_type("/mnt/shared").as_deref(), Some("ext4"));
+        assert!(lacks_native_events("nfs4"));
+        assert!(!lacks_native_events("ext4"));
+    }
+
     #[test]
     fn test_detects_both_ignore_files() {
         assert!(FileWatcher::is_ignore_file(&PathBuf::from(
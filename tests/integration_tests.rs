@@ -1,11 +1,11 @@
 use flashgrep::chunking::Chunker;
 use flashgrep::config::paths::FlashgrepPaths;
 use flashgrep::config::Config;
-use flashgrep::db::models::{FileMetadata, SymbolType};
+use flashgrep::db::models::{BlameInfo, FileMetadata, SymbolType};
 use flashgrep::db::Database;
 use flashgrep::index::engine::Indexer;
 use flashgrep::index::scanner::{FileScanner, FlashgrepIgnore};
-use flashgrep::search::Searcher;
+use flashgrep::search::{QueryMode, QueryOptions, Searcher};
 use flashgrep::symbols::SymbolDetector;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -150,6 +150,7 @@ fn test_database_basic_operations() {
         file_size: 100,
         last_modified: 1234567890,
         language: Some("rust".to_string()),
+        encoding: None,
     };
     let file_id = db.insert_file(&file).expect("Failed to insert file");
     assert!(file_id > 0);
@@ -177,6 +178,7 @@ fn test_database_batch_inserts() {
         file_size: 100,
         last_modified: 1234567890,
         language: Some("rust".to_string()),
+        encoding: None,
     };
     db.insert_file(&file).unwrap();
 
@@ -426,6 +428,117 @@ fn test_ignored_directories_do_not_appear_in_files_query_symbol() {
     assert!(query_hits.is_empty());
 }
 
+#[test]
+fn test_language_filter_includes_extensionless_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+
+    create_test_file(
+        &repo_root,
+        "main.rs",
+        "fn deploy_target() { let _ = \"LANG_TOKEN\"; }",
+    );
+    create_test_file(
+        &repo_root,
+        "deploy",
+        "#!/usr/bin/env bash\ndeploy_target() {\n  echo LANG_TOKEN\n}\n",
+    );
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+
+    let mut options = QueryOptions::new("LANG_TOKEN".to_string(), 10);
+    options.languages = vec!["bash".to_string()];
+    let hits = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].file_path.ends_with("deploy"));
+
+    let shell_files = searcher
+        .language_files(&["sh".to_string()])
+        .unwrap()
+        .expect("language filter");
+    assert_eq!(shell_files.len(), 1);
+    assert!(searcher.language_files(&[]).unwrap().is_none());
+
+    let rust_symbols: Vec<_> = searcher
+        .get_symbol("deploy_target")
+        .unwrap()
+        .into_iter()
+        .filter(|s| {
+            searcher
+                .language_files(&["rust".to_string()])
+                .unwrap()
+                .is_some_and(|files| files.contains(&s.file_path))
+        })
+        .collect();
+    assert_eq!(rust_symbols.len(), 1);
+    assert!(rust_symbols[0].file_path.ends_with("main.rs"));
+}
+
+#[test]
+fn test_structured_query_applies_field_filters() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(repo_root.join("src/mcp")).unwrap();
+    fs::create_dir_all(repo_root.join("src/cli")).unwrap();
+
+    create_test_file(
+        &repo_root,
+        "src/mcp/server.rs",
+        "fn handle_request() {\n    let _ = \"DSL_TOKEN exact phrase\";\n}\n",
+    );
+    create_test_file(
+        &repo_root,
+        "src/mcp/server_test.rs",
+        "fn handle_request() {\n    let _ = \"DSL_TOKEN exact phrase test\";\n}\n",
+    );
+    create_test_file(
+        &repo_root,
+        "src/cli/main.rs",
+        "fn run() {\n    let _ = \"DSL_TOKEN exact phrase\";\n}\n",
+    );
+    create_test_file(
+        &repo_root,
+        "src/mcp/notes.py",
+        "def handle_request():\n    return \"DSL_TOKEN exact phrase\"\n",
+    );
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    let query = |text: &str| {
+        let mut options = QueryOptions::new(text.to_string(), 10);
+        options.mode = QueryMode::Structured;
+        searcher.query_with_options(&options)
+    };
+
+    let hits = query(r#"path:src/mcp lang:rust sym:handle_request "exact phrase" -test case:yes"#)
+        .unwrap()
+        .results;
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].file_path.ends_with("src/mcp/server.rs"));
+
+    let hits = query("dsl_token -path:src/cli case:no").unwrap().results;
+    assert_eq!(hits.len(), 3);
+    assert!(query("dsl_token case:yes").unwrap().results.is_empty());
+
+    let hits = query("path:*.py DSL_TOKEN").unwrap().results;
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].file_path.ends_with("notes.py"));
+
+    let err = query("DSL_TOKEN lang:").unwrap_err().to_string();
+    assert!(
+        err.contains("missing value after 'lang:' at column 16"),
+        "{}",
+        err
+    );
+}
+
 #[test]
 fn test_ignore_file_update_prunes_newly_ignored_indexed_files() {
     let temp_dir = TempDir::new().unwrap();
@@ -471,6 +584,21 @@ fn test_ignore_file_update_prunes_newly_ignored_indexed_files() {
     let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
     let hits = searcher.query("TRANSIENT_IGNORE_TOKEN", 10).unwrap();
     assert!(hits.is_empty());
+
+    // Files the patterns stop ignoring are indexed again
+    fs::write(repo_root.join(".flashgrepignore"), "# empty again\n").unwrap();
+    let ignore = FlashgrepIgnore::from_root(&repo_root);
+    let (removed, _kept) = indexer
+        .reconcile_ignored_files(&repo_root, &ignore)
+        .unwrap();
+    assert_eq!(removed, 0);
+    assert_eq!(
+        indexer.db().get_all_files().unwrap().len(),
+        before_files.len()
+    );
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    let hits = searcher.query("TRANSIENT_IGNORE_TOKEN", 10).unwrap();
+    assert_eq!(hits.len(), 1);
 }
 
 #[test]
@@ -539,3 +667,346 @@ fn test_runtime_docs_skills_parity_for_ai_governance_fields() {
     assert!(troubleshooting.contains("prompt_hash"));
     assert!(troubleshooting.contains("budget_profile"));
 }
+
+/// Run git in `root` for test setup
+fn git(root: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "-c",
+            "user.name=flashgrep",
+            "-c",
+            "user.email=flashgrep@example.com",
+            "-c",
+            "commit.gpgsign=false",
+        ])
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn test_query_indexed_revision_separately_from_working_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    git(&repo_root, &["init", "-q", "-b", "main"]);
+    create_test_file(&repo_root, "lib.rs", "pub fn committed_marker() {}\n");
+    create_test_file(&repo_root, "notes.bin", "not indexed\n");
+    git(&repo_root, &["add", "."]);
+    git(&repo_root, &["commit", "-q", "-m", "initial"]);
+    create_test_file(&repo_root, "lib.rs", "pub fn working_marker() {}\n");
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+    let summary = indexer.index_revision("main").unwrap();
+    assert_eq!(summary.files, 1);
+    assert_eq!(summary.commit_id.len(), 40);
+
+    // Reindexing the working-tree file must leave the revision's documents alone
+    indexer
+        .force_reindex_file(&repo_root.join("lib.rs"))
+        .unwrap();
+    indexer.commit().unwrap();
+
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    let search = |text: &str, rev: Option<&str>| {
+        let mut options = QueryOptions::new(text.to_string(), 10);
+        options.revision = rev.map(ToString::to_string);
+        searcher.query_with_options(&options).map(|r| r.results)
+    };
+
+    assert!(search("committed_marker", None).unwrap().is_empty());
+    assert_eq!(search("working_marker", None).unwrap().len(), 1);
+    assert!(search("working_marker", Some("main")).unwrap().is_empty());
+    let hits = search("committed_marker", Some("main")).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].file_path, repo_root.join("lib.rs"));
+    assert_eq!(
+        hits[0].revision.as_deref(),
+        Some(summary.commit_id.as_str())
+    );
+    assert_eq!(
+        search("committed_marker", Some(&summary.commit_id[..10]))
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(search("committed_marker", Some("other")).is_err());
+
+    let report = flashgrep::index::doctor::DoctorReport::check(&repo_root).unwrap();
+    assert!(report.orphaned_documents.is_empty());
+}
+
+#[test]
+fn test_indexed_revisions_do_not_crowd_out_working_tree_hits() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    git(&repo_root, &["init", "-q", "-b", "main"]);
+    // Committed files mention the token often, so their documents score higher
+    for i in 0..40 {
+        create_test_file(
+            &repo_root,
+            &format!("f{i}.rs"),
+            &format!("fn crowd_{i}() {{ crowd_token(); }}\n").repeat(5),
+        );
+    }
+    git(&repo_root, &["add", "."]);
+    git(&repo_root, &["commit", "-q", "-m", "initial"]);
+    for i in 0..40 {
+        create_test_file(&repo_root, &format!("f{i}.rs"), "fn quiet() {}\n");
+    }
+    create_test_file(
+        &repo_root,
+        "f0.rs",
+        "fn quiet() {}\nfn once() { crowd_token(); }\n",
+    );
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_revision("main").unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    let hits = searcher
+        .query_with_options(&QueryOptions::new("crowd_token".to_string(), 1))
+        .unwrap()
+        .results;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].file_path, repo_root.join("f0.rs"));
+    assert!(hits[0].revision.is_none());
+
+    // Reconciling ignore patterns drops the newly ignored file but keeps the revision
+    create_test_file(&repo_root, ".gitignore", "f1.rs\n");
+    let ignore = FlashgrepIgnore::from_root(&repo_root);
+    let (removed, _) = indexer
+        .reconcile_ignored_files(&repo_root, &ignore)
+        .unwrap();
+    assert_eq!(removed, 1);
+    assert_eq!(indexer.db().get_revisions().unwrap().len(), 1);
+    let mut options = QueryOptions::new("crowd_token".to_string(), 100);
+    options.revision = Some("main".to_string());
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    assert_eq!(
+        searcher.query_with_options(&options).unwrap().results.len(),
+        40
+    );
+}
+
+#[test]
+fn test_query_changed_since_limits_results_and_marks_hunks() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    git(&repo_root, &["init", "-q", "-b", "main"]);
+    create_test_file(&repo_root, "stable.rs", "fn stable() { shared_token(); }\n");
+    create_test_file(&repo_root, "edited.rs", "fn edited() {}\n");
+    git(&repo_root, &["add", "."]);
+    git(&repo_root, &["commit", "-q", "-m", "initial"]);
+    create_test_file(
+        &repo_root,
+        "edited.rs",
+        "fn edited() {}\nfn added() { shared_token(); }\n",
+    );
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+
+    let mut options = QueryOptions::new("shared_token".to_string(), 10);
+    assert_eq!(
+        searcher.query_with_options(&options).unwrap().results.len(),
+        2
+    );
+
+    options.changed_since = Some("HEAD".to_string());
+    let results = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].file_path, repo_root.join("edited.rs"));
+    assert_eq!(
+        results[0].changed_hunks,
+        vec![flashgrep::db::models::LineRange::new(2, 2)]
+    );
+
+    options.changed_since = Some("no-such-ref".to_string());
+    assert!(searcher.query_with_options(&options).is_err());
+}
+
+#[test]
+fn test_query_with_blame_attaches_last_commit() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    git(&repo_root, &["init", "-q", "-b", "main"]);
+    create_test_file(&repo_root, "owned.rs", "fn owned_token() {}\n");
+    git(&repo_root, &["add", "owned.rs"]);
+    git(&repo_root, &["commit", "-q", "-m", "Add owned token"]);
+    create_test_file(&repo_root, "fresh.rs", "fn fresh_token() {}\n");
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+
+    let mut options = QueryOptions::new("owned_token".to_string(), 10);
+    assert!(searcher.query_with_options(&options).unwrap().results[0]
+        .blame
+        .is_none());
+
+    options.with_blame = true;
+    let results = searcher.query_with_options(&options).unwrap().results;
+    let blame = results[0].blame.clone().expect("blame");
+    assert_eq!(blame.summary, "Add owned token");
+    assert_eq!(blame.author, "flashgrep");
+    assert_eq!(blame.commit_id.len(), 40);
+    // Cached by file and content hash, so a second query does not need git
+    let owned = repo_root.join("owned.rs");
+    let content_hash = indexer.db().get_file_chunks(&owned).unwrap()[0]
+        .content_hash
+        .clone();
+    assert_eq!(
+        indexer
+            .db()
+            .get_cached_blame(&owned, &content_hash)
+            .unwrap(),
+        Some(blame.clone())
+    );
+    let marked = BlameInfo {
+        summary: "served from the cache".to_string(),
+        ..blame
+    };
+    indexer
+        .db()
+        .cache_blame(&owned, &content_hash, &marked)
+        .unwrap();
+    let cached = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(cached[0].blame, Some(marked));
+
+    // Untracked files have no history to attach
+    options.text = "fresh_token".to_string();
+    let results = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].blame.is_none());
+}
+
+#[test]
+fn test_identical_chunks_are_stored_once_and_collapsed_in_results() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    let vendored = "pub fn vendored_helper() { dedup_token(); }\n";
+    fs::create_dir_all(repo_root.join("fixtures/a")).unwrap();
+    fs::create_dir_all(repo_root.join("fixtures/b")).unwrap();
+    create_test_file(&repo_root, "fixtures/a/util.rs", vendored);
+    create_test_file(&repo_root, "fixtures/b/util.rs", vendored);
+    create_test_file(&repo_root, "main.rs", "fn main() { dedup_token(); }\n");
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    let stats = indexer.index_repository(&repo_root).unwrap();
+    assert_eq!(stats.total_chunks, 3);
+    assert_eq!(stats.unique_chunks, 2);
+
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    let mut options = QueryOptions::new("dedup_token".to_string(), 10);
+    let results = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(results.len(), 2);
+    let (main, duplicate) = if results[0].file_path == repo_root.join("main.rs") {
+        (&results[0], &results[1])
+    } else {
+        (&results[1], &results[0])
+    };
+    assert_eq!(main.file_path, repo_root.join("main.rs"));
+    assert!(main.also_in.is_empty());
+
+    let vendored_paths = [
+        repo_root.join("fixtures/a/util.rs"),
+        repo_root.join("fixtures/b/util.rs"),
+    ];
+    assert!(vendored_paths.contains(&duplicate.file_path));
+    assert_eq!(duplicate.also_in.len(), 1);
+    assert!(vendored_paths.contains(&duplicate.also_in[0].file_path));
+    assert_ne!(duplicate.also_in[0].file_path, duplicate.file_path);
+
+    // A later page does not repeat the collapsed copy
+    options.limit = 1;
+    let first = searcher.query_with_options(&options).unwrap();
+    options.offset = first.next_offset.unwrap();
+    let second = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(second.len(), 1);
+    let pages = [&first.results[0].file_path, &second[0].file_path];
+    assert!(pages.contains(&&repo_root.join("main.rs")));
+    assert_eq!(
+        pages.iter().filter(|p| vendored_paths.contains(p)).count(),
+        1
+    );
+}
+
+#[test]
+fn test_context_previews_come_from_the_content_store() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    create_test_file(
+        &repo_root,
+        "lib.rs",
+        "fn before() {}\n\nfn preview_target() {}\n\nfn after() {}\n",
+    );
+
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+
+    // Previews reflect what was indexed, not the file as it is now
+    create_test_file(&repo_root, "lib.rs", "rewritten\n");
+
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+    let mut options = QueryOptions::new("preview_target".to_string(), 10);
+    options.context = 1;
+    let results = searcher.query_with_options(&options).unwrap().results;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].preview.contains("fn preview_target() {}"));
+    assert!(!results[0].preview.contains("rewritten"));
+}
+
+#[test]
+fn test_filter_only_structured_queries_search_past_the_fetch_window() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_root = temp_dir.path().to_path_buf();
+    for i in 0..100 {
+        create_test_file(
+            &repo_root,
+            &format!("filler_{i:03}.rs"),
+            &format!("fn filler_{i}() {{}}\n"),
+        );
+    }
+    let mut indexer = Indexer::new(repo_root.clone()).unwrap();
+    indexer.index_repository(&repo_root).unwrap();
+    // Indexed last, so its document comes after every filler in the fetch order
+    create_test_file(&repo_root, "zzz_target.rs", "fn zzz_target_symbol() {}\n");
+    indexer
+        .index_file(&repo_root.join("zzz_target.rs"))
+        .unwrap();
+    indexer.commit().unwrap();
+    let paths = FlashgrepPaths::new(&repo_root);
+    let searcher = Searcher::new(indexer.tantivy_index(), &paths.metadata_db()).unwrap();
+
+    for query in [
+        "sym:zzz_target_symbol",
+        "path:zzz",
+        "lang:rust path:zzz_*",
+        "-path:filler",
+    ] {
+        let mut options = QueryOptions::new(query.to_string(), 1);
+        options.mode = QueryMode::Structured;
+        let response = searcher.query_with_options(&options).unwrap();
+        assert_eq!(response.results.len(), 1, "{}", query);
+        assert_eq!(
+            response.results[0].file_path,
+            repo_root.join("zzz_target.rs"),
+            "{}",
+            query
+        );
+    }
+}